@@ -1,6 +1,5 @@
 export { calculatorPlugin } from "./core/calculator";
 export { webSearchPlugin } from "./core/web-search";
 export { spotifyPlugin } from "./integrations/spotify";
-export { currencyPlugin } from "./integrations/currency";
-export { clipboardPlugin } from "./integrations/clipboard";
+export { clipboardPlugin, isClipboardEntry } from "./integrations/clipboard";
 export { contactsPlugin } from "./integrations/contacts";
@@ -1,10 +1,19 @@
 import { invoke } from "@tauri-apps/api/core";
 import type { GeniePlugin, SearchResult } from "../../src/types";
 
+interface ContactField {
+  value: string;
+  label?: string | null;
+}
+
 interface Contact {
   name: string;
-  email?: string;
-  phone?: string;
+  email?: string | null;
+  phone?: string | null;
+  emails?: ContactField[];
+  phones?: ContactField[];
+  organization?: string | null;
+  nicknames?: string[];
 }
 
 const CACHE_TTL = 5 * 60 * 1000;
@@ -31,14 +40,34 @@ function parseContactsQuery(raw: string): { query: string; forced: boolean } {
   return { query: trimmed, forced: false };
 }
 
+function searchableFields(contact: Contact): string[] {
+  return [
+    contact.name,
+    contact.email ?? "",
+    contact.phone ?? "",
+    contact.organization ?? "",
+    ...(contact.nicknames ?? []),
+    ...(contact.emails ?? []).map((field) => field.value),
+    ...(contact.phones ?? []).map((field) => field.value),
+  ];
+}
+
 function matchesContact(contact: Contact, query: string): boolean {
   if (!query) return true;
   const q = query.toLowerCase();
-  return (
-    contact.name.toLowerCase().includes(q) ||
-    (contact.email ?? "").toLowerCase().includes(q) ||
-    (contact.phone ?? "").toLowerCase().includes(q)
-  );
+  return searchableFields(contact).some((field) => field.toLowerCase().includes(q));
+}
+
+// NOTE: launch targets are checked against the CONTACT scheme allowlist in Rust
+function primaryTarget(contact: Contact): string {
+  if (contact.email) return `mailto:${contact.email}`;
+  if (contact.phone) return `tel:${contact.phone}`;
+  return "";
+}
+
+function contactSubtitle(contact: Contact): string {
+  const primary = contact.email || contact.phone || "Contact";
+  return contact.organization ? `${primary} · ${contact.organization}` : primary;
 }
 
 function scoreContact(contact: Contact, query: string, index: number, forced: boolean): number {
@@ -48,6 +77,9 @@ function scoreContact(contact: Contact, query: string, index: number, forced: bo
   if (!query) return baseScore - index;
   const q = query.toLowerCase();
   if (contact.name.toLowerCase().startsWith(q)) return baseScore + 160 - index;
+  if ((contact.nicknames ?? []).some((nickname) => nickname.toLowerCase().startsWith(q))) {
+    return baseScore + 120 - index;
+  }
   return baseScore + 40 - index;
 }
 
@@ -105,24 +137,32 @@ export const contactsPlugin: GeniePlugin = {
     
     if (matches.length === 0) return [];
 
-    return matches.slice(0, 8).map((contact, index) => ({
+    const results: SearchResult[] = matches.slice(0, 8).map((contact, index) => ({
       id: `contact:${contact.name}:${contact.email ?? contact.phone ?? index}`,
       title: contact.name,
-      subtitle: contact.email || contact.phone || "Contact",
+      subtitle: contactSubtitle(contact),
       category: "CONTACT",
       icon: "users",
-      action_data: contact.email || contact.phone || "",
+      action_data: primaryTarget(contact),
       score: scoreContact(contact, parsed.query, index, parsed.forced),
     }));
+
+    // List every action for the contact being looked up, right below it
+    if (parsed.forced || matches.length === 1) {
+      const top = results[0];
+      const actions = await invoke<SearchResult[]>("get_contact_actions", {
+        contact: matches[0],
+      }).catch(() => [] as SearchResult[]);
+      actions.forEach((action, index) => {
+        results.push({ ...action, score: top.score - 1 - index });
+      });
+    }
+    return results;
   },
 
   async onAction(result: SearchResult): Promise<void> {
-    if (result.action_data.includes("@")) {
-      window.location.href = `mailto:${result.action_data}`;
-      return;
-    }
     if (result.action_data) {
-      await navigator.clipboard.writeText(result.action_data);
+      await invoke("launch_item", { actionData: result.action_data, category: "CONTACT" });
     }
   },
 };
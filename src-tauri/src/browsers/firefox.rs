@@ -0,0 +1,90 @@
+use std::path::Path;
+
+pub(super) const PLACES_FILE: &str = "places.sqlite";
+
+/// `(title, url)` pairs for every bookmark in a profile's `places.sqlite`. Bookmarks
+/// without their own title fall back to the page title.
+pub(super) fn bookmarks(profile: &Path) -> Result<Vec<(String, String)>, String> {
+    super::with_snapshot(&profile.join(PLACES_FILE), |db| {
+        let mut statement = db.prepare(
+            "SELECT COALESCE(NULLIF(b.title, ''), p.title, ''), p.url
+             FROM moz_bookmarks b JOIN moz_places p ON p.id = b.fk
+             WHERE b.type = 1
+             ORDER BY b.id",
+        )?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    })
+}
+
+/// `(title, url, visit_count, last_visit)` for pages visited since `since` (Unix
+/// seconds); Firefox stores visit times in microseconds.
+pub(super) fn history(profile: &Path, since: u64) -> Result<Vec<super::HistoryRow>, String> {
+    super::with_snapshot(&profile.join(PLACES_FILE), |db| {
+        let mut statement = db.prepare(
+            "SELECT COALESCE(title, ''), url, visit_count, last_visit_date / 1000000
+             FROM moz_places
+             WHERE visit_count > 0 AND hidden = 0 AND last_visit_date >= ?1
+             ORDER BY last_visit_date DESC
+             LIMIT ?2",
+        )?;
+        let rows = statement.query_map(
+            rusqlite::params![
+                since as i64 * 1_000_000,
+                super::history::MAX_VISITS_PER_PROFILE as i64
+            ],
+            super::history_row,
+        )?;
+        rows.collect()
+    })
+}
+
+/// A minimal `places.sqlite` with the columns the readers query.
+#[cfg(test)]
+pub(super) fn write_test_places(profile: &Path) {
+    let db = rusqlite::Connection::open(profile.join(PLACES_FILE)).unwrap();
+    db.execute_batch(
+        "CREATE TABLE moz_places (
+           id INTEGER PRIMARY KEY, url TEXT, title TEXT,
+           visit_count INTEGER DEFAULT 0, last_visit_date INTEGER, hidden INTEGER DEFAULT 0
+         );
+         CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, type INTEGER, fk INTEGER, title TEXT);
+         INSERT INTO moz_places VALUES
+           (1, 'https://www.rust-lang.org/', 'Rust Programming Language', 3, 1690000000000000, 0),
+           (2, 'https://example.com/untitled', NULL, 1, 1650000000000000, 0),
+           (3, 'place:sort=8&maxResults=10', NULL, 0, NULL, 0),
+           (4, 'https://old.example/', 'Old', 2, 1500000000000000, 0),
+           (5, 'https://example.com/frame', 'Frame', 1, 1690000000000000, 1);
+         INSERT INTO moz_bookmarks VALUES
+           (1, 2, NULL, 'toolbar'),
+           (2, 1, 1, 'Rust'),
+           (3, 1, 2, ''),
+           (4, 1, 3, 'Most Visited');",
+    )
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_bookmarks_from_a_copy_of_places() {
+        let profile = super::super::test_dir("firefox-bookmarks");
+        write_test_places(&profile);
+        let entries = bookmarks(&profile).unwrap();
+        assert_eq!(
+            entries,
+            [
+                ("Rust".to_string(), "https://www.rust-lang.org/".to_string()),
+                (String::new(), "https://example.com/untitled".to_string()),
+                (
+                    "Most Visited".to_string(),
+                    "place:sort=8&maxResults=10".to_string()
+                ),
+            ]
+        );
+        assert!(bookmarks(&profile.join("missing")).is_err());
+        let _ = std::fs::remove_dir_all(profile);
+    }
+}
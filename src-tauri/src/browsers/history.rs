@@ -0,0 +1,250 @@
+use super::{chromium, firefox, BrowserKind, Profile};
+use crate::settings;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+const RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+/// Rows read per profile; older pages are rarely what a launcher search is after.
+pub(super) const MAX_VISITS_PER_PROFILE: usize = 5000;
+const MAX_RESULTS: usize = 6;
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A visited page, merged across every profile that has been to the same URL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Visit {
+    pub title: String,
+    pub url: String,
+    pub browser: &'static str,
+    pub visit_count: u64,
+    /// Unix seconds of the latest visit.
+    pub last_visit: u64,
+}
+
+/// Kept in memory only: history is never written to the config directory, and the
+/// database copies it is read from are deleted as soon as they have been read, or at
+/// the next start if the app was killed first.
+static HISTORY: OnceLock<Mutex<Vec<Visit>>> = OnceLock::new();
+static LAST_LOAD_AT_SECS: AtomicU64 = AtomicU64::new(0);
+static LOADING: AtomicBool = AtomicBool::new(false);
+
+/// Visited pages matching `query`, ranked by match quality, visit count and recency.
+/// Returns nothing (and forgets what was loaded) unless history search is enabled.
+pub fn search_history(query: &str, matcher: &SkimMatcherV2) -> Vec<(Visit, i64)> {
+    let history = HISTORY.get_or_init(|| Mutex::new(Vec::new()));
+    let config = settings::get().browser_history;
+    if !config.enabled {
+        if let Ok(mut guard) = history.lock() {
+            guard.clear();
+        }
+        LAST_LOAD_AT_SECS.store(0, Ordering::SeqCst);
+        return Vec::new();
+    }
+    let now = super::now_secs();
+    reload_if_stale(now, config.max_age_days);
+    let Ok(visits) = history.lock().map(|visits| visits.clone()) else {
+        return Vec::new();
+    };
+    rank_visits(&visits, query, matcher, now)
+}
+
+fn reload_if_stale(now: u64, max_age_days: u64) {
+    let last_load = LAST_LOAD_AT_SECS.load(Ordering::SeqCst);
+    if now.saturating_sub(last_load) < RELOAD_INTERVAL.as_secs() {
+        return;
+    }
+    if LOADING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(move || {
+        let since = now.saturating_sub(max_age_days.max(1) * SECS_PER_DAY);
+        let visits = load_visits(&super::profiles(), since);
+        // The setting may have been switched off while the profiles were being read.
+        if settings::get().browser_history.enabled {
+            if let Some(history) = HISTORY.get() {
+                if let Ok(mut guard) = history.lock() {
+                    *guard = visits;
+                }
+            }
+            LAST_LOAD_AT_SECS.store(now, Ordering::SeqCst);
+        }
+        LOADING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// Reads every profile's pages visited since `since`, merging repeat URLs by summing
+/// their visit counts and keeping the latest visit.
+fn load_visits(profiles: &[Profile], since: u64) -> Vec<Visit> {
+    let mut merged: HashMap<String, Visit> = HashMap::new();
+    for profile in profiles {
+        let rows = match profile.kind {
+            BrowserKind::Firefox => firefox::history(&profile.dir, since),
+            BrowserKind::Chromium => chromium::history(&profile.dir, since),
+        };
+        let Ok(rows) = rows else {
+            continue;
+        };
+        for (title, url, visit_count, last_visit) in rows {
+            if !super::is_web_url(&url) {
+                continue;
+            }
+            let visit = merged.entry(url.clone()).or_insert_with(|| Visit {
+                title: String::new(),
+                url,
+                browser: profile.browser,
+                visit_count: 0,
+                last_visit: 0,
+            });
+            visit.visit_count += visit_count;
+            if last_visit >= visit.last_visit {
+                visit.last_visit = last_visit;
+                visit.browser = profile.browser;
+                if !title.trim().is_empty() {
+                    visit.title = title;
+                }
+            }
+        }
+    }
+    let mut visits: Vec<Visit> = merged
+        .into_values()
+        .map(|mut visit| {
+            if visit.title.trim().is_empty() {
+                visit.title = super::display_url(&visit.url).to_string();
+            }
+            visit
+        })
+        .collect();
+    visits.sort_by_key(|visit| std::cmp::Reverse(visit.last_visit));
+    visits
+}
+
+fn rank_visits(
+    visits: &[Visit],
+    query: &str,
+    matcher: &SkimMatcherV2,
+    now: u64,
+) -> Vec<(Visit, i64)> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut matches: Vec<(Visit, i64)> = visits
+        .iter()
+        .filter_map(|visit| {
+            let title_score = matcher.fuzzy_match(&visit.title, query);
+            let url_score = matcher
+                .fuzzy_match(super::display_url(&visit.url), query)
+                .map(|score| score / 2);
+            let score = title_score.max(url_score)?;
+            Some((visit.clone(), score + usage_boost(visit, now)))
+        })
+        .collect();
+    matches.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    matches.truncate(MAX_RESULTS);
+    matches
+}
+
+/// Up to 40 points for frequently visited pages and up to 30 for pages seen this month,
+/// so between two similar matches the familiar, recent page wins.
+fn usage_boost(visit: &Visit, now: u64) -> i64 {
+    let frequency = ((visit.visit_count as f64).ln_1p() * 8.0).min(40.0) as i64;
+    let age_days = now.saturating_sub(visit.last_visit) / SECS_PER_DAY;
+    let recency = 30 - age_days.min(30) as i64;
+    frequency + recency
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn visit(title: &str, url: &str, visit_count: u64, days_ago: u64) -> Visit {
+        Visit {
+            title: title.into(),
+            url: url.into(),
+            browser: "Firefox",
+            visit_count,
+            last_visit: NOW - days_ago * SECS_PER_DAY,
+        }
+    }
+
+    #[test]
+    fn frequent_recent_pages_rank_first() {
+        let matcher = SkimMatcherV2::default();
+        let visits = vec![
+            visit(
+                "Rust issue tracker",
+                "https://github.com/rust-lang/rust/issues",
+                1,
+                90,
+            ),
+            visit(
+                "Rust issue tracker",
+                "https://github.com/rust-lang/rust/pulls",
+                40,
+                0,
+            ),
+            visit("Weather", "https://weather.example/", 100, 0),
+        ];
+        let ranked = rank_visits(&visits, "rust issue", &matcher, NOW);
+        let urls: Vec<_> = ranked.iter().map(|(v, _)| v.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://github.com/rust-lang/rust/pulls",
+                "https://github.com/rust-lang/rust/issues",
+            ]
+        );
+        assert_eq!(usage_boost(&visits[0], NOW), 5);
+        assert_eq!(usage_boost(&visits[2], NOW), 66);
+    }
+
+    #[test]
+    fn merges_visits_across_profiles() {
+        let root = super::super::test_dir("history");
+        let firefox_dir = root.join("firefox");
+        let chrome_dir = root.join("Default");
+        std::fs::create_dir_all(&firefox_dir).unwrap();
+        std::fs::create_dir_all(&chrome_dir).unwrap();
+        firefox::write_test_places(&firefox_dir);
+        chromium::write_test_history(&chrome_dir);
+        let profiles = [
+            Profile {
+                browser: "Firefox",
+                kind: BrowserKind::Firefox,
+                dir: firefox_dir,
+            },
+            Profile {
+                browser: "Chrome",
+                kind: BrowserKind::Chromium,
+                dir: chrome_dir,
+            },
+        ];
+
+        let visits = load_visits(&profiles, 1_600_000_000);
+        assert_eq!(
+            visits,
+            [
+                Visit {
+                    title: "Rust".into(),
+                    url: "https://www.rust-lang.org/".into(),
+                    browser: "Chrome",
+                    visit_count: 7,
+                    last_visit: 1_700_000_000,
+                },
+                Visit {
+                    title: "example.com/untitled".into(),
+                    url: "https://example.com/untitled".into(),
+                    browser: "Firefox",
+                    visit_count: 1,
+                    last_visit: 1_650_000_000,
+                },
+            ]
+        );
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
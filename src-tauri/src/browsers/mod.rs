@@ -0,0 +1,515 @@
+mod chromium;
+mod firefox;
+mod history;
+
+pub use history::search_history;
+
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Bookmarks change rarely; re-reading every profile on each keystroke would be wasteful.
+const RELOAD_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const MAX_RESULTS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowserKind {
+    Firefox,
+    Chromium,
+}
+
+/// One browser profile directory on disk, e.g. `~/.mozilla/firefox/abcd.default-release`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub browser: &'static str,
+    pub kind: BrowserKind,
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub title: String,
+    pub url: String,
+    pub browser: &'static str,
+}
+
+static BOOKMARKS: OnceLock<Mutex<Vec<Bookmark>>> = OnceLock::new();
+static LAST_LOAD_AT_SECS: AtomicU64 = AtomicU64::new(0);
+static LOADING: AtomicBool = AtomicBool::new(false);
+
+/// Starts reading bookmarks in the background so the first search does not wait on disk.
+pub fn init() {
+    let _ = BOOKMARKS.set(Mutex::new(Vec::new()));
+    reload_if_stale();
+}
+
+/// Bookmarks whose title or URL fuzzy-matches `query`, best first.
+pub fn search_bookmarks(query: &str, matcher: &SkimMatcherV2) -> Vec<(Bookmark, i64)> {
+    reload_if_stale();
+    let Some(bookmarks) = BOOKMARKS
+        .get()
+        .and_then(|b| b.lock().ok().map(|b| b.clone()))
+    else {
+        return Vec::new();
+    };
+    rank_bookmarks(&bookmarks, query, matcher)
+}
+
+fn rank_bookmarks(
+    bookmarks: &[Bookmark],
+    query: &str,
+    matcher: &SkimMatcherV2,
+) -> Vec<(Bookmark, i64)> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut matches: Vec<(Bookmark, i64)> = bookmarks
+        .iter()
+        .filter_map(|bookmark| {
+            let title_score = matcher.fuzzy_match(&bookmark.title, query);
+            // URL hits count for less so a matching title wins over a matching path.
+            let url_score = matcher
+                .fuzzy_match(display_url(&bookmark.url), query)
+                .map(|score| score / 2);
+            let score = title_score.max(url_score)?;
+            Some((bookmark.clone(), score))
+        })
+        .collect();
+    matches.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    matches.truncate(MAX_RESULTS);
+    matches
+}
+
+/// The URL without its scheme, which is noise both for matching and for display.
+pub fn display_url(url: &str) -> &str {
+    url.strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url)
+}
+
+fn reload_if_stale() {
+    let now = now_secs();
+    let last_load = LAST_LOAD_AT_SECS.load(Ordering::SeqCst);
+    if now.saturating_sub(last_load) < RELOAD_INTERVAL.as_secs() {
+        return;
+    }
+    if LOADING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(move || {
+        let bookmarks = load_bookmarks(&profiles());
+        if let Some(store) = BOOKMARKS.get() {
+            if let Ok(mut guard) = store.lock() {
+                *guard = bookmarks;
+            }
+        }
+        LAST_LOAD_AT_SECS.store(now, Ordering::SeqCst);
+        LOADING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// Reads every profile, skipping unreadable ones and keeping the first copy of each URL.
+fn load_bookmarks(profiles: &[Profile]) -> Vec<Bookmark> {
+    let mut seen = HashSet::new();
+    let mut bookmarks = Vec::new();
+    for profile in profiles {
+        let entries = match profile.kind {
+            BrowserKind::Firefox => firefox::bookmarks(&profile.dir),
+            BrowserKind::Chromium => chromium::bookmarks(&profile.dir),
+        };
+        let Ok(entries) = entries else {
+            continue;
+        };
+        for (title, url) in entries {
+            if !is_web_url(&url) || !seen.insert(url.clone()) {
+                continue;
+            }
+            let title = if title.trim().is_empty() {
+                display_url(&url).to_string()
+            } else {
+                title
+            };
+            bookmarks.push(Bookmark {
+                title,
+                url,
+                browser: profile.browser,
+            });
+        }
+    }
+    bookmarks
+}
+
+fn is_web_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+/// All browser profiles found on this machine.
+pub fn profiles() -> Vec<Profile> {
+    profiles_in(&browser_roots())
+}
+
+/// Firefox roots hold one directory per profile; Chromium user-data directories hold
+/// `Default` and `Profile N`.
+fn profiles_in(roots: &[(&'static str, BrowserKind, PathBuf)]) -> Vec<Profile> {
+    let mut profiles = Vec::new();
+    for (browser, kind, root) in roots {
+        let Ok(entries) = std::fs::read_dir(root) else {
+            continue;
+        };
+        let mut dirs: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir() && is_profile_dir(*kind, path))
+            .collect();
+        dirs.sort();
+        profiles.extend(dirs.into_iter().map(|dir| Profile {
+            browser,
+            kind: *kind,
+            dir,
+        }));
+    }
+    profiles
+}
+
+fn is_profile_dir(kind: BrowserKind, path: &Path) -> bool {
+    match kind {
+        BrowserKind::Firefox => path.join(firefox::PLACES_FILE).is_file(),
+        BrowserKind::Chromium => path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name == "Default" || name.starts_with("Profile ")),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn browser_roots() -> Vec<(&'static str, BrowserKind, PathBuf)> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let config = dirs::config_dir().unwrap_or_else(|| home.join(".config"));
+    vec![
+        (
+            "Firefox",
+            BrowserKind::Firefox,
+            home.join(".mozilla/firefox"),
+        ),
+        (
+            "Firefox",
+            BrowserKind::Firefox,
+            home.join("snap/firefox/common/.mozilla/firefox"),
+        ),
+        (
+            "Firefox",
+            BrowserKind::Firefox,
+            home.join(".var/app/org.mozilla.firefox/.mozilla/firefox"),
+        ),
+        (
+            "Chrome",
+            BrowserKind::Chromium,
+            config.join("google-chrome"),
+        ),
+        ("Chromium", BrowserKind::Chromium, config.join("chromium")),
+        (
+            "Chromium",
+            BrowserKind::Chromium,
+            home.join("snap/chromium/common/chromium"),
+        ),
+        (
+            "Brave",
+            BrowserKind::Chromium,
+            config.join("BraveSoftware/Brave-Browser"),
+        ),
+        ("Edge", BrowserKind::Chromium, config.join("microsoft-edge")),
+        ("Vivaldi", BrowserKind::Chromium, config.join("vivaldi")),
+    ]
+}
+
+#[cfg(target_os = "macos")]
+fn browser_roots() -> Vec<(&'static str, BrowserKind, PathBuf)> {
+    let support = dirs::home_dir()
+        .unwrap_or_default()
+        .join("Library/Application Support");
+    vec![
+        (
+            "Firefox",
+            BrowserKind::Firefox,
+            support.join("Firefox/Profiles"),
+        ),
+        (
+            "Chrome",
+            BrowserKind::Chromium,
+            support.join("Google/Chrome"),
+        ),
+        ("Chromium", BrowserKind::Chromium, support.join("Chromium")),
+        (
+            "Brave",
+            BrowserKind::Chromium,
+            support.join("BraveSoftware/Brave-Browser"),
+        ),
+        (
+            "Edge",
+            BrowserKind::Chromium,
+            support.join("Microsoft Edge"),
+        ),
+        ("Vivaldi", BrowserKind::Chromium, support.join("Vivaldi")),
+    ]
+}
+
+#[cfg(target_os = "windows")]
+fn browser_roots() -> Vec<(&'static str, BrowserKind, PathBuf)> {
+    let roaming = dirs::config_dir().unwrap_or_default();
+    let local = dirs::data_local_dir().unwrap_or_default();
+    vec![
+        (
+            "Firefox",
+            BrowserKind::Firefox,
+            roaming.join("Mozilla/Firefox/Profiles"),
+        ),
+        (
+            "Chrome",
+            BrowserKind::Chromium,
+            local.join("Google/Chrome/User Data"),
+        ),
+        (
+            "Chromium",
+            BrowserKind::Chromium,
+            local.join("Chromium/User Data"),
+        ),
+        (
+            "Brave",
+            BrowserKind::Chromium,
+            local.join("BraveSoftware/Brave-Browser/User Data"),
+        ),
+        (
+            "Edge",
+            BrowserKind::Chromium,
+            local.join("Microsoft/Edge/User Data"),
+        ),
+        (
+            "Vivaldi",
+            BrowserKind::Chromium,
+            local.join("Vivaldi/User Data"),
+        ),
+    ]
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn browser_roots() -> Vec<(&'static str, BrowserKind, PathBuf)> {
+    Vec::new()
+}
+
+/// `(title, url, visit_count, last_visit)` with the last visit in Unix seconds.
+type HistoryRow = (String, String, u64, u64);
+
+fn history_row(row: &rusqlite::Row) -> rusqlite::Result<HistoryRow> {
+    let visit_count: i64 = row.get(2)?;
+    let last_visit: i64 = row.get(3)?;
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        visit_count.max(0) as u64,
+        last_visit.max(0) as u64,
+    ))
+}
+
+/// Opens a private copy of a browser database. Running browsers hold a lock on the
+/// live file, so it is copied (with its write-ahead log) and read from the copy. The
+/// copy lives in a fresh owner-only directory in the user's cache, never in the shared
+/// temp dir, and is removed however the read ends.
+fn with_snapshot<T>(
+    db: &Path,
+    read: impl FnOnce(&rusqlite::Connection) -> rusqlite::Result<T>,
+) -> Result<T, String> {
+    let dir = SnapshotDir::create()?;
+    let copy = dir.0.join("snapshot.sqlite");
+    std::fs::copy(db, &copy).map_err(|e| format!("Failed to copy {}: {}", db.display(), e))?;
+    let wal = PathBuf::from(format!("{}-wal", db.display()));
+    if wal.is_file() {
+        let _ = std::fs::copy(&wal, dir.0.join("snapshot.sqlite-wal"));
+    }
+    let connection = rusqlite::Connection::open(&copy).map_err(|e| e.to_string())?;
+    let result = read(&connection).map_err(|e| e.to_string());
+    drop(connection);
+    result
+}
+
+/// A directory only this user can enter, deleted with everything in it on drop.
+struct SnapshotDir(PathBuf);
+
+impl SnapshotDir {
+    fn create() -> Result<Self, String> {
+        static NEXT_SNAPSHOT: AtomicU64 = AtomicU64::new(0);
+        static SWEEP: std::sync::Once = std::sync::Once::new();
+        let root = dirs::cache_dir()
+            .ok_or("No cache directory for browser snapshots")?
+            .join("spotlight-genie")
+            .join("browser-snapshots");
+        std::fs::create_dir_all(&root).map_err(|e| e.to_string())?;
+        SWEEP.call_once(|| remove_left_over_snapshots(&root));
+        let dir = root.join(format!(
+            "{}-{}-{}",
+            std::process::id(),
+            now_secs(),
+            NEXT_SNAPSHOT.fetch_add(1, Ordering::SeqCst)
+        ));
+        let mut builder = std::fs::DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        // NOTE: `create` fails if the directory already exists rather than reusing it
+        builder
+            .create(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        Ok(Self(dir))
+    }
+}
+
+/// Copies left by an earlier run that was killed mid-read; they may hold full browsing
+/// history, so they are not left lying around until the cache is cleared.
+fn remove_left_over_snapshots(root: &Path) {
+    let own_prefix = format!("{}-", std::process::id());
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if !entry.file_name().to_string_lossy().starts_with(&own_prefix) {
+            let _ = std::fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+impl Drop for SnapshotDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+pub(crate) fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("genie-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(title: &str, url: &str) -> Bookmark {
+        Bookmark {
+            title: title.into(),
+            url: url.into(),
+            browser: "Firefox",
+        }
+    }
+
+    #[test]
+    fn snapshot_dirs_are_private_and_removed() {
+        let dir = SnapshotDir::create().unwrap();
+        let path = dir.0.clone();
+        assert!(path.is_dir());
+        assert!(!path.starts_with(std::env::temp_dir()));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        std::fs::write(path.join("snapshot.sqlite"), b"").unwrap();
+        drop(dir);
+        assert!(!path.exists());
+        assert!(with_snapshot(Path::new("/nonexistent/places.sqlite"), |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn left_over_snapshots_from_other_runs_are_removed() {
+        let root = test_dir("snapshots");
+        let stale = root.join("1-1700000000-0");
+        let own = root.join(format!("{}-1700000000-1", std::process::id()));
+        std::fs::create_dir_all(&stale).unwrap();
+        std::fs::write(stale.join("snapshot.sqlite"), b"history").unwrap();
+        std::fs::create_dir_all(&own).unwrap();
+        remove_left_over_snapshots(&root);
+        assert!(!stale.exists());
+        assert!(own.exists());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn ranks_title_matches_above_url_matches() {
+        let matcher = SkimMatcherV2::default();
+        let bookmarks = vec![
+            bookmark("Release notes", "https://example.com/rust"),
+            bookmark("Rust Blog", "https://blog.rust-lang.org/"),
+            bookmark("Weather", "https://weather.example/"),
+        ];
+        let ranked = rank_bookmarks(&bookmarks, "rust", &matcher);
+        let titles: Vec<_> = ranked.iter().map(|(b, _)| b.title.as_str()).collect();
+        assert_eq!(titles, ["Rust Blog", "Release notes"]);
+        assert!(rank_bookmarks(&bookmarks, "  ", &matcher).is_empty());
+    }
+
+    #[test]
+    fn discovers_profiles_and_dedupes_bookmarks() {
+        let root = test_dir("browsers");
+        let firefox_root = root.join("firefox");
+        let chrome_root = root.join("chrome");
+        std::fs::create_dir_all(firefox_root.join("abcd.default")).unwrap();
+        std::fs::create_dir_all(firefox_root.join("Crash Reports")).unwrap();
+        std::fs::create_dir_all(chrome_root.join("Default")).unwrap();
+        std::fs::create_dir_all(chrome_root.join("System Profile")).unwrap();
+        firefox::write_test_places(&firefox_root.join("abcd.default"));
+        std::fs::write(
+            chrome_root.join("Default/Bookmarks"),
+            chromium::TEST_BOOKMARKS_JSON,
+        )
+        .unwrap();
+
+        let found = profiles_in(&[
+            ("Firefox", BrowserKind::Firefox, firefox_root.clone()),
+            ("Chrome", BrowserKind::Chromium, chrome_root.clone()),
+            ("Edge", BrowserKind::Chromium, root.join("missing")),
+        ]);
+        assert_eq!(
+            found,
+            vec![
+                Profile {
+                    browser: "Firefox",
+                    kind: BrowserKind::Firefox,
+                    dir: firefox_root.join("abcd.default"),
+                },
+                Profile {
+                    browser: "Chrome",
+                    kind: BrowserKind::Chromium,
+                    dir: chrome_root.join("Default"),
+                },
+            ]
+        );
+
+        let bookmarks = load_bookmarks(&found);
+        let urls: Vec<_> = bookmarks
+            .iter()
+            .map(|b| (b.browser, b.url.as_str()))
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                ("Firefox", "https://www.rust-lang.org/"),
+                ("Firefox", "https://example.com/untitled"),
+                ("Chrome", "https://docs.rs/"),
+            ]
+        );
+        assert_eq!(bookmarks[1].title, "example.com/untitled");
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
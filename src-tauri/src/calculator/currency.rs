@@ -0,0 +1,175 @@
+use super::expression;
+use super::units::SEPARATORS;
+use crate::currency::RatesTable;
+
+const CODE_LEN: usize = 3;
+/// Optional prefix kept from the old webview plugin; it also allows `cc 100 usd eur`.
+const KEYWORD: &str = "cc ";
+
+#[derive(Debug)]
+pub(super) struct CurrencyConversion {
+    pub amount: f64,
+    pub from: String,
+    pub to: String,
+    /// Units of `to` per one `from`.
+    pub rate: f64,
+    /// Trailing arithmetic applied to the converted amount, e.g. `* 2`.
+    pub tail: String,
+    pub result: f64,
+}
+
+/// Parses queries like `100 usd to eur`, `eur in jpy` or `100 usd to eur * 2`.
+/// Only ISO codes present in `rates` are accepted, so unit queries are left alone.
+pub(super) fn parse_conversion(query: &str, rates: &RatesTable) -> Option<CurrencyConversion> {
+    let lowered = query.trim().to_lowercase();
+    let (lowered, keyword) = match lowered.strip_prefix(KEYWORD) {
+        Some(rest) => (rest.trim().to_string(), true),
+        None => (lowered, false),
+    };
+    let mut split_points: Vec<(usize, usize)> = SEPARATORS
+        .iter()
+        .flat_map(|separator| {
+            lowered
+                .match_indices(separator)
+                .map(|(index, matched)| (index, matched.len()))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    split_points.sort_by_key(|(index, _)| std::cmp::Reverse(*index));
+    if keyword {
+        if let Some(index) = lowered.rfind(' ') {
+            split_points.push((index, 1));
+        }
+    }
+
+    split_points.into_iter().find_map(|(index, len)| {
+        let (value_expr, from) = split_amount_and_code(lowered[..index].trim(), rates)?;
+        let (to, tail) = split_code_and_tail(lowered[index + len..].trim(), rates)?;
+        let amount = if value_expr.is_empty() {
+            1.0
+        } else {
+            expression::evaluate_expression(value_expr)?
+        };
+        let rate = rates.rate(from, to)?;
+        let converted = amount * rate;
+        let result = if tail.is_empty() {
+            converted
+        } else {
+            expression::evaluate_expression(&format!("({:?}){}", converted, tail))?
+        };
+        result.is_finite().then(|| CurrencyConversion {
+            amount,
+            from: from.to_ascii_uppercase(),
+            to: to.to_ascii_uppercase(),
+            rate,
+            tail: tail.trim().to_string(),
+            result,
+        })
+    })
+}
+
+/// Every conversion names two codes, e.g. `usd` and `eur` in `2usd->eur`; checked
+/// before the rates are needed.
+pub(super) fn could_be_conversion(query: &str) -> bool {
+    query
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|word| word.len() == CODE_LEN)
+        .take(2)
+        .count()
+        == 2
+}
+
+/// Splits `100 usd` / `100usd` / `(2+3) usd` into the amount expression and the code.
+fn split_amount_and_code<'a>(text: &'a str, rates: &RatesTable) -> Option<(&'a str, &'a str)> {
+    let split = text.len().checked_sub(CODE_LEN)?;
+    let (value_expr, code) = (text.get(..split)?, text.get(split..)?);
+    let standalone = !value_expr.ends_with(|c: char| c.is_alphabetic());
+    (standalone && is_code(code, rates)).then(|| (value_expr.trim(), code))
+}
+
+/// Splits `eur * 2` into the target code and the arithmetic that follows it.
+fn split_code_and_tail<'a>(text: &'a str, rates: &RatesTable) -> Option<(&'a str, &'a str)> {
+    let (code, tail) = (text.get(..CODE_LEN)?, text.get(CODE_LEN..)?);
+    let tail_ok = tail.is_empty()
+        || tail
+            .trim_start()
+            .starts_with(['+', '-', '*', '/', '^', '%']);
+    (tail_ok && is_code(code, rates)).then_some((code, tail))
+}
+
+fn is_code(text: &str, rates: &RatesTable) -> bool {
+    text.chars().all(|c| c.is_ascii_alphabetic()) && rates.has_currency(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn rates() -> RatesTable {
+        RatesTable {
+            base: "USD".into(),
+            fetched_at: 0,
+            rates: HashMap::from([("EUR".into(), 0.5), ("JPY".into(), 150.0)]),
+        }
+    }
+
+    #[test]
+    fn converts_between_known_codes() {
+        let conversion = parse_conversion("100 usd to eur", &rates()).unwrap();
+        assert_eq!(conversion.from, "USD");
+        assert_eq!(conversion.to, "EUR");
+        assert_eq!(conversion.result, 50.0);
+        assert_eq!(
+            parse_conversion("2EUR->jpy", &rates()).unwrap().result,
+            600.0
+        );
+        assert_eq!(
+            parse_conversion("eur in usd", &rates()).unwrap().result,
+            2.0
+        );
+        assert_eq!(
+            parse_conversion("(10 + 10) usd as eur", &rates())
+                .unwrap()
+                .result,
+            10.0
+        );
+    }
+
+    #[test]
+    fn applies_trailing_arithmetic_to_the_converted_amount() {
+        let conversion = parse_conversion("100 usd to eur * 2", &rates()).unwrap();
+        assert_eq!(conversion.result, 100.0);
+        assert_eq!(conversion.tail, "* 2");
+        assert_eq!(
+            parse_conversion("100 usd to eur - 5", &rates())
+                .unwrap()
+                .result,
+            45.0
+        );
+        assert!(parse_conversion("100 usd to eur extra", &rates()).is_none());
+        assert_eq!(
+            parse_conversion("cc 3 eur usd", &rates()).unwrap().result,
+            6.0
+        );
+        assert!(parse_conversion("3 eur usd", &rates()).is_none());
+    }
+
+    #[test]
+    fn ignores_unknown_codes_and_words() {
+        assert!(parse_conversion("100 usd to xyz", &rates()).is_none());
+        assert!(parse_conversion("5 km to mi", &rates()).is_none());
+        assert!(parse_conversion("time in tokyo", &rates()).is_none());
+        assert!(parse_conversion("pauseur to eur", &rates()).is_none());
+    }
+
+    #[test]
+    fn only_queries_with_two_codes_could_be_conversions() {
+        assert!(could_be_conversion("100 usd to eur"));
+        assert!(could_be_conversion("2EUR->jpy"));
+        assert!(could_be_conversion("cc 3 eur usd"));
+        assert!(!could_be_conversion("firefox"));
+        assert!(!could_be_conversion("5 km to mi"));
+        assert!(!could_be_conversion("100 usd"));
+    }
+}
@@ -0,0 +1,396 @@
+use super::Answer;
+use jiff::civil::{Date, DateTime};
+use jiff::tz::{self, TimeZone};
+use jiff::{Span, Timestamp, Zoned};
+
+const ISO_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+const ISO_DATE_FORMAT: &str = "%Y-%m-%d";
+const HUMAN_FORMAT: &str = "%A, %-d %B %Y at %H:%M %Z";
+const HUMAN_DATE_FORMAT: &str = "%A, %-d %B %Y";
+/// Unix timestamps above this are read as milliseconds (it is year 5138 in seconds).
+const MAX_UNIX_SECONDS: i64 = 99_999_999_999;
+
+/// A point in time parsed from the query. Date-only moments (`today`, `2026-12-25`)
+/// sit at local midnight and are printed without a time of day.
+struct Moment {
+    zoned: Zoned,
+    date_only: bool,
+}
+
+/// Evaluates date/time queries relative to `now`; returns no answers for anything else.
+pub(super) fn evaluate(query: &str, now: &Zoned) -> Vec<Answer> {
+    let text = query.trim().to_lowercase();
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let label = query.trim();
+    unix_timestamp(&text, now, label)
+        .or_else(|| days_between(&text, now))
+        .or_else(|| week_number(&text, now))
+        .or_else(|| time_in_zone(&text, now, label))
+        .or_else(|| parse_moment(&text, now).map(|moment| moment_answers(&moment, label)))
+        .unwrap_or_default()
+}
+
+/// `unix 1700000000` converts a timestamp; `unix`, `unix now` and `<moment> to unix`
+/// produce one.
+fn unix_timestamp(text: &str, now: &Zoned, label: &str) -> Option<Vec<Answer>> {
+    if let Some(rest) = text.strip_prefix("unix") {
+        let rest = rest.trim();
+        if let Ok(number) = rest.parse::<i64>() {
+            let timestamp = if number.abs() > MAX_UNIX_SECONDS {
+                Timestamp::from_millisecond(number).ok()?
+            } else {
+                Timestamp::from_second(number).ok()?
+            };
+            let local = timestamp.to_zoned(now.time_zone().clone());
+            let human = local.strftime(HUMAN_FORMAT).to_string();
+            let iso = timestamp.to_string();
+            return Some(vec![
+                answer(iso, format!("ISO 8601 (UTC) · {}", label)),
+                answer(human, label.to_string()),
+            ]);
+        }
+        if !rest.is_empty() && rest != "now" {
+            return None;
+        }
+        return Some(vec![unix_answer(now, label)]);
+    }
+    let moment = [" to unix", " in unix"]
+        .iter()
+        .find_map(|suffix| text.strip_suffix(suffix))
+        .and_then(|base| parse_moment(base.trim(), now))?;
+    Some(vec![unix_answer(&moment.zoned, label)])
+}
+
+fn unix_answer(zoned: &Zoned, label: &str) -> Answer {
+    answer(
+        zoned.timestamp().as_second().to_string(),
+        format!(
+            "Unix timestamp · {} · {}",
+            zoned.strftime(HUMAN_FORMAT),
+            label
+        ),
+    )
+}
+
+/// `days until 2026-12-25` / `days since 2020-01-01`, counted in calendar days.
+fn days_between(text: &str, now: &Zoned) -> Option<Vec<Answer>> {
+    let (direction, target) = ["until", "till", "to", "since"]
+        .iter()
+        .find_map(|direction| {
+            let target = text.strip_prefix("days ")?.strip_prefix(direction)?;
+            target
+                .starts_with(' ')
+                .then_some((*direction, target.trim()))
+        })?;
+    let moment = parse_moment(target, now)?;
+    let mut days = now.date().until(moment.zoned.date()).ok()?.get_days();
+    if direction == "since" {
+        days = -days;
+    }
+    let unit = if days.abs() == 1 { "day" } else { "days" };
+    let weeks = days.abs() / 7;
+    let breakdown = if weeks > 0 {
+        format!(" · {} weeks, {} days", weeks, days.abs() % 7)
+    } else {
+        String::new()
+    };
+    Some(vec![Answer {
+        title: format!("{} {}", days, unit),
+        subtitle: format!(
+            "{} {}{}",
+            direction,
+            moment.zoned.strftime(HUMAN_DATE_FORMAT),
+            breakdown
+        ),
+        value: days.to_string(),
+    }])
+}
+
+/// `2026-10-17 in week number`, `week number of tomorrow` or plain `week number`.
+fn week_number(text: &str, now: &Zoned) -> Option<Vec<Answer>> {
+    let base = if text == "week number" {
+        "today"
+    } else {
+        [" in week number", " week number"]
+            .iter()
+            .find_map(|suffix| text.strip_suffix(suffix))
+            .or_else(|| text.strip_prefix("week number of "))?
+            .trim()
+    };
+    let date = parse_moment(base, now)?.zoned.date();
+    let week = date.iso_week_date();
+    let iso = format!(
+        "{}-W{:02}-{}",
+        week.year(),
+        week.week(),
+        week.weekday().to_monday_one_offset()
+    );
+    Some(vec![
+        Answer {
+            title: format!("Week {}", week.week()),
+            subtitle: format!("ISO week of {}", date.strftime(HUMAN_DATE_FORMAT)),
+            value: week.week().to_string(),
+        },
+        answer(iso, "ISO 8601 week date".into()),
+    ])
+}
+
+/// `time in Tokyo`, `now in utc` or `2026-12-25 09:00 in new york`.
+fn time_in_zone(text: &str, now: &Zoned, label: &str) -> Option<Vec<Answer>> {
+    let (base, zone) = text.rsplit_once(" in ")?;
+    let time_zone = find_time_zone(zone.trim())?;
+    let base = base.trim();
+    let zoned = if base == "time" {
+        now.clone()
+    } else {
+        parse_moment(base, now)?.zoned
+    };
+    let converted = zoned.with_time_zone(time_zone);
+    let zone_name = converted
+        .time_zone()
+        .iana_name()
+        .unwrap_or("UTC")
+        .to_string();
+    Some(vec![
+        answer(
+            converted.strftime("%H:%M %Z · %A, %-d %B").to_string(),
+            format!("{} · {}", zone_name, label),
+        ),
+        answer(
+            converted.strftime(ISO_FORMAT).to_string(),
+            format!("ISO 8601 · {}", zone_name),
+        ),
+    ])
+}
+
+/// Matches IANA names (`asia/tokyo`) or their city part (`tokyo`, `new york`)
+/// against the bundled tz database.
+fn find_time_zone(name: &str) -> Option<TimeZone> {
+    if matches!(name, "utc" | "gmt" | "z") {
+        return Some(TimeZone::UTC);
+    }
+    let wanted = name.replace(' ', "_");
+    let database = tz::db();
+    let found = database.available().find(|candidate| {
+        let candidate = candidate.as_str();
+        candidate.eq_ignore_ascii_case(&wanted)
+            || candidate
+                .rsplit('/')
+                .next()
+                .is_some_and(|city| city.eq_ignore_ascii_case(&wanted))
+    })?;
+    database.get(found.as_str()).ok()
+}
+
+/// Parses a base moment followed by optional `+ 3 weeks - 2 days` style offsets.
+fn parse_moment(text: &str, now: &Zoned) -> Option<Moment> {
+    if let Some(moment) = parse_base(text, now) {
+        return Some(moment);
+    }
+    // NOTE: the base may contain `-` itself (`2026-12-25`), so try every sign position
+    text.char_indices()
+        .filter(|(_, c)| *c == '+' || *c == '-')
+        .find_map(|(index, _)| {
+            let base = parse_base(text[..index].trim(), now)?;
+            apply_offsets(base, &text[index..])
+        })
+}
+
+fn parse_base(text: &str, now: &Zoned) -> Option<Moment> {
+    let today = || now.date().to_zoned(now.time_zone().clone()).ok();
+    let date_moment = |zoned: Option<Zoned>| {
+        zoned.map(|zoned| Moment {
+            zoned,
+            date_only: true,
+        })
+    };
+    match text {
+        "now" => {
+            return Some(Moment {
+                zoned: now.clone(),
+                date_only: false,
+            })
+        }
+        "today" => return date_moment(today()),
+        "tomorrow" => return date_moment(today()?.tomorrow().ok()),
+        "yesterday" => return date_moment(today()?.yesterday().ok()),
+        _ => {}
+    }
+    if !text.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    if text.contains(':') {
+        let datetime = text.parse::<DateTime>().ok()?;
+        return Some(Moment {
+            zoned: datetime.to_zoned(now.time_zone().clone()).ok()?,
+            date_only: false,
+        });
+    }
+    let date = text.parse::<Date>().ok()?;
+    date_moment(date.to_zoned(now.time_zone().clone()).ok())
+}
+
+fn apply_offsets(mut moment: Moment, text: &str) -> Option<Moment> {
+    let mut rest = text.trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let negative = match rest.chars().next()? {
+            '+' => false,
+            '-' => true,
+            _ => return None,
+        };
+        rest = rest[1..].trim_start();
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        let amount: i64 = rest[..digits].parse().ok()?;
+        rest = rest[digits..].trim_start();
+        let unit_len = rest
+            .chars()
+            .take_while(|c| c.is_alphabetic())
+            .map(char::len_utf8)
+            .sum();
+        let (span, has_time) =
+            offset_span(&rest[..unit_len], if negative { -amount } else { amount })?;
+        rest = rest[unit_len..].trim_start();
+        moment.zoned = moment.zoned.checked_add(span).ok()?;
+        moment.date_only &= !has_time;
+    }
+    Some(moment)
+}
+
+/// Returns the span for `amount` of `unit`, and whether it moves the time of day.
+fn offset_span(unit: &str, amount: i64) -> Option<(Span, bool)> {
+    let span = Span::new();
+    let result = match unit {
+        "y" | "yr" | "yrs" | "year" | "years" => (span.try_years(amount).ok()?, false),
+        "mo" | "month" | "months" => (span.try_months(amount).ok()?, false),
+        "w" | "wk" | "wks" | "week" | "weeks" => (span.try_weeks(amount).ok()?, false),
+        "d" | "day" | "days" => (span.try_days(amount).ok()?, false),
+        "h" | "hr" | "hrs" | "hour" | "hours" => (span.try_hours(amount).ok()?, true),
+        "m" | "min" | "mins" | "minute" | "minutes" => (span.try_minutes(amount).ok()?, true),
+        "s" | "sec" | "secs" | "second" | "seconds" => (span.try_seconds(amount).ok()?, true),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn moment_answers(moment: &Moment, label: &str) -> Vec<Answer> {
+    let (iso_format, human_format) = if moment.date_only {
+        (ISO_DATE_FORMAT, HUMAN_DATE_FORMAT)
+    } else {
+        (ISO_FORMAT, HUMAN_FORMAT)
+    };
+    vec![
+        answer(
+            moment.zoned.strftime(iso_format).to_string(),
+            format!("ISO 8601 · {}", label),
+        ),
+        answer(
+            moment.zoned.strftime(human_format).to_string(),
+            label.to_string(),
+        ),
+    ]
+}
+
+fn answer(title: String, subtitle: String) -> Answer {
+    Answer {
+        value: title.clone(),
+        title,
+        subtitle,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::date;
+
+    fn now() -> Zoned {
+        date(2026, 10, 18)
+            .at(14, 30, 0, 0)
+            .in_tz("Europe/Berlin")
+            .unwrap()
+    }
+
+    fn titles(query: &str) -> Vec<String> {
+        evaluate(query, &now())
+            .into_iter()
+            .map(|answer| answer.title)
+            .collect()
+    }
+
+    #[test]
+    fn adds_offsets_to_now_and_dates() {
+        assert_eq!(
+            titles("now + 3 weeks"),
+            vec![
+                "2026-11-08T14:30:00+01:00",
+                "Sunday, 8 November 2026 at 14:30 CET"
+            ]
+        );
+        assert_eq!(titles("today - 1 day")[0], "2026-10-17");
+        assert_eq!(titles("2026-12-25 + 1w - 2d")[0], "2026-12-30");
+        assert_eq!(titles("tomorrow + 2h")[0], "2026-10-19T02:00:00+02:00");
+        assert_eq!(titles("2026-12-25")[1], "Friday, 25 December 2026");
+    }
+
+    #[test]
+    fn counts_days_between_dates() {
+        let answers = evaluate("days until 2026-12-25", &now());
+        assert_eq!(answers[0].title, "68 days");
+        assert_eq!(answers[0].value, "68");
+        assert!(answers[0].subtitle.contains("9 weeks, 5 days"));
+        assert_eq!(titles("days since 2026-10-17"), vec!["1 day"]);
+    }
+
+    #[test]
+    fn converts_unix_timestamps() {
+        assert_eq!(titles("unix 1700000000")[0], "2023-11-14T22:13:20Z");
+        assert_eq!(titles("unix 1700000000000")[0], "2023-11-14T22:13:20Z");
+        assert_eq!(titles("unix now"), vec!["1792326600"]);
+        assert_eq!(titles("2023-11-14 23:13:20 to unix"), vec!["1700000000"]);
+    }
+
+    #[test]
+    fn reports_iso_week_numbers() {
+        assert_eq!(
+            titles("2026-10-17 in week number"),
+            vec!["Week 42", "2026-W42-6"]
+        );
+        assert_eq!(titles("week number")[0], "Week 42");
+        assert_eq!(titles("week number of 2027-01-01")[1], "2026-W53-5");
+    }
+
+    #[test]
+    fn converts_between_time_zones() {
+        let answers = titles("time in Tokyo");
+        assert_eq!(answers[0], "21:30 JST · Sunday, 18 October");
+        assert_eq!(answers[1], "2026-10-18T21:30:00+09:00");
+        assert_eq!(titles("now in utc")[1], "2026-10-18T12:30:00+00:00");
+        assert_eq!(
+            titles("2026-12-25 09:00 in new york")[0],
+            "03:00 EST · Friday, 25 December"
+        );
+    }
+
+    #[test]
+    fn ignores_unrelated_queries() {
+        for query in [
+            "snow",
+            "nowhere",
+            "days until foo",
+            "time in atlantis",
+            "unix socket",
+            "5 + 3",
+            "today + 3 parsecs",
+        ] {
+            assert!(
+                evaluate(query, &now()).is_empty(),
+                "{query} should not match"
+            );
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use super::expression::{self, CalcToken, Function};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+/// Largest `10^n` accepted in a literal before falling back to `f64`.
+const MAX_LITERAL_EXPONENT: u32 = 1_000;
+/// Size cap for `^` results so inputs like `9^9^9` fall back instead of stalling.
+const MAX_POWER_BITS: u64 = 65_536;
+const MAX_FACTORIAL: u64 = 1_000;
+const MAX_SHIFT: usize = 4_096;
+
+/// Parses a decimal literal such as `0.1` or `2.5e-3` into an exact rational.
+pub(super) fn parse_decimal(literal: &str) -> Option<BigRational> {
+    let (mantissa, exponent) = match literal.find(['e', 'E']) {
+        Some(index) => (&literal[..index], literal[index + 1..].parse::<i32>().ok()?),
+        None => (literal, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let digits = format!("{}{}", integer, fraction);
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let scale = exponent.checked_sub(i32::try_from(fraction.len()).ok()?)?;
+    if scale.unsigned_abs() > MAX_LITERAL_EXPONENT {
+        return None;
+    }
+    let numerator: BigInt = digits.parse().ok()?;
+    let power = BigInt::from(10).pow(scale.unsigned_abs());
+    Some(if scale >= 0 {
+        BigRational::from_integer(numerator * power)
+    } else {
+        BigRational::new(numerator, power)
+    })
+}
+
+/// Evaluates RPN without rounding. Returns `None` as soon as the expression needs an
+/// approximate constant, an irrational function or a fractional power, so the caller
+/// can fall back to `f64`.
+pub(super) fn eval_exact(tokens: &[CalcToken]) -> Option<BigRational> {
+    let mut stack: Vec<BigRational> = Vec::new();
+    for token in tokens {
+        match token {
+            CalcToken::Exact(value) => stack.push(value.clone()),
+            CalcToken::Operator(op) => {
+                let right = stack.pop()?;
+                let left = stack.pop()?;
+                stack.push(apply_operator(left, right, *op)?);
+            }
+            CalcToken::Negate => {
+                let value = stack.pop()?;
+                stack.push(-value);
+            }
+            CalcToken::BitNot => {
+                let value = as_integer(&stack.pop()?)?;
+                stack.push(BigRational::from_integer(!value));
+            }
+            CalcToken::Factorial => {
+                let value = stack.pop()?;
+                stack.push(factorial(&value)?);
+            }
+            CalcToken::Function(function) => {
+                let arity = function.arity();
+                if stack.len() < arity {
+                    return None;
+                }
+                let args = stack.split_off(stack.len() - arity);
+                stack.push(apply_function(*function, args)?);
+            }
+            _ => return None,
+        }
+    }
+    if stack.len() == 1 {
+        stack.pop()
+    } else {
+        None
+    }
+}
+
+fn apply_operator(left: BigRational, right: BigRational, op: char) -> Option<BigRational> {
+    match op {
+        '+' => Some(left + right),
+        '-' => Some(left - right),
+        '*' => Some(left * right),
+        '/' if !right.is_zero() => Some(left / right),
+        // NOTE: truncated remainder, matching `f64 %`
+        '%' if !right.is_zero() => {
+            let quotient = (&left / &right).trunc();
+            Some(left - quotient * right)
+        }
+        '^' => power(left, &right),
+        op if expression::is_bitwise(op) => {
+            let result = apply_bitwise(as_integer(&left)?, as_integer(&right)?, op)?;
+            Some(BigRational::from_integer(result))
+        }
+        _ => None,
+    }
+}
+
+fn apply_bitwise(left: BigInt, right: BigInt, op: char) -> Option<BigInt> {
+    let shift = || right.to_usize().filter(|shift| *shift <= MAX_SHIFT);
+    match op {
+        '&' => Some(left & right),
+        '|' => Some(left | right),
+        expression::XOR => Some(left ^ right),
+        expression::SHIFT_LEFT => Some(left << shift()?),
+        expression::SHIFT_RIGHT => Some(left >> shift()?),
+        _ => None,
+    }
+}
+
+fn as_integer(value: &BigRational) -> Option<BigInt> {
+    value.is_integer().then(|| value.to_integer())
+}
+
+fn power(base: BigRational, exponent: &BigRational) -> Option<BigRational> {
+    if !exponent.is_integer() {
+        return None;
+    }
+    let exponent = exponent.to_integer().to_i64()?;
+    let magnitude = u32::try_from(exponent.unsigned_abs()).ok()?;
+    let bits = base.numer().bits() + base.denom().bits();
+    if bits.saturating_mul(u64::from(magnitude)) > MAX_POWER_BITS {
+        return None;
+    }
+    let raised = BigRational::new(base.numer().pow(magnitude), base.denom().pow(magnitude));
+    if exponent >= 0 {
+        Some(raised)
+    } else if raised.is_zero() {
+        None
+    } else {
+        Some(raised.recip())
+    }
+}
+
+fn factorial(value: &BigRational) -> Option<BigRational> {
+    if !value.is_integer() || value.is_negative() {
+        return None;
+    }
+    let n = value.to_integer().to_u64()?;
+    if n > MAX_FACTORIAL {
+        return None;
+    }
+    let product = (2..=n).fold(BigInt::one(), |acc, k| acc * k);
+    Some(BigRational::from_integer(product))
+}
+
+fn apply_function(function: Function, args: Vec<BigRational>) -> Option<BigRational> {
+    let mut args = args.into_iter();
+    let x = args.next()?;
+    match function {
+        Function::Abs => Some(x.abs()),
+        Function::Floor => Some(x.floor()),
+        Function::Ceil => Some(x.ceil()),
+        Function::Round => Some(x.round()),
+        Function::Min => Some(x.min(args.next()?)),
+        Function::Max => Some(x.max(args.next()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rational(numerator: i64, denominator: i64) -> BigRational {
+        BigRational::new(numerator.into(), denominator.into())
+    }
+
+    #[test]
+    fn parses_decimal_literals_exactly() {
+        assert_eq!(parse_decimal("0.1"), Some(rational(1, 10)));
+        assert_eq!(parse_decimal("2.5e-3"), Some(rational(1, 400)));
+        assert_eq!(parse_decimal("1E+2"), Some(rational(100, 1)));
+        assert_eq!(parse_decimal(".5"), Some(rational(1, 2)));
+        assert_eq!(parse_decimal("."), None);
+        assert_eq!(parse_decimal("1..2"), None);
+        assert_eq!(parse_decimal("1e5000"), None);
+    }
+
+    #[test]
+    fn powers_stay_exact_within_limits() {
+        let two = rational(2, 1);
+        assert_eq!(power(two.clone(), &rational(-2, 1)), Some(rational(1, 4)));
+        assert_eq!(power(two.clone(), &rational(1, 2)), None);
+        assert_eq!(power(rational(9, 1), &rational(387_420_489, 1)), None);
+        assert_eq!(power(rational(0, 1), &rational(-1, 1)), None);
+    }
+
+    #[test]
+    fn bitwise_operators_use_twos_complement() {
+        let integer = |value: i64| BigRational::from_integer(value.into());
+        assert_eq!(
+            apply_operator(integer(-8), integer(0xff), '&'),
+            Some(integer(0xf8))
+        );
+        assert_eq!(
+            apply_operator(integer(1), integer(100), expression::SHIFT_LEFT),
+            Some(BigRational::from_integer(BigInt::one() << 100usize))
+        );
+        assert_eq!(
+            apply_operator(integer(-9), integer(1), expression::SHIFT_RIGHT),
+            Some(integer(-5))
+        );
+        assert_eq!(apply_operator(rational(1, 2), integer(1), '|'), None);
+        assert_eq!(
+            apply_operator(integer(1), integer(-1), expression::SHIFT_LEFT),
+            None
+        );
+    }
+
+    #[test]
+    fn remainder_follows_dividend_sign() {
+        assert_eq!(
+            apply_operator(rational(-7, 1), rational(3, 1), '%'),
+            Some(rational(-1, 1))
+        );
+        assert_eq!(apply_operator(rational(1, 1), rational(0, 1), '/'), None);
+    }
+}
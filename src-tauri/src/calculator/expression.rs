@@ -0,0 +1,795 @@
+use super::exact;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::ToPrimitive;
+use std::collections::HashMap;
+use std::f64::consts::{E, PI, TAU};
+
+const MAX_FACTORIAL: f64 = 170.0;
+/// Internal operator chars for the multi-character `xor`, `<<` and `>>`.
+pub(super) const XOR: char = '⊕';
+pub(super) const SHIFT_LEFT: char = '«';
+pub(super) const SHIFT_RIGHT: char = '»';
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum CalcToken {
+    /// Approximate value such as `pi`, or a literal too large to hold exactly.
+    Number(f64),
+    /// Decimal literal kept as a rational so the exact evaluator can use it.
+    Exact(BigRational),
+    Operator(char),
+    Negate,
+    /// Prefix `~`, bitwise complement of an integer.
+    BitNot,
+    Factorial,
+    Function(Function),
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Function {
+    Sqrt,
+    Cbrt,
+    Abs,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Ln,
+    Log10,
+    Log2,
+    Exp,
+    Floor,
+    Ceil,
+    Round,
+    Min,
+    Max,
+    Atan2,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        let function = match name {
+            "sqrt" | "√" => Function::Sqrt,
+            "cbrt" => Function::Cbrt,
+            "abs" => Function::Abs,
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "tan" => Function::Tan,
+            "asin" | "arcsin" => Function::Asin,
+            "acos" | "arccos" => Function::Acos,
+            "atan" | "arctan" => Function::Atan,
+            "sinh" => Function::Sinh,
+            "cosh" => Function::Cosh,
+            "tanh" => Function::Tanh,
+            "ln" => Function::Ln,
+            "log" | "log10" => Function::Log10,
+            "log2" => Function::Log2,
+            "exp" => Function::Exp,
+            "floor" => Function::Floor,
+            "ceil" => Function::Ceil,
+            "round" => Function::Round,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "atan2" => Function::Atan2,
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    pub(super) fn arity(self) -> usize {
+        match self {
+            Function::Min | Function::Max | Function::Atan2 => 2,
+            _ => 1,
+        }
+    }
+
+    fn apply(self, args: &[f64]) -> Option<f64> {
+        let x = *args.first()?;
+        let value = match self {
+            Function::Sqrt => x.sqrt(),
+            Function::Cbrt => x.cbrt(),
+            Function::Abs => x.abs(),
+            Function::Sin => x.sin(),
+            Function::Cos => x.cos(),
+            Function::Tan => x.tan(),
+            Function::Asin => x.asin(),
+            Function::Acos => x.acos(),
+            Function::Atan => x.atan(),
+            Function::Sinh => x.sinh(),
+            Function::Cosh => x.cosh(),
+            Function::Tanh => x.tanh(),
+            Function::Ln => x.ln(),
+            Function::Log10 => x.log10(),
+            Function::Log2 => x.log2(),
+            Function::Exp => x.exp(),
+            Function::Floor => x.floor(),
+            Function::Ceil => x.ceil(),
+            Function::Round => x.round(),
+            Function::Min => x.min(*args.get(1)?),
+            Function::Max => x.max(*args.get(1)?),
+            Function::Atan2 => x.atan2(*args.get(1)?),
+        };
+        Some(value)
+    }
+}
+
+/// Session variables by lowercase name; values are `Exact` or `Number` tokens.
+pub(super) type Variables = HashMap<String, CalcToken>;
+
+/// Names that cannot be assigned because they already mean something in an expression.
+pub(super) fn is_reserved(name: &str) -> bool {
+    constant(name).is_some() || Function::from_name(name).is_some() || name == "x" || name == "xor"
+}
+
+fn constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" | "π" => Some(PI),
+        "e" => Some(E),
+        "tau" | "τ" => Some(TAU),
+        _ => None,
+    }
+}
+
+/// Kind of the previous token, used to detect unary minus and implicit multiplication.
+#[derive(Clone, Copy, PartialEq)]
+enum Previous {
+    Start,
+    Literal,
+    Value,
+    Operator,
+}
+
+pub(super) struct Tokenized {
+    pub tokens: Vec<CalcToken>,
+    /// True when a literal used scientific notation (e.g. `1e6`), which makes even a
+    /// lone number worth evaluating.
+    pub has_exponent: bool,
+    /// True when a literal used a `0x`, `0b` or `0o` prefix.
+    pub has_radix_literal: bool,
+}
+
+impl Tokenized {
+    /// Radix literals and bitwise operators mark a programmer calculation, whose integer
+    /// results are also shown in hex and binary.
+    pub fn is_programmer(&self) -> bool {
+        self.has_radix_literal
+            || self.tokens.iter().any(|token| match token {
+                CalcToken::BitNot => true,
+                CalcToken::Operator(op) => is_bitwise(*op),
+                _ => false,
+            })
+    }
+}
+
+pub(super) fn evaluate_expression(expr: &str) -> Option<f64> {
+    eval_rpn(parse_expression(expr, &Variables::new())?.0)
+}
+
+/// Tokenizes `expr` and converts it to RPN, ready for either evaluator. The flag is
+/// `Tokenized::is_programmer`.
+pub(super) fn parse_expression(
+    expr: &str,
+    variables: &Variables,
+) -> Option<(Vec<CalcToken>, bool)> {
+    let tokenized = tokenize_expression(expr, variables)?;
+    let programmer = tokenized.is_programmer();
+    Some((to_rpn(tokenized.tokens)?, programmer))
+}
+
+/// True when `expr` contains something to compute rather than a bare number or word.
+pub(super) fn is_calculation(expr: &str, variables: &Variables) -> bool {
+    let Some(tokenized) = tokenize_expression(expr, variables) else {
+        return false;
+    };
+    tokenized.has_exponent
+        || tokenized.has_radix_literal
+        || tokenized.tokens.iter().any(|token| {
+            matches!(
+                token,
+                CalcToken::Operator(_)
+                    | CalcToken::Negate
+                    | CalcToken::BitNot
+                    | CalcToken::Factorial
+                    | CalcToken::Function(_)
+            )
+        })
+}
+
+pub(super) fn tokenize_expression(expr: &str, variables: &Variables) -> Option<Tokenized> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut has_exponent = false;
+    let mut has_radix_literal = false;
+    let mut previous = Previous::Start;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c.is_ascii_digit() || c == '.' {
+            if previous == Previous::Literal {
+                return None;
+            }
+            if let Some((value, len)) = scan_radix_literal(&chars[i..]) {
+                push_value(&mut tokens, previous, CalcToken::Exact(value));
+                has_radix_literal = true;
+                previous = Previous::Literal;
+                i += len;
+                continue;
+            }
+            let (literal, len, exponent) = scan_number(&chars[i..]);
+            let token = match exact::parse_decimal(&literal) {
+                Some(value) => CalcToken::Exact(value),
+                None => CalcToken::Number(literal.parse::<f64>().ok()?),
+            };
+            push_value(&mut tokens, previous, token);
+            has_exponent |= exponent;
+            previous = Previous::Literal;
+            i += len;
+            continue;
+        }
+
+        if is_identifier_char(c) {
+            let (name, len) = scan_identifier(&chars[i..]);
+            if let Some(value) = constant(&name) {
+                push_value(&mut tokens, previous, CalcToken::Number(value));
+                previous = Previous::Value;
+            } else if let Some(function) = Function::from_name(&name) {
+                push_value(&mut tokens, previous, CalcToken::Function(function));
+                previous = Previous::Operator;
+            } else if name == "x" && is_value(previous) {
+                tokens.push(CalcToken::Operator('*'));
+                previous = Previous::Operator;
+            } else if name == "xor" && is_value(previous) {
+                tokens.push(CalcToken::Operator(XOR));
+                previous = Previous::Operator;
+            } else if let Some(value) = variables.get(&name) {
+                push_value(&mut tokens, previous, value.clone());
+                previous = Previous::Value;
+            } else {
+                return None;
+            }
+            i += len;
+            continue;
+        }
+
+        match c {
+            '-' | '+' if !is_value(previous) => {
+                if c == '-' {
+                    tokens.push(CalcToken::Negate);
+                }
+                previous = Previous::Operator;
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' | '×' | '÷' | '&' | '|' => {
+                if !is_value(previous) {
+                    return None;
+                }
+                let op = match c {
+                    '×' => '*',
+                    '÷' => '/',
+                    other => other,
+                };
+                tokens.push(CalcToken::Operator(op));
+                previous = Previous::Operator;
+            }
+            '<' | '>' => {
+                if !is_value(previous) || chars.get(i + 1) != Some(&c) {
+                    return None;
+                }
+                let op = if c == '<' { SHIFT_LEFT } else { SHIFT_RIGHT };
+                tokens.push(CalcToken::Operator(op));
+                previous = Previous::Operator;
+                i += 1;
+            }
+            '~' => {
+                if is_value(previous) {
+                    return None;
+                }
+                tokens.push(CalcToken::BitNot);
+                previous = Previous::Operator;
+            }
+            '!' => {
+                if !is_value(previous) {
+                    return None;
+                }
+                tokens.push(CalcToken::Factorial);
+                previous = Previous::Value;
+            }
+            '(' => {
+                push_value(&mut tokens, previous, CalcToken::LeftParen);
+                previous = Previous::Start;
+            }
+            ')' => {
+                tokens.push(CalcToken::RightParen);
+                previous = Previous::Value;
+            }
+            ',' => {
+                tokens.push(CalcToken::Comma);
+                previous = Previous::Start;
+            }
+            _ => return None,
+        }
+        i += 1;
+    }
+
+    if tokens.is_empty() {
+        return None;
+    }
+    Some(Tokenized {
+        tokens,
+        has_exponent,
+        has_radix_literal,
+    })
+}
+
+fn is_value(previous: Previous) -> bool {
+    matches!(previous, Previous::Literal | Previous::Value)
+}
+
+/// Pushes a token that starts an operand, inserting `*` for implicit multiplication
+/// such as `2(3+4)`, `2pi` or `3 sqrt(2)`.
+fn push_value(tokens: &mut Vec<CalcToken>, previous: Previous, token: CalcToken) {
+    if is_value(previous) {
+        tokens.push(CalcToken::Operator('*'));
+    }
+    tokens.push(token);
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphabetic() || c == '√'
+}
+
+/// Reads a name like `sin` or `log10`. Trailing digits only belong to the name when the
+/// result is a known function, so `x3` still tokenizes as `x` followed by `3`.
+fn scan_identifier(chars: &[char]) -> (String, usize) {
+    if chars[0] == '√' {
+        return ("√".into(), 1);
+    }
+    let letters = chars.iter().take_while(|c| c.is_alphabetic()).count();
+    let digits = chars[letters..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit())
+        .count();
+    let with_digits: String = chars[..letters + digits]
+        .iter()
+        .collect::<String>()
+        .to_lowercase();
+    if digits > 0 && Function::from_name(&with_digits).is_some() {
+        return (with_digits, letters + digits);
+    }
+    let letters_only: String = chars[..letters].iter().collect::<String>().to_lowercase();
+    (letters_only, letters)
+}
+
+/// Scans `0x1f`, `0b1010` or `0o17` into an exact integer, returning (value, length).
+fn scan_radix_literal(chars: &[char]) -> Option<(BigRational, usize)> {
+    if chars.first() != Some(&'0') {
+        return None;
+    }
+    let radix = match chars.get(1)?.to_ascii_lowercase() {
+        'x' => 16,
+        'b' => 2,
+        'o' => 8,
+        _ => return None,
+    };
+    let digits: String = chars[2..]
+        .iter()
+        .take_while(|c| c.is_digit(radix) || **c == '_')
+        .filter(|c| **c != '_')
+        .collect();
+    let len = 2 + chars[2..]
+        .iter()
+        .take_while(|c| c.is_digit(radix) || **c == '_')
+        .count();
+    if digits.is_empty() || chars.get(len).is_some_and(|c| c.is_alphanumeric()) {
+        return None;
+    }
+    let value = BigInt::parse_bytes(digits.as_bytes(), radix)?;
+    Some((BigRational::from_integer(value), len))
+}
+
+/// Scans a decimal literal with optional exponent, returning (literal, length, has_exponent).
+fn scan_number(chars: &[char]) -> (String, usize, bool) {
+    let mut len = chars
+        .iter()
+        .take_while(|c| c.is_ascii_digit() || **c == '.')
+        .count();
+    let mut has_exponent = false;
+    if matches!(chars.get(len), Some('e') | Some('E')) {
+        let sign = usize::from(matches!(chars.get(len + 1), Some('+') | Some('-')));
+        let digits = chars[(len + 1 + sign).min(chars.len())..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        if digits > 0 {
+            len += 1 + sign + digits;
+            has_exponent = true;
+        }
+    }
+    (chars[..len].iter().collect(), len, has_exponent)
+}
+
+pub(super) fn to_rpn(tokens: Vec<CalcToken>) -> Option<Vec<CalcToken>> {
+    let mut output = Vec::new();
+    let mut ops: Vec<CalcToken> = Vec::new();
+    // NOTE: one argument counter per open parenthesis, to validate function arity
+    let mut arg_counts: Vec<usize> = Vec::new();
+
+    let mut iter = tokens.into_iter().peekable();
+    while let Some(token) = iter.next() {
+        match token {
+            CalcToken::Number(_) | CalcToken::Exact(_) | CalcToken::Factorial => output.push(token),
+            CalcToken::Function(function) => {
+                if function.arity() > 1 && iter.peek() != Some(&CalcToken::LeftParen) {
+                    return None;
+                }
+                ops.push(token);
+            }
+            CalcToken::Negate | CalcToken::BitNot => ops.push(token),
+            CalcToken::Operator(op1) => {
+                while let Some(top) = ops.last() {
+                    let Some((top_precedence, _)) = stack_precedence(top) else {
+                        break;
+                    };
+                    let pops = top_precedence > precedence(op1)
+                        || (top_precedence == precedence(op1) && is_left_assoc(op1));
+                    if !pops {
+                        break;
+                    }
+                    output.push(ops.pop()?);
+                }
+                ops.push(token);
+            }
+            CalcToken::LeftParen => {
+                ops.push(token);
+                arg_counts.push(1);
+            }
+            CalcToken::Comma => {
+                pop_until_left_paren(&mut ops, &mut output)?;
+                *arg_counts.last_mut()? += 1;
+            }
+            CalcToken::RightParen => {
+                pop_until_left_paren(&mut ops, &mut output)?;
+                ops.pop()?;
+                let args = arg_counts.pop()?;
+                if let Some(&CalcToken::Function(function)) = ops.last() {
+                    if function.arity() != args {
+                        return None;
+                    }
+                    output.push(ops.pop()?);
+                } else if args != 1 {
+                    return None;
+                }
+            }
+        }
+    }
+
+    while let Some(top) = ops.pop() {
+        match top {
+            CalcToken::LeftParen => return None,
+            _ => output.push(top),
+        }
+    }
+    Some(output)
+}
+
+fn pop_until_left_paren(ops: &mut Vec<CalcToken>, output: &mut Vec<CalcToken>) -> Option<()> {
+    loop {
+        match ops.last()? {
+            CalcToken::LeftParen => return Some(()),
+            _ => output.push(ops.pop()?),
+        }
+    }
+}
+
+/// Precedence of operators that may sit on the stack and be popped by a binary operator.
+fn stack_precedence(token: &CalcToken) -> Option<(u8, bool)> {
+    match *token {
+        CalcToken::Operator(op) => Some((precedence(op), is_left_assoc(op))),
+        CalcToken::Negate | CalcToken::BitNot | CalcToken::Function(_) => {
+            Some((PREFIX_PRECEDENCE, false))
+        }
+        _ => None,
+    }
+}
+
+/// Prefix minus and parenthesis-free function calls bind tighter than `*` but looser
+/// than `^`, so `-2^2 = -4` and `sin pi/4 = sin(pi)/4`.
+const PREFIX_PRECEDENCE: u8 = 7;
+
+pub(super) fn eval_rpn(tokens: Vec<CalcToken>) -> Option<f64> {
+    let mut stack: Vec<f64> = Vec::new();
+    for token in tokens {
+        match token {
+            CalcToken::Number(v) => stack.push(v),
+            CalcToken::Exact(v) => stack.push(v.to_f64()?),
+            CalcToken::Operator(op) => {
+                let right = stack.pop()?;
+                let left = stack.pop()?;
+                let value = apply_operator(left, right, op)?;
+                stack.push(value);
+            }
+            CalcToken::Negate => {
+                let value = stack.pop()?;
+                stack.push(-value);
+            }
+            CalcToken::BitNot => {
+                let value = as_integer(stack.pop()?)?;
+                stack.push(!value as f64);
+            }
+            CalcToken::Factorial => {
+                let value = stack.pop()?;
+                stack.push(factorial(value)?);
+            }
+            CalcToken::Function(function) => {
+                let arity = function.arity();
+                if stack.len() < arity {
+                    return None;
+                }
+                let args = stack.split_off(stack.len() - arity);
+                stack.push(function.apply(&args)?);
+            }
+            _ => return None,
+        }
+    }
+    if stack.len() == 1 {
+        stack.first().copied()
+    } else {
+        None
+    }
+}
+
+/// C-like precedence, except that `^` is exponentiation and bitwise xor is spelled `xor`.
+fn precedence(op: char) -> u8 {
+    match op {
+        '^' => 8,
+        '*' | '/' | '%' => 6,
+        '+' | '-' => 5,
+        SHIFT_LEFT | SHIFT_RIGHT => 4,
+        '&' => 3,
+        XOR => 2,
+        '|' => 1,
+        _ => 0,
+    }
+}
+
+fn is_left_assoc(op: char) -> bool {
+    op != '^'
+}
+
+pub(super) fn is_bitwise(op: char) -> bool {
+    matches!(op, '&' | '|' | XOR | SHIFT_LEFT | SHIFT_RIGHT)
+}
+
+fn apply_operator(left: f64, right: f64, op: char) -> Option<f64> {
+    match op {
+        '+' => Some(left + right),
+        '-' => Some(left - right),
+        '*' => Some(left * right),
+        '/' => Some(left / right),
+        '%' => Some(left % right),
+        '^' => Some(left.powf(right)),
+        op if is_bitwise(op) => {
+            apply_bitwise(as_integer(left)?, as_integer(right)?, op).map(|v| v as f64)
+        }
+        _ => None,
+    }
+}
+
+fn apply_bitwise(left: i64, right: i64, op: char) -> Option<i64> {
+    match op {
+        '&' => Some(left & right),
+        '|' => Some(left | right),
+        XOR => Some(left ^ right),
+        SHIFT_LEFT => {
+            let shift = u32::try_from(right).ok()?;
+            let shifted = left.checked_shl(shift)?;
+            (shifted >> shift == left).then_some(shifted)
+        }
+        SHIFT_RIGHT => Some(left >> u32::try_from(right).ok()?.min(63)),
+        _ => None,
+    }
+}
+
+/// Bitwise operators need integers that `f64` still represents exactly.
+fn as_integer(value: f64) -> Option<i64> {
+    const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+    (value.fract() == 0.0 && value.abs() <= MAX_SAFE_INTEGER).then_some(value as i64)
+}
+
+fn factorial(value: f64) -> Option<f64> {
+    if value < 0.0 || value.fract() != 0.0 || value > MAX_FACTORIAL {
+        return None;
+    }
+    Some((2..=value as u64).fold(1.0, |acc, n| acc * n as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(expr: &str) -> f64 {
+        evaluate_expression(expr).unwrap_or_else(|| panic!("{expr} should evaluate"))
+    }
+
+    fn assert_close(expr: &str, expected: f64) {
+        let actual = eval(expr);
+        assert!(
+            (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+            "{expr}: expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn basic_arithmetic_and_precedence() {
+        assert_close("1 + 2 * 3", 7.0);
+        assert_close("(1 + 2) * 3", 9.0);
+        assert_close("10 / 4", 2.5);
+        assert_close("10 % 4", 2.0);
+        assert_close("2 ^ 3 ^ 2", 512.0);
+        assert_close("8 - 3 - 2", 3.0);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_close("-5 + 2", -3.0);
+        assert_close("-(3 + 4)", -7.0);
+        assert_close("-2^2", -4.0);
+        assert_close("2^-1", 0.5);
+        assert_close("3 * -2", -6.0);
+        assert_close("--4", 4.0);
+        assert_close("+4", 4.0);
+    }
+
+    #[test]
+    fn constants() {
+        assert_close("pi", PI);
+        assert_close("π / 2", PI / 2.0);
+        assert_close("tau", TAU);
+        assert_close("e^2", E * E);
+        assert_close("PI * 2", TAU);
+    }
+
+    #[test]
+    fn functions_with_and_without_parentheses() {
+        assert_close("sqrt(2)", 2f64.sqrt());
+        assert_close("sin(pi/4)", (PI / 4.0).sin());
+        assert_close("ln 10", 10f64.ln());
+        assert_close("ln 10 + 1", 10f64.ln() + 1.0);
+        assert_close("sin pi/4", PI.sin() / 4.0);
+        assert_close("log(1000)", 3.0);
+        assert_close("log10(100)", 2.0);
+        assert_close("log2 8", 3.0);
+        assert_close("√16", 4.0);
+        assert_close("abs(-3)", 3.0);
+        assert_close("cbrt(27)", 3.0);
+        assert_close("round(2.5) + floor(1.9) + ceil(1.1)", 6.0);
+        assert_close("exp(1)", E);
+        assert_close("sqrt(sqrt(16))", 2.0);
+    }
+
+    #[test]
+    fn multi_argument_functions() {
+        assert_close("max(3, 7)", 7.0);
+        assert_close("min(3, 7) * 2", 6.0);
+        assert_close("atan2(1, 1)", PI / 4.0);
+        assert!(evaluate_expression("max(3)").is_none());
+        assert!(evaluate_expression("max 3").is_none());
+        assert!(evaluate_expression("sqrt(1, 2)").is_none());
+        assert!(evaluate_expression("(1, 2)").is_none());
+    }
+
+    #[test]
+    fn scientific_notation() {
+        assert_close("1e6", 1_000_000.0);
+        assert_close("2.5E-3 * 2", 0.005);
+        assert_close("1e+2", 100.0);
+        assert_close("2e", 2.0 * E);
+    }
+
+    #[test]
+    fn factorial_operator() {
+        assert_close("5!", 120.0);
+        assert_close("0!", 1.0);
+        assert_close("3!!", 720.0);
+        assert_close("(2+1)!", 6.0);
+        assert_close("2^3!", 64.0);
+        assert_close("-3!", -6.0);
+        assert!(evaluate_expression("2.5!").is_none());
+        assert!(evaluate_expression("171!").is_none());
+    }
+
+    #[test]
+    fn implicit_multiplication() {
+        assert_close("2(3+4)", 14.0);
+        assert_close("(1+1)(2+2)", 8.0);
+        assert_close("2pi", TAU);
+        assert_close("3 sqrt(4)", 6.0);
+        assert_close("4!2", 48.0);
+        assert_close("2x3", 6.0);
+        assert_close("2 x 3", 6.0);
+        assert_close("6 ÷ 2 × 3", 9.0);
+        assert!(evaluate_expression("2 3").is_none());
+    }
+
+    #[test]
+    fn radix_literals() {
+        assert_close("0xff", 255.0);
+        assert_close("0XFF + 1", 256.0);
+        assert_close("0b1010", 10.0);
+        assert_close("0o17", 15.0);
+        assert_close("0xdead_beef", 3_735_928_559.0);
+        assert!(evaluate_expression("0xfg").is_none());
+        assert!(evaluate_expression("0b102").is_none());
+        assert!(evaluate_expression("0x").is_none());
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        assert_close("1 << 12", 4096.0);
+        assert_close("0xdead & 0xff", 173.0);
+        assert_close("0b1100 | 0b0011", 15.0);
+        assert_close("6 xor 3", 5.0);
+        assert_close("256 >> 4", 16.0);
+        assert_close("~0", -1.0);
+        assert_close("1 + 1 << 2", 8.0);
+        assert_close("1 | 2 & 3", 3.0);
+        assert!(evaluate_expression("1.5 & 1").is_none());
+        assert!(evaluate_expression("1 < 2").is_none());
+        assert!(evaluate_expression("2 ~ 3").is_none());
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        for expr in [
+            "", "(", "1 +", "* 2", "(1 + 2", "1 + 2)", "foo(2)", "x", "!3", "1..2",
+        ] {
+            assert!(evaluate_expression(expr).is_none(), "{expr:?} should fail");
+        }
+    }
+
+    fn is_calculation(expr: &str) -> bool {
+        super::is_calculation(expr, &Variables::new())
+    }
+
+    #[test]
+    fn variables_are_substituted() {
+        let variables = Variables::from([
+            ("rate".to_string(), CalcToken::Number(1.5)),
+            ("ans".to_string(), CalcToken::Number(4.0)),
+        ]);
+        let eval_with = |expr: &str| eval_rpn(parse_expression(expr, &variables)?.0);
+        assert_eq!(eval_with("100 rate"), Some(150.0));
+        assert_eq!(eval_with("ans * 2 + RATE"), Some(9.5));
+        assert_eq!(eval_with("other * 2"), None);
+        assert!(super::is_calculation("ans + 1", &variables));
+        assert!(!super::is_calculation("ans", &variables));
+        assert!(is_reserved("pi") && is_reserved("sqrt") && !is_reserved("rate"));
+    }
+
+    #[test]
+    fn calculation_detection() {
+        assert!(is_calculation("1+1"));
+        assert!(is_calculation("sqrt 2"));
+        assert!(is_calculation("5!"));
+        assert!(is_calculation("1e6"));
+        assert!(is_calculation("2pi"));
+        assert!(is_calculation("0xff"));
+        assert!(is_calculation("~5"));
+        assert!(!is_calculation("42"));
+        assert!(!is_calculation("pi"));
+        assert!(!is_calculation("spotify"));
+    }
+}
@@ -0,0 +1,293 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+/// Significant digits shown for `f64` results; enough to hide binary rounding noise.
+pub(super) const FLOAT_PRECISION: usize = 15;
+/// Exact results are printed in full up to this many significant digits.
+const MAX_EXACT_DIGITS: usize = 30;
+/// Values below `1e-6` are shown in scientific notation.
+const MIN_FIXED_POINT: i64 = -5;
+
+/// A decimal number as significant digits and the position of the decimal point,
+/// e.g. digits `[1, 2, 5]` with point `1` is `1.25`.
+struct Decimal {
+    negative: bool,
+    digits: Vec<u8>,
+    point: i64,
+}
+
+impl Decimal {
+    fn from_f64(value: f64, significant: usize) -> Self {
+        let scientific = format!("{:.*e}", significant.saturating_sub(1), value.abs());
+        let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+        let digits = mantissa
+            .bytes()
+            .filter(u8::is_ascii_digit)
+            .map(|b| b - b'0')
+            .collect();
+        let point = exponent.parse::<i64>().unwrap_or(0) + 1;
+        Decimal {
+            negative: value < 0.0,
+            digits,
+            point,
+        }
+        .trimmed()
+    }
+
+    /// Expands `value` by long division, keeping it exact when it terminates within
+    /// `MAX_EXACT_DIGITS` and rounding to `FLOAT_PRECISION` otherwise. The flag reports
+    /// whether the digits are exact.
+    fn from_rational(value: &BigRational) -> (Self, bool) {
+        let negative = value.is_negative();
+        let denominator = value.denom().abs();
+        let numerator = value.numer().abs();
+        let integer = &numerator / &denominator;
+        let mut remainder = numerator % &denominator;
+
+        let mut digits: Vec<u8> = if integer.is_zero() {
+            Vec::new()
+        } else {
+            integer.to_string().bytes().map(|b| b - b'0').collect()
+        };
+        let mut point = digits.len() as i64;
+        let ten = BigInt::from(10);
+        while !remainder.is_zero() && digits.len() <= MAX_EXACT_DIGITS {
+            remainder *= &ten;
+            let digit = (&remainder / &denominator).to_string();
+            remainder %= &denominator;
+            let digit = digit.parse::<u8>().unwrap_or(0);
+            if digits.is_empty() && digit == 0 {
+                point -= 1;
+            } else {
+                digits.push(digit);
+            }
+        }
+
+        let decimal = Decimal {
+            negative,
+            digits,
+            point,
+        };
+        if remainder.is_zero() && decimal.digits.len() <= MAX_EXACT_DIGITS {
+            (decimal.trimmed(), true)
+        } else {
+            (decimal.rounded(FLOAT_PRECISION), false)
+        }
+    }
+
+    fn rounded(mut self, significant: usize) -> Self {
+        if self.digits.len() <= significant {
+            return self.trimmed();
+        }
+        let round_up = self.digits[significant] >= 5;
+        self.digits.truncate(significant);
+        if round_up {
+            let mut index = significant;
+            loop {
+                if index == 0 {
+                    self.digits.insert(0, 1);
+                    self.digits.pop();
+                    self.point += 1;
+                    break;
+                }
+                index -= 1;
+                if self.digits[index] == 9 {
+                    self.digits[index] = 0;
+                } else {
+                    self.digits[index] += 1;
+                    break;
+                }
+            }
+        }
+        self.trimmed()
+    }
+
+    fn trimmed(mut self) -> Self {
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+        self
+    }
+
+    /// Fixed notation while the integer part has at most `max_point` digits, scientific beyond.
+    fn render(&self, max_point: i64) -> String {
+        if self.digits.is_empty() {
+            return "0".into();
+        }
+        let digits: String = self.digits.iter().map(|d| char::from(b'0' + d)).collect();
+        let sign = if self.negative { "-" } else { "" };
+        if self.point > max_point || self.point < MIN_FIXED_POINT {
+            let (lead, rest) = digits.split_at(1);
+            let separator = if rest.is_empty() { "" } else { "." };
+            return format!("{sign}{lead}{separator}{rest}e{}", self.point - 1);
+        }
+        let len = digits.len() as i64;
+        if self.point <= 0 {
+            let zeros = "0".repeat(self.point.unsigned_abs() as usize);
+            format!("{sign}0.{zeros}{digits}")
+        } else if self.point >= len {
+            let zeros = "0".repeat((self.point - len) as usize);
+            format!("{sign}{digits}{zeros}")
+        } else {
+            let (integer, fraction) = digits.split_at(self.point as usize);
+            format!("{sign}{integer}.{fraction}")
+        }
+    }
+}
+
+/// Formats a float with at most `significant` digits and no trailing zeros.
+pub(super) fn format_float(value: f64, significant: usize) -> String {
+    Decimal::from_f64(value, significant).render(FLOAT_PRECISION as i64)
+}
+
+pub(super) fn format_rational(value: &BigRational) -> String {
+    let (decimal, exact) = Decimal::from_rational(value);
+    let max_point = if exact {
+        MAX_EXACT_DIGITS
+    } else {
+        FLOAT_PRECISION
+    };
+    decimal.render(max_point as i64)
+}
+
+/// Negative values are shown in two's complement at the narrowest of 8, 16, 32 or 64
+/// bits that holds them, the way a debugger or register view would print them.
+pub(super) fn radix_forms(value: &BigInt, max_bits: u64) -> Vec<super::RadixForm> {
+    if value.is_negative() {
+        return twos_complement_forms(value);
+    }
+    if value.bits() > max_bits {
+        return Vec::new();
+    }
+    vec![
+        super::RadixForm {
+            label: "Hexadecimal",
+            value: format!("{:#x}", value),
+        },
+        super::RadixForm {
+            label: "Binary",
+            value: format!("{:#b}", value),
+        },
+    ]
+}
+
+fn twos_complement_forms(value: &BigInt) -> Vec<super::RadixForm> {
+    const WIDTHS: [(usize, &str, &str); 4] = [
+        (8, "Hexadecimal (8-bit)", "Binary (8-bit)"),
+        (16, "Hexadecimal (16-bit)", "Binary (16-bit)"),
+        (32, "Hexadecimal (32-bit)", "Binary (32-bit)"),
+        (64, "Hexadecimal (64-bit)", "Binary (64-bit)"),
+    ];
+    let Some((bits, hex_label, binary_label)) = WIDTHS
+        .into_iter()
+        .find(|(bits, _, _)| *value >= -(BigInt::one() << (bits - 1)))
+    else {
+        return Vec::new();
+    };
+    let unsigned = value + (BigInt::one() << bits);
+    vec![
+        super::RadixForm {
+            label: hex_label,
+            value: format!("0x{:0width$x}", unsigned, width = bits / 4),
+        },
+        super::RadixForm {
+            label: binary_label,
+            value: format!("0b{:0width$b}", unsigned, width = bits),
+        },
+    ]
+}
+
+/// Inserts `,` between thousands in the integer part; scientific notation is left alone.
+pub(super) fn group_thousands(plain: &str) -> String {
+    if plain.contains('e') {
+        return plain.to_string();
+    }
+    let (sign, unsigned) = match plain.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", plain),
+    };
+    let (integer, fraction) = match unsigned.find('.') {
+        Some(index) => unsigned.split_at(index),
+        None => (unsigned, ""),
+    };
+    let mut grouped = String::new();
+    for (index, c) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    format!("{sign}{grouped}{fraction}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rational(numerator: i64, denominator: i64) -> BigRational {
+        BigRational::new(numerator.into(), denominator.into())
+    }
+
+    #[test]
+    fn floats_drop_rounding_noise() {
+        assert_eq!(format_float(0.1 + 0.2, FLOAT_PRECISION), "0.3");
+        assert_eq!(
+            format_float(2f64.sqrt(), FLOAT_PRECISION),
+            "1.4142135623731"
+        );
+        assert_eq!(format_float(-2.5, FLOAT_PRECISION), "-2.5");
+        assert_eq!(format_float(0.0, FLOAT_PRECISION), "0");
+        assert_eq!(format_float(1e20, FLOAT_PRECISION), "1e20");
+        assert_eq!(format_float(1.5e-7, FLOAT_PRECISION), "1.5e-7");
+        assert_eq!(format_float(0.000_123, FLOAT_PRECISION), "0.000123");
+        assert_eq!(format_float(9.999_999_9, 6), "10");
+    }
+
+    #[test]
+    fn rationals_are_exact_when_they_terminate() {
+        assert_eq!(format_rational(&rational(3, 10)), "0.3");
+        assert_eq!(format_rational(&rational(-5, 2)), "-2.5");
+        assert_eq!(format_rational(&rational(1, 3)), "0.333333333333333");
+        assert_eq!(format_rational(&rational(2, 3)), "0.666666666666667");
+        assert_eq!(format_rational(&rational(1, 1_000_000_000)), "1e-9");
+        let big = BigRational::from_integer(BigInt::from(2).pow(64));
+        assert_eq!(format_rational(&big), "18446744073709551616");
+        let huge = BigRational::from_integer(BigInt::from(10).pow(40));
+        assert_eq!(format_rational(&huge), "1e40");
+    }
+
+    #[test]
+    fn thousands_are_grouped() {
+        assert_eq!(
+            group_thousands("18446744073709551616"),
+            "18,446,744,073,709,551,616"
+        );
+        assert_eq!(group_thousands("-1234567.891"), "-1,234,567.891");
+        assert_eq!(group_thousands("999"), "999");
+        assert_eq!(group_thousands("1.5e-7"), "1.5e-7");
+    }
+
+    #[test]
+    fn negative_radix_forms_use_twos_complement() {
+        let forms = |value: i64| -> Vec<(&'static str, String)> {
+            radix_forms(&BigInt::from(value), 256)
+                .into_iter()
+                .map(|form| (form.label, form.value))
+                .collect()
+        };
+        assert_eq!(
+            forms(-1),
+            vec![
+                ("Hexadecimal (8-bit)", "0xff".to_string()),
+                ("Binary (8-bit)", "0b11111111".to_string()),
+            ]
+        );
+        assert_eq!(forms(-128)[0].1, "0x80");
+        assert_eq!(forms(-129)[0], ("Hexadecimal (16-bit)", "0xff7f".into()));
+        assert_eq!(forms(-70_000)[0].1, "0xfffeee90");
+        assert_eq!(forms(i64::MIN)[0].1, "0x8000000000000000");
+        assert!(radix_forms(&(BigInt::from(i64::MIN) - 1), 256).is_empty());
+        assert_eq!(forms(5)[1].1, "0b101");
+    }
+}
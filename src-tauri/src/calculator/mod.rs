@@ -0,0 +1,361 @@
+mod currency;
+mod date_time;
+mod exact;
+mod expression;
+mod format;
+mod session;
+mod units;
+
+pub use session::HistoryEntry;
+
+/// Significant digits kept when formatting converted quantities.
+const UNIT_PRECISION: usize = 6;
+/// Integers wider than this are not repeated in hex and binary.
+const MAX_RADIX_BITS: u64 = 256;
+/// Decimal places shown for converted money amounts of at least one unit.
+const CURRENCY_DECIMALS: i32 = 2;
+
+pub struct Calculation {
+    /// `<expression> = <result>` with thousands separators.
+    pub title: String,
+    /// Bare result, copied to the clipboard when the result is chosen.
+    pub value: String,
+    /// Hex and binary renderings of integer results of programmer expressions.
+    pub radix_forms: Vec<RadixForm>,
+    /// Variable name for `name = expression` input, stored when the result is committed.
+    pub assignment: Option<String>,
+    result: expression::CalcToken,
+}
+
+pub struct RadixForm {
+    pub label: &'static str,
+    pub value: String,
+}
+
+/// A calculator-style result with its own copyable value.
+pub struct Answer {
+    pub title: String,
+    pub subtitle: String,
+    /// Copied to the clipboard when the result is chosen.
+    pub value: String,
+}
+
+pub fn try_calculate(expr: &str) -> Option<String> {
+    evaluate(expr).map(|calculation| calculation.title)
+}
+
+/// Evaluates against the session variables without changing them.
+pub fn evaluate(expr: &str) -> Option<Calculation> {
+    evaluate_with(expr, &session::variables())
+}
+
+/// Evaluates `expr` and keeps the result: it becomes `ans`, assignments store their
+/// variable, and the expression is added to the session history.
+pub fn commit(expr: &str) -> Option<Calculation> {
+    let calculation = evaluate(expr)?;
+    session::record(
+        expr.trim(),
+        &calculation.value,
+        &calculation.result,
+        calculation.assignment.as_deref(),
+    );
+    Some(calculation)
+}
+
+/// Committed expressions for this session, newest first.
+pub fn history() -> Vec<HistoryEntry> {
+    session::history()
+}
+
+/// Evaluates exactly with rationals when the expression allows it, otherwise in `f64`.
+fn evaluate_with(expr: &str, variables: &expression::Variables) -> Option<Calculation> {
+    let trimmed = expr.trim();
+    let (assignment, body) = match split_assignment(trimmed) {
+        Some((name, body)) => (Some(name), body),
+        None => (None, trimmed),
+    };
+    if body.is_empty() || (assignment.is_none() && !expression::is_calculation(body, variables)) {
+        return None;
+    }
+    let (rpn, programmer) = expression::parse_expression(body, variables)?;
+    let mut radix_forms = Vec::new();
+    let (value, result) = match exact::eval_exact(&rpn) {
+        Some(result) => {
+            if programmer && result.is_integer() {
+                radix_forms = format::radix_forms(&result.to_integer(), MAX_RADIX_BITS);
+            }
+            (
+                format::format_rational(&result),
+                expression::CalcToken::Exact(result),
+            )
+        }
+        None => {
+            let result = expression::eval_rpn(rpn).filter(|result| result.is_finite())?;
+            (
+                format::format_float(result, format::FLOAT_PRECISION),
+                expression::CalcToken::Number(result),
+            )
+        }
+    };
+    let lhs = assignment.as_deref().unwrap_or(expr);
+    Some(Calculation {
+        title: format!("{} = {}", lhs, format::group_thousands(&value)),
+        value,
+        radix_forms,
+        assignment,
+        result,
+    })
+}
+
+/// Splits `rate = 1.17` into the lowercase variable name and its expression.
+fn split_assignment(text: &str) -> Option<(String, &str)> {
+    let (name, body) = text.split_once('=')?;
+    let name = name.trim().to_lowercase();
+    let valid = !name.is_empty()
+        && name.chars().all(char::is_alphabetic)
+        && name != session::ANS
+        && !expression::is_reserved(&name);
+    valid.then(|| (name, body.trim()))
+}
+
+/// Converts queries like `5 km to mi`; the subtitle lists the value in other common units.
+pub fn try_convert_units(query: &str) -> Option<Answer> {
+    let conversion = units::parse_conversion(query)?;
+    let value = format::format_float(conversion.result, UNIT_PRECISION);
+    let subtitle = conversion
+        .alternates()
+        .into_iter()
+        .map(|(amount, unit)| format!("{} {}", format_quantity(amount), unit.symbol))
+        .collect::<Vec<_>>()
+        .join(" · ");
+    Some(Answer {
+        title: format!(
+            "{} {} = {} {}",
+            format_quantity(conversion.value),
+            conversion.from.symbol,
+            format::group_thousands(&value),
+            conversion.to.symbol
+        ),
+        subtitle: if subtitle.is_empty() {
+            "Unit Conversion".into()
+        } else {
+            subtitle
+        },
+        value,
+    })
+}
+
+/// Converts queries like `100 usd to eur * 2` with the cached exchange rates.
+pub fn try_convert_currency(query: &str) -> Option<Answer> {
+    // NOTE: asking for rates may fetch them, so other queries must not get this far
+    if !currency::could_be_conversion(query) {
+        return None;
+    }
+    convert_currency(query, &crate::currency::rates()?)
+}
+
+fn convert_currency(query: &str, rates: &crate::currency::RatesTable) -> Option<Answer> {
+    let conversion = currency::parse_conversion(query, rates)?;
+    let value = format_money(conversion.result);
+    let amount = format_quantity(conversion.amount);
+    let lhs = if conversion.tail.is_empty() {
+        format!("{} {}", amount, conversion.from)
+    } else {
+        format!(
+            "({} {} in {}) {}",
+            amount, conversion.from, conversion.to, conversion.tail
+        )
+    };
+    let updated = jiff::Timestamp::from_second(rates.fetched_at as i64)
+        .map(|fetched| {
+            fetched
+                .to_zoned(jiff::tz::TimeZone::system())
+                .strftime("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default();
+    Some(Answer {
+        title: format!(
+            "{} = {} {}",
+            lhs,
+            format::group_thousands(&value),
+            conversion.to
+        ),
+        subtitle: format!(
+            "1 {} = {} {} · rates updated {}",
+            conversion.from,
+            format::format_float(conversion.rate, UNIT_PRECISION),
+            conversion.to,
+            updated
+        ),
+        value,
+    })
+}
+
+/// Date and time queries such as `now + 3 weeks`, `days until 2026-12-25`,
+/// `unix 1700000000` or `time in Tokyo`, evaluated with the bundled tz database.
+pub fn try_date_time(query: &str) -> Vec<Answer> {
+    date_time::evaluate(query, &jiff::Zoned::now())
+}
+
+fn format_quantity(value: f64) -> String {
+    format::group_thousands(&format::format_float(value, UNIT_PRECISION))
+}
+
+/// Cents for everyday amounts; small fractions keep their significant digits.
+fn format_money(value: f64) -> String {
+    if value.abs() >= 1.0 {
+        let scale = 10f64.powi(CURRENCY_DECIMALS);
+        format::format_float((value * scale).round() / scale, format::FLOAT_PRECISION)
+    } else {
+        format::format_float(value, UNIT_PRECISION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_expression_and_result() {
+        assert_eq!(try_calculate("2(3+4)").as_deref(), Some("2(3+4) = 14"));
+        assert_eq!(try_calculate("5!").as_deref(), Some("5! = 120"));
+    }
+
+    #[test]
+    fn exact_arithmetic_avoids_float_noise() {
+        assert_eq!(try_calculate("0.1+0.2").as_deref(), Some("0.1+0.2 = 0.3"));
+        assert_eq!(
+            try_calculate("2^64").as_deref(),
+            Some("2^64 = 18,446,744,073,709,551,616")
+        );
+        assert_eq!(
+            try_calculate("1/3").as_deref(),
+            Some("1/3 = 0.333333333333333")
+        );
+        assert_eq!(try_calculate("-7 % 3").as_deref(), Some("-7 % 3 = -1"));
+        assert_eq!(
+            try_calculate("25!").as_deref(),
+            Some("25! = 15,511,210,043,330,985,984,000,000")
+        );
+        assert_eq!(
+            try_calculate("1e300 * 1e300").as_deref(),
+            Some("1e300 * 1e300 = 1e600")
+        );
+        let calculation = evaluate("1000 * 1000").unwrap();
+        assert_eq!(calculation.title, "1000 * 1000 = 1,000,000");
+        assert_eq!(calculation.value, "1000000");
+    }
+
+    #[test]
+    fn programmer_expressions_offer_hex_and_binary() {
+        let calculation = evaluate("0xdead & 0xff").unwrap();
+        assert_eq!(calculation.title, "0xdead & 0xff = 173");
+        let forms: Vec<(&str, &str)> = calculation
+            .radix_forms
+            .iter()
+            .map(|form| (form.label, form.value.as_str()))
+            .collect();
+        assert_eq!(
+            forms,
+            vec![("Hexadecimal", "0xad"), ("Binary", "0b10101101")]
+        );
+        assert_eq!(evaluate("1 << 12").unwrap().value, "4096");
+        assert!(evaluate("0xff / 2").unwrap().radix_forms.is_empty());
+        assert!(evaluate("2 + 2").unwrap().radix_forms.is_empty());
+    }
+
+    #[test]
+    fn assignments_and_ans_use_session_variables() {
+        let variables = expression::Variables::from([
+            ("rate".to_string(), expression::CalcToken::Number(1.17)),
+            (
+                session::ANS.to_string(),
+                expression::CalcToken::Number(10.0),
+            ),
+        ]);
+        let calculation = evaluate_with("Rate = 2 * 0.5", &variables).unwrap();
+        assert_eq!(calculation.title, "rate = 1");
+        assert_eq!(calculation.assignment.as_deref(), Some("rate"));
+        assert_eq!(
+            evaluate_with("100 * rate", &variables).unwrap().value,
+            "117"
+        );
+        assert_eq!(evaluate_with("ans / 4", &variables).unwrap().value, "2.5");
+        assert!(evaluate_with("ans = 3", &variables).is_none());
+        assert!(evaluate_with("pi = 3", &variables).is_none());
+        assert!(evaluate_with("rate =", &variables).is_none());
+        assert!(evaluate("unknown * 2").is_none());
+    }
+
+    #[test]
+    fn transcendental_functions_fall_back_to_floats() {
+        assert_eq!(
+            try_calculate("sqrt(2)").as_deref(),
+            Some("sqrt(2) = 1.4142135623731")
+        );
+        assert_eq!(
+            try_calculate("2 * pi").as_deref(),
+            Some("2 * pi = 6.28318530717959")
+        );
+        assert_eq!(try_calculate("1e5000 * 2"), None);
+        assert_eq!(
+            try_calculate("2^0.5").as_deref(),
+            Some("2^0.5 = 1.4142135623731")
+        );
+    }
+
+    #[test]
+    fn ignores_plain_words_numbers_and_non_finite_results() {
+        assert_eq!(try_calculate("safari"), None);
+        assert_eq!(try_calculate("42"), None);
+        assert_eq!(try_calculate("1/0"), None);
+        assert_eq!(try_calculate("sqrt(-1)"), None);
+    }
+
+    #[test]
+    fn formats_unit_conversions() {
+        let conversion = try_convert_units("5 km to mi").unwrap();
+        assert_eq!(conversion.title, "5 km = 3.10686 mi");
+        assert_eq!(conversion.value, "3.10686");
+        assert!(conversion.subtitle.contains("5,000 m"));
+        assert_eq!(
+            try_convert_units("3 GiB in MB").unwrap().title,
+            "3 GiB = 3,221.23 MB"
+        );
+        assert_eq!(try_convert_units("90 min to h").unwrap().value, "1.5");
+        assert!(try_convert_units("2 + 2").is_none());
+    }
+
+    #[test]
+    fn formats_currency_conversions() {
+        let rates = crate::currency::RatesTable {
+            base: "USD".into(),
+            fetched_at: 1_700_000_000,
+            rates: std::collections::HashMap::from([
+                ("EUR".into(), 0.921_34),
+                ("JPY".into(), 150.0),
+            ]),
+        };
+        let conversion = convert_currency("100 usd to eur", &rates).unwrap();
+        assert_eq!(conversion.title, "100 USD = 92.13 EUR");
+        assert_eq!(conversion.value, "92.13");
+        assert!(conversion
+            .subtitle
+            .starts_with("1 USD = 0.92134 EUR · rates updated 2023-11-"));
+        assert_eq!(
+            convert_currency("100 usd to eur * 2", &rates)
+                .unwrap()
+                .title,
+            "(100 USD in EUR) * 2 = 184.27 EUR"
+        );
+        assert_eq!(
+            convert_currency("1 jpy to usd", &rates).unwrap().value,
+            "0.00666667"
+        );
+        assert_eq!(
+            convert_currency("10000 usd in jpy", &rates).unwrap().title,
+            "10,000 USD = 1,500,000 JPY"
+        );
+    }
+}
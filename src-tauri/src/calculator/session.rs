@@ -0,0 +1,63 @@
+use super::expression::{CalcToken, Variables};
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+const MAX_HISTORY: usize = 20;
+/// Always holds the most recently committed result.
+pub(super) const ANS: &str = "ans";
+
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    pub expression: String,
+    pub value: String,
+}
+
+/// Variables and history for the current app session; nothing is written to disk.
+#[derive(Default)]
+struct Session {
+    variables: Variables,
+    history: VecDeque<HistoryEntry>,
+}
+
+static SESSION: OnceLock<Mutex<Session>> = OnceLock::new();
+
+fn session() -> &'static Mutex<Session> {
+    SESSION.get_or_init(|| Mutex::new(Session::default()))
+}
+
+pub(super) fn variables() -> Variables {
+    session()
+        .lock()
+        .map(|session| session.variables.clone())
+        .unwrap_or_default()
+}
+
+pub(super) fn history() -> Vec<HistoryEntry> {
+    session()
+        .lock()
+        .map(|session| session.history.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Stores `result` as `ans` (and as `assignment`, if given) and records the expression.
+pub(super) fn record(expression: &str, value: &str, result: &CalcToken, assignment: Option<&str>) {
+    let Ok(mut session) = session().lock() else {
+        return;
+    };
+    session.variables.insert(ANS.into(), result.clone());
+    if let Some(name) = assignment {
+        session.variables.insert(name.into(), result.clone());
+    }
+    if session
+        .history
+        .front()
+        .is_some_and(|entry| entry.expression == expression)
+    {
+        session.history.pop_front();
+    }
+    session.history.push_front(HistoryEntry {
+        expression: expression.into(),
+        value: value.into(),
+    });
+    session.history.truncate(MAX_HISTORY);
+}
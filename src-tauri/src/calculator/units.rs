@@ -0,0 +1,379 @@
+use super::expression;
+
+/// A unit of measure; `factor` and `offset` convert values into the dimension's base unit.
+#[derive(Debug)]
+pub(super) struct Unit {
+    pub symbol: &'static str,
+    /// Lowercase spellings; plural `s` and British `-metre`/`-litre` are normalised away.
+    aliases: &'static [&'static str],
+    factor: f64,
+    /// Added before scaling; only non-zero for temperature scales.
+    offset: f64,
+}
+
+impl Unit {
+    fn convert_to_base(&self, value: f64) -> f64 {
+        (value + self.offset) * self.factor
+    }
+
+    fn convert_from_base(&self, value: f64) -> f64 {
+        value / self.factor - self.offset
+    }
+}
+
+const fn linear(symbol: &'static str, factor: f64, aliases: &'static [&'static str]) -> Unit {
+    Unit {
+        symbol,
+        aliases,
+        factor,
+        offset: 0.0,
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Dimension {
+    units: &'static [Unit],
+    /// Symbols shown as alternate conversions in the result subtitle.
+    common: &'static [&'static str],
+}
+
+const KIB: f64 = 1024.0;
+const DAY: f64 = 86_400.0;
+const INCH: f64 = 0.0254;
+const FOOT: f64 = 0.3048;
+const YARD: f64 = 0.9144;
+const MILE: f64 = 1_609.344;
+const NAUTICAL_MILE: f64 = 1_852.0;
+const US_GALLON: f64 = 3.785_411_784;
+
+// Base unit: metre
+static LENGTH: &[Unit] = &[
+    linear("nm", 1e-9, &["nm", "nanometer"]),
+    linear("µm", 1e-6, &["µm", "um", "micron", "micrometer"]),
+    linear("mm", 1e-3, &["mm", "millimeter"]),
+    linear("cm", 1e-2, &["cm", "centimeter"]),
+    linear("m", 1.0, &["m", "meter"]),
+    linear("km", 1e3, &["km", "kilometer"]),
+    linear("in", INCH, &["in", "inch", "inches", "\""]),
+    linear("ft", FOOT, &["ft", "foot", "feet", "'"]),
+    linear("yd", YARD, &["yd", "yard"]),
+    linear("mi", MILE, &["mi", "mile"]),
+    linear("nmi", NAUTICAL_MILE, &["nmi", "nautical mile"]),
+];
+
+// Base unit: kilogram
+static MASS: &[Unit] = &[
+    linear("mg", 1e-6, &["mg", "milligram"]),
+    linear("g", 1e-3, &["g", "gram"]),
+    linear("kg", 1.0, &["kg", "kilo", "kilogram"]),
+    linear("t", 1e3, &["t", "tonne", "metric ton"]),
+    linear("oz", 0.028_349_523_125, &["oz", "ounce"]),
+    linear("lb", 0.453_592_37, &["lb", "pound"]),
+    linear("st", 6.350_293_18, &["st", "stone"]),
+];
+
+// Base unit: kelvin
+static TEMPERATURE: &[Unit] = &[
+    Unit {
+        symbol: "°C",
+        aliases: &["°c", "c", "celsius", "degc"],
+        factor: 1.0,
+        offset: 273.15,
+    },
+    Unit {
+        symbol: "°F",
+        aliases: &["°f", "f", "fahrenheit", "degf"],
+        factor: 5.0 / 9.0,
+        offset: 459.67,
+    },
+    linear("K", 1.0, &["k", "kelvin"]),
+];
+
+// Base unit: litre (US customary measures)
+static VOLUME: &[Unit] = &[
+    linear("ml", 1e-3, &["ml", "milliliter"]),
+    linear("cl", 1e-2, &["cl", "centiliter"]),
+    linear("dl", 1e-1, &["dl", "deciliter"]),
+    linear("l", 1.0, &["l", "liter"]),
+    linear("m³", 1e3, &["m³", "m3", "cubic meter"]),
+    linear("tsp", US_GALLON / 768.0, &["tsp", "teaspoon"]),
+    linear("tbsp", US_GALLON / 256.0, &["tbsp", "tablespoon"]),
+    linear(
+        "fl oz",
+        US_GALLON / 128.0,
+        &["fl oz", "floz", "fluid ounce"],
+    ),
+    linear("cup", US_GALLON / 16.0, &["cup"]),
+    linear("pt", US_GALLON / 8.0, &["pt", "pint"]),
+    linear("qt", US_GALLON / 4.0, &["qt", "quart"]),
+    linear("gal", US_GALLON, &["gal", "gallon"]),
+];
+
+// Base unit: byte. SI prefixes are decimal, IEC prefixes binary.
+static DATA: &[Unit] = &[
+    linear("bit", 0.125, &["bit"]),
+    linear("kbit", 125.0, &["kbit", "kilobit"]),
+    linear("Mbit", 125e3, &["mbit", "megabit"]),
+    linear("Gbit", 125e6, &["gbit", "gigabit"]),
+    linear("B", 1.0, &["b", "byte"]),
+    linear("KB", 1e3, &["kb", "kilobyte"]),
+    linear("MB", 1e6, &["mb", "megabyte"]),
+    linear("GB", 1e9, &["gb", "gigabyte"]),
+    linear("TB", 1e12, &["tb", "terabyte"]),
+    linear("PB", 1e15, &["pb", "petabyte"]),
+    linear("KiB", KIB, &["kib", "kibibyte"]),
+    linear("MiB", KIB * KIB, &["mib", "mebibyte"]),
+    linear("GiB", KIB * KIB * KIB, &["gib", "gibibyte"]),
+    linear("TiB", KIB * KIB * KIB * KIB, &["tib", "tebibyte"]),
+    linear("PiB", KIB * KIB * KIB * KIB * KIB, &["pib", "pebibyte"]),
+];
+
+// Base unit: second
+static TIME: &[Unit] = &[
+    linear("ms", 1e-3, &["ms", "millisecond"]),
+    linear("s", 1.0, &["s", "sec", "second"]),
+    linear("min", 60.0, &["min", "minute"]),
+    linear("h", 3_600.0, &["h", "hr", "hour"]),
+    linear("d", DAY, &["d", "day"]),
+    linear("wk", 7.0 * DAY, &["wk", "week"]),
+    linear("mo", 30.436_875 * DAY, &["mo", "month"]),
+    linear("yr", 365.2425 * DAY, &["yr", "year"]),
+];
+
+// Base unit: metre per second
+static SPEED: &[Unit] = &[
+    linear("m/s", 1.0, &["m/s", "mps"]),
+    linear("km/h", 1e3 / 3_600.0, &["km/h", "kph", "kmh"]),
+    linear("mph", MILE / 3_600.0, &["mph", "mi/h"]),
+    linear("kn", NAUTICAL_MILE / 3_600.0, &["kn", "kt", "knot"]),
+    linear("ft/s", FOOT, &["ft/s", "fps"]),
+];
+
+// Base unit: square metre
+static AREA: &[Unit] = &[
+    linear("mm²", 1e-6, &["mm²", "mm2", "sq mm"]),
+    linear("cm²", 1e-4, &["cm²", "cm2", "sq cm"]),
+    linear("m²", 1.0, &["m²", "m2", "sqm", "sq m", "square meter"]),
+    linear("ha", 1e4, &["ha", "hectare"]),
+    linear("km²", 1e6, &["km²", "km2", "sq km", "square kilometer"]),
+    linear(
+        "in²",
+        INCH * INCH,
+        &["in²", "in2", "sq in", "square inch", "square inches"],
+    ),
+    linear(
+        "ft²",
+        FOOT * FOOT,
+        &["ft²", "ft2", "sqft", "sq ft", "square foot", "square feet"],
+    ),
+    linear("yd²", YARD * YARD, &["yd²", "yd2", "sq yd", "square yard"]),
+    linear("ac", 4_046.856_422_4, &["ac", "acre"]),
+    linear("mi²", MILE * MILE, &["mi²", "mi2", "sq mi", "square mile"]),
+];
+
+static DIMENSIONS: &[Dimension] = &[
+    Dimension {
+        units: LENGTH,
+        common: &["m", "km", "ft", "mi", "cm", "in"],
+    },
+    Dimension {
+        units: MASS,
+        common: &["kg", "lb", "g", "oz"],
+    },
+    Dimension {
+        units: TEMPERATURE,
+        common: &["°C", "°F", "K"],
+    },
+    Dimension {
+        units: VOLUME,
+        common: &["l", "ml", "gal", "cup", "fl oz"],
+    },
+    Dimension {
+        units: DATA,
+        common: &["MB", "MiB", "GB", "GiB", "KB", "TB"],
+    },
+    Dimension {
+        units: TIME,
+        common: &["s", "min", "h", "d"],
+    },
+    Dimension {
+        units: SPEED,
+        common: &["km/h", "mph", "m/s", "kn"],
+    },
+    Dimension {
+        units: AREA,
+        common: &["m²", "ft²", "ha", "ac", "km²"],
+    },
+];
+
+/// Words and arrows that separate the source quantity from the target unit.
+pub(super) const SEPARATORS: &[&str] = &[" to ", " in ", " as ", "->", "→"];
+const MAX_ALTERNATES: usize = 3;
+
+#[derive(Debug)]
+pub(super) struct Conversion {
+    dimension: &'static Dimension,
+    pub value: f64,
+    pub from: &'static Unit,
+    pub to: &'static Unit,
+    pub result: f64,
+}
+
+impl Conversion {
+    /// The value expressed in other common units of the same dimension.
+    pub fn alternates(&self) -> Vec<(f64, &'static Unit)> {
+        let base = self.from.convert_to_base(self.value);
+        self.dimension
+            .common
+            .iter()
+            .filter_map(|symbol| {
+                self.dimension
+                    .units
+                    .iter()
+                    .find(|unit| unit.symbol == *symbol)
+            })
+            .filter(|unit| !std::ptr::eq(*unit, self.from) && !std::ptr::eq(*unit, self.to))
+            .take(MAX_ALTERNATES)
+            .map(|unit| (unit.convert_from_base(base), unit))
+            .collect()
+    }
+}
+
+/// Parses queries like `5 km to mi`, `72 f in c` or `(1+2) GiB as MB`.
+pub(super) fn parse_conversion(query: &str) -> Option<Conversion> {
+    let lowered = query
+        .trim()
+        .to_lowercase()
+        .replace("metre", "meter")
+        .replace("litre", "liter");
+    let mut split_points: Vec<(usize, usize)> = SEPARATORS
+        .iter()
+        .flat_map(|separator| {
+            lowered
+                .match_indices(separator)
+                .map(|(index, matched)| (index, matched.len()))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    // NOTE: try the rightmost separator first so `5 in to cm` reads `5 in` as inches
+    split_points.sort_by_key(|(index, _)| std::cmp::Reverse(*index));
+
+    split_points.into_iter().find_map(|(index, len)| {
+        let left = lowered[..index].trim();
+        let right = lowered[index + len..].trim();
+        let (dimension, to) = find_unit(right)?;
+        let (value_expr, from) = split_value_and_unit(left)?;
+        if !dimension.units.iter().any(|unit| std::ptr::eq(unit, from)) {
+            return None;
+        }
+        let value = if value_expr.is_empty() {
+            1.0
+        } else {
+            expression::evaluate_expression(value_expr)?
+        };
+        let result = to.convert_from_base(from.convert_to_base(value));
+        result.is_finite().then_some(Conversion {
+            dimension,
+            value,
+            from,
+            to,
+            result,
+        })
+    })
+}
+
+fn all_units() -> impl Iterator<Item = (&'static Dimension, &'static Unit)> {
+    DIMENSIONS
+        .iter()
+        .flat_map(|dimension| dimension.units.iter().map(move |unit| (dimension, unit)))
+}
+
+fn find_unit(text: &str) -> Option<(&'static Dimension, &'static Unit)> {
+    let exact = |text: &str| all_units().find(|(_, unit)| unit.aliases.contains(&text));
+    exact(text).or_else(|| exact(text.strip_suffix('s')?))
+}
+
+/// Splits `5 km` / `5km` / `(2+3) miles` into the value expression and the longest unit suffix.
+fn split_value_and_unit(text: &str) -> Option<(&str, &'static Unit)> {
+    longest_unit_suffix(text).or_else(|| longest_unit_suffix(text.strip_suffix('s')?))
+}
+
+fn longest_unit_suffix(text: &str) -> Option<(&str, &'static Unit)> {
+    all_units()
+        .flat_map(|(_, unit)| unit.aliases.iter().map(move |alias| (*alias, unit)))
+        .filter(|(alias, _)| {
+            text.strip_suffix(alias)
+                .is_some_and(|rest| !rest.chars().next_back().is_some_and(|c| c.is_alphabetic()))
+        })
+        .max_by_key(|(alias, _)| alias.len())
+        .map(|(alias, unit)| (text[..text.len() - alias.len()].trim(), unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(query: &str) -> (f64, &'static str) {
+        let conversion =
+            parse_conversion(query).unwrap_or_else(|| panic!("{query} should convert"));
+        (conversion.result, conversion.to.symbol)
+    }
+
+    fn assert_converts(query: &str, expected: f64, symbol: &str) {
+        let (actual, actual_symbol) = convert(query);
+        assert_eq!(actual_symbol, symbol, "{query}");
+        assert!(
+            (actual - expected).abs() <= 1e-6 * expected.abs().max(1.0),
+            "{query}: expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn converts_requested_examples() {
+        assert_converts("5 km to mi", 3.106_855_961, "mi");
+        assert_converts("72 f in c", 22.222_222_222, "°C");
+        assert_converts("3 GiB in MB", 3_221.225_472, "MB");
+        assert_converts("90 min to h", 1.5, "h");
+    }
+
+    #[test]
+    fn converts_every_dimension() {
+        assert_converts("1 lb to g", 453.592_37, "g");
+        assert_converts("-40 c to f", -40.0, "°F");
+        assert_converts("0 k in c", -273.15, "°C");
+        assert_converts("1 gal to l", 3.785_411_784, "l");
+        assert_converts("100 km/h to mph", 62.137_119_224, "mph");
+        assert_converts("1 acre to m2", 4_046.856_422_4, "m²");
+        assert_converts("8 bits to bytes", 1.0, "B");
+        assert_converts("2 weeks in days", 14.0, "d");
+    }
+
+    #[test]
+    fn accepts_attached_units_expressions_and_implicit_one() {
+        assert_converts("5km to m", 5_000.0, "m");
+        assert_converts("(1+1) ft in in", 24.0, "in");
+        assert_converts("5 in to cm", 12.7, "cm");
+        assert_converts("km to mi", 0.621_371_192, "mi");
+        assert_converts("10 sq ft to m²", 0.929_030_4, "m²");
+        assert_converts("3 kilometres in miles", 1.864_113_577, "mi");
+    }
+
+    #[test]
+    fn rejects_mismatched_or_unknown_units() {
+        assert!(parse_conversion("5 km to kg").is_none());
+        assert!(parse_conversion("5 km to parsecs").is_none());
+        assert!(parse_conversion("ssh to h").is_none());
+        assert!(parse_conversion("time in tokyo").is_none());
+        assert!(parse_conversion("5 km").is_none());
+    }
+
+    #[test]
+    fn alternates_exclude_source_and_target() {
+        let conversion = parse_conversion("5 km to mi").unwrap();
+        let alternates = conversion.alternates();
+        assert!(!alternates.is_empty());
+        assert!(alternates
+            .iter()
+            .all(|(_, unit)| unit.symbol != "km" && unit.symbol != "mi"));
+    }
+}
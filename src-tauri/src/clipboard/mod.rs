@@ -0,0 +1,616 @@
+mod platform;
+mod vault;
+
+use crate::settings::{self, ClipboardSettings};
+use platform::Payload;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Time for focus to return to the previous app after the window hides.
+const PASTE_DELAY: Duration = Duration::from_millis(150);
+const HISTORY_FILE: &str = "clipboard-history.bin";
+const IMAGES_DIR: &str = "clipboard-images";
+const KEY_FILE: &str = "clipboard.key";
+const PREVIEW_CHARS: usize = 80;
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClipContent {
+    Text {
+        text: String,
+    },
+    /// The PNG data lives in its own encrypted file under `clipboard-images/`.
+    Image {
+        width: u32,
+        height: u32,
+        bytes: usize,
+    },
+    Files {
+        paths: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipEntry {
+    pub id: u64,
+    pub content: ClipContent,
+    /// Unix seconds of the most recent copy of this content.
+    pub copied_at: u64,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub source_app: Option<String>,
+    /// Recognises repeated copies so they move to the top instead of duplicating. A
+    /// SHA-256 in hex; histories from before it was stored get it filled in on load.
+    #[serde(default, rename = "sha256")]
+    digest: String,
+}
+
+impl ClipEntry {
+    /// A single-line preview suitable for a result title.
+    pub fn preview(&self) -> String {
+        match &self.content {
+            ClipContent::Text { text } => {
+                let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                match line.char_indices().nth(PREVIEW_CHARS) {
+                    Some((index, _)) => format!("{}…", &line[..index]),
+                    None => line,
+                }
+            }
+            ClipContent::Image { width, height, .. } => format!("Image {}×{}", width, height),
+            ClipContent::Files { paths } => {
+                let names: Vec<String> = paths
+                    .iter()
+                    .map(|path| {
+                        std::path::Path::new(path)
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_else(|| path.clone())
+                    })
+                    .collect();
+                match names.as_slice() {
+                    [name] => name.clone(),
+                    _ => format!("{} files: {}", names.len(), names.join(", ")),
+                }
+            }
+        }
+    }
+
+    /// `Copied 5m ago · Safari · Pinned`
+    pub fn describe(&self, now: u64) -> String {
+        let mut parts = vec![format!("Copied {}", format_age(now, self.copied_at))];
+        if let ClipContent::Image { bytes, .. } = &self.content {
+            parts.push(format!("{} KB", bytes.div_ceil(1024)));
+        }
+        if let Some(app) = &self.source_app {
+            parts.push(app.clone());
+        }
+        if self.pinned {
+            parts.push("Pinned".into());
+        }
+        parts.join(" · ")
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self.content {
+            ClipContent::Text { .. } => "clipboard",
+            ClipContent::Image { .. } => "image",
+            ClipContent::Files { .. } => "file",
+        }
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        if query.is_empty() {
+            return true;
+        }
+        let in_app = self
+            .source_app
+            .as_ref()
+            .is_some_and(|app| app.to_lowercase().contains(query));
+        in_app
+            || match &self.content {
+                ClipContent::Text { text } => text.to_lowercase().contains(query),
+                ClipContent::Image { .. } => "image".starts_with(query),
+                ClipContent::Files { paths } => {
+                    paths.iter().any(|path| path.to_lowercase().contains(query))
+                }
+            }
+    }
+}
+
+/// Entries are kept newest first.
+struct Store {
+    vault: vault::Vault,
+    dir: PathBuf,
+    entries: Vec<ClipEntry>,
+    /// Digest of the last clipboard content seen, recorded or not.
+    last_digest: Option<String>,
+}
+
+impl Store {
+    fn history_path(&self) -> PathBuf {
+        self.dir.join(HISTORY_FILE)
+    }
+
+    fn image_path(&self, id: u64) -> PathBuf {
+        self.dir.join(IMAGES_DIR).join(format!("{}.bin", id))
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let json = serde_json::to_vec(&self.entries).map_err(|e| e.to_string())?;
+        self.vault.write(&self.history_path(), &json)
+    }
+
+    fn discard(&self, removed: &[ClipEntry]) {
+        for entry in removed {
+            if matches!(entry.content, ClipContent::Image { .. }) {
+                let _ = std::fs::remove_file(self.image_path(entry.id));
+            }
+        }
+    }
+
+    fn payload(&self, entry: &ClipEntry) -> Result<Payload, String> {
+        Ok(match &entry.content {
+            ClipContent::Text { text } => Payload::Text(text.clone()),
+            ClipContent::Files { paths } => Payload::Files(paths.clone()),
+            ClipContent::Image { .. } => {
+                Payload::Image(self.vault.read(&self.image_path(entry.id))?)
+            }
+        })
+    }
+}
+
+static STORE: OnceLock<Mutex<Store>> = OnceLock::new();
+
+/// Opens the encrypted history and starts the background clipboard watcher.
+pub fn init() {
+    let dir = dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("spotlight-genie");
+    let _ = std::fs::create_dir_all(dir.join(IMAGES_DIR));
+    let Ok(vault) = vault::Vault::open(&dir.join(KEY_FILE)) else {
+        return;
+    };
+    let entries = vault
+        .read(&dir.join(HISTORY_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or_default();
+    let mut store = Store {
+        vault,
+        dir,
+        entries,
+        last_digest: None,
+    };
+    if fill_missing_digests(&mut store) {
+        let _ = store.persist();
+    }
+    if STORE.set(Mutex::new(store)).is_ok() {
+        std::thread::spawn(watch);
+    }
+}
+
+/// Returns whether any entry was updated.
+fn fill_missing_digests(store: &mut Store) -> bool {
+    let digests: Vec<(usize, String)> = store
+        .entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.digest.is_empty())
+        .filter_map(|(index, entry)| Some((index, digest_of(&store.payload(entry).ok()?))))
+        .collect();
+    for (index, digest) in &digests {
+        store.entries[*index].digest = digest.clone();
+    }
+    !digests.is_empty()
+}
+
+fn watch() {
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let settings = settings::get().clipboard;
+        if !settings.enabled {
+            continue;
+        }
+        let Some(payload) = platform::read() else {
+            continue;
+        };
+        let digest = digest_of(&payload);
+        let _ = observe(payload, digest, &settings);
+    }
+}
+
+/// Records `payload` unless it was already seen last poll or an excluded app is frontmost.
+fn observe(payload: Payload, digest: String, settings: &ClipboardSettings) -> Result<(), String> {
+    {
+        let mut store = lock()?;
+        if store.last_digest.as_ref() == Some(&digest) {
+            return Ok(());
+        }
+        store.last_digest = Some(digest.clone());
+    }
+    // NOTE: look up the source app outside the lock; it shells out
+    let source_app = platform::frontmost_app();
+    if is_excluded(source_app.as_deref(), &settings.excluded_apps) {
+        return Ok(());
+    }
+
+    let mut store = lock()?;
+    let now = now_secs();
+    if !touch(&mut store.entries, &digest, source_app.clone(), now) {
+        let id = next_id(&store.entries);
+        let content = match &payload {
+            Payload::Text(text) => ClipContent::Text { text: text.clone() },
+            Payload::Files(paths) => ClipContent::Files {
+                paths: paths.clone(),
+            },
+            Payload::Image(png) => {
+                store.vault.write(&store.image_path(id), png)?;
+                let (width, height) = png_dimensions(png).unwrap_or_default();
+                ClipContent::Image {
+                    width,
+                    height,
+                    bytes: png.len(),
+                }
+            }
+        };
+        store.entries.insert(
+            0,
+            ClipEntry {
+                id,
+                content,
+                copied_at: now,
+                pinned: false,
+                source_app,
+                digest,
+            },
+        );
+    }
+    let removed = prune(
+        &mut store.entries,
+        settings.max_entries,
+        settings.retention_days,
+        now,
+    );
+    store.discard(&removed);
+    store.persist()
+}
+
+/// Pinned entries first, then most recent; matches text, file paths and source app.
+pub fn search(query: &str, limit: usize) -> Vec<ClipEntry> {
+    let Ok(store) = lock() else {
+        return Vec::new();
+    };
+    search_entries(&store.entries, query, limit)
+}
+
+/// Puts an entry back on the system clipboard.
+pub fn restore(id: u64) -> Result<(), String> {
+    let payload = {
+        let store = lock()?;
+        let entry = find(&store.entries, id)?;
+        store.payload(entry)?
+    };
+    platform::write(&payload)
+}
+
+/// Sends the platform paste shortcut once the launcher window has given up focus, then
+/// moves the caret `cursor_back` characters left.
+pub fn paste_after_delay(cursor_back: usize) {
+    std::thread::spawn(move || {
+        std::thread::sleep(PASTE_DELAY);
+        let _ = platform::send_paste(cursor_back);
+    });
+}
+
+/// The text currently on the system clipboard, if any.
+pub fn current_text() -> Option<String> {
+    match platform::read_current()? {
+        Payload::Text(text) => Some(text),
+        _ => None,
+    }
+}
+
+pub fn copy_text(text: &str) -> Result<(), String> {
+    platform::write(&Payload::Text(text.to_string()))
+}
+
+/// Flips the pinned flag and returns the new state.
+pub fn toggle_pin(id: u64) -> Result<bool, String> {
+    let mut store = lock()?;
+    let entry = store
+        .entries
+        .iter_mut()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| format!("Unknown clipboard entry: {}", id))?;
+    entry.pinned = !entry.pinned;
+    let pinned = entry.pinned;
+    store.persist()?;
+    Ok(pinned)
+}
+
+pub fn delete(id: u64) -> Result<(), String> {
+    let mut store = lock()?;
+    let index = store
+        .entries
+        .iter()
+        .position(|entry| entry.id == id)
+        .ok_or_else(|| format!("Unknown clipboard entry: {}", id))?;
+    let removed = store.entries.remove(index);
+    store.discard(&[removed]);
+    store.persist()
+}
+
+/// Removes every entry except pinned ones.
+pub fn clear() -> Result<(), String> {
+    let mut store = lock()?;
+    let (pinned, removed): (Vec<_>, Vec<_>) =
+        store.entries.drain(..).partition(|entry| entry.pinned);
+    store.entries = pinned;
+    store.discard(&removed);
+    store.persist()
+}
+
+fn lock() -> Result<std::sync::MutexGuard<'static, Store>, String> {
+    STORE
+        .get()
+        .ok_or_else(|| "Clipboard history is unavailable".to_string())?
+        .lock()
+        .map_err(|_| "Clipboard history is unavailable".to_string())
+}
+
+fn find(entries: &[ClipEntry], id: u64) -> Result<&ClipEntry, String> {
+    entries
+        .iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| format!("Unknown clipboard entry: {}", id))
+}
+
+fn search_entries(entries: &[ClipEntry], query: &str, limit: usize) -> Vec<ClipEntry> {
+    let query = query.trim().to_lowercase();
+    let mut matches: Vec<&ClipEntry> = entries
+        .iter()
+        .filter(|entry| entry.matches(&query))
+        .collect();
+    // NOTE: stable sort keeps recency order within pinned and unpinned entries
+    matches.sort_by_key(|entry| !entry.pinned);
+    matches.into_iter().take(limit).cloned().collect()
+}
+
+/// Moves an existing entry with the same content to the top. Returns whether one was found.
+fn touch(entries: &mut Vec<ClipEntry>, digest: &str, source_app: Option<String>, now: u64) -> bool {
+    let Some(index) = entries.iter().position(|entry| entry.digest == digest) else {
+        return false;
+    };
+    let mut entry = entries.remove(index);
+    entry.copied_at = now;
+    entry.source_app = source_app.or(entry.source_app);
+    entries.insert(0, entry);
+    true
+}
+
+/// Drops unpinned entries beyond `max_entries` or older than `retention_days`
+/// (`0` keeps them forever) and returns what was removed.
+fn prune(
+    entries: &mut Vec<ClipEntry>,
+    max_entries: usize,
+    retention_days: u64,
+    now: u64,
+) -> Vec<ClipEntry> {
+    let cutoff = now.saturating_sub(retention_days.saturating_mul(86_400));
+    let mut unpinned = 0;
+    let mut removed = Vec::new();
+    entries.retain(|entry| {
+        if entry.pinned {
+            return true;
+        }
+        unpinned += 1;
+        let keep = unpinned <= max_entries && (retention_days == 0 || entry.copied_at >= cutoff);
+        if !keep {
+            removed.push(entry.clone());
+        }
+        keep
+    });
+    removed
+}
+
+fn next_id(entries: &[ClipEntry]) -> u64 {
+    let max = entries.iter().map(|entry| entry.id).max().unwrap_or(0);
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    millis.max(max + 1)
+}
+
+fn is_excluded(source_app: Option<&str>, excluded_apps: &[String]) -> bool {
+    source_app.is_some_and(|app| {
+        excluded_apps
+            .iter()
+            .any(|excluded| excluded.trim().eq_ignore_ascii_case(app.trim()))
+    })
+}
+
+/// Stored with the history, so it must not change between builds the way `std`'s
+/// hashers may.
+fn digest_of(payload: &Payload) -> String {
+    let mut hasher = Sha256::new();
+    match payload {
+        Payload::Text(text) => {
+            hasher.update(b"text\0");
+            hasher.update(text.as_bytes());
+        }
+        Payload::Image(png) => {
+            hasher.update(b"image\0");
+            hasher.update(png);
+        }
+        Payload::Files(paths) => {
+            hasher.update(b"files\0");
+            for path in paths {
+                hasher.update(path.as_bytes());
+                hasher.update(b"\0");
+            }
+        }
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Width and height from the PNG `IHDR` chunk.
+fn png_dimensions(png: &[u8]) -> Option<(u32, u32)> {
+    if !png.starts_with(PNG_SIGNATURE) || png.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(png.get(16..20)?.try_into().ok()?);
+    let height = u32::from_be_bytes(png.get(20..24)?.try_into().ok()?);
+    Some((width, height))
+}
+
+fn format_age(now: u64, then: u64) -> String {
+    let secs = now.saturating_sub(then);
+    match secs {
+        0..=59 => "just now".into(),
+        60..=3_599 => format!("{}m ago", secs / 60),
+        3_600..=86_399 => format!("{}h ago", secs / 3_600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_entry(id: u64, text: &str, copied_at: u64) -> ClipEntry {
+        let payload = Payload::Text(text.into());
+        ClipEntry {
+            id,
+            content: ClipContent::Text { text: text.into() },
+            copied_at,
+            pinned: false,
+            source_app: None,
+            digest: digest_of(&payload),
+        }
+    }
+
+    #[test]
+    fn repeated_copies_move_to_the_top() {
+        let mut entries = vec![text_entry(2, "b", 20), text_entry(1, "a", 10)];
+        let digest = digest_of(&Payload::Text("a".into()));
+        assert!(touch(&mut entries, &digest, Some("Terminal".into()), 30));
+        assert_eq!(entries[0].id, 1);
+        assert_eq!(entries[0].copied_at, 30);
+        assert_eq!(entries[0].source_app.as_deref(), Some("Terminal"));
+        assert!(!touch(
+            &mut entries,
+            &digest_of(&Payload::Text("c".into())),
+            None,
+            40
+        ));
+    }
+
+    #[test]
+    fn digests_are_stable_sha256() {
+        assert_eq!(
+            digest_of(&Payload::Text("hello".into())),
+            "306f89347195cc05509ddca47462e259aac6fd01943d2557004ea6f26370cd58"
+        );
+        assert_ne!(
+            digest_of(&Payload::Files(vec!["ab".into()])),
+            digest_of(&Payload::Files(vec!["a".into(), "b".into()]))
+        );
+        let legacy: ClipEntry = serde_json::from_str(
+            r#"{"id":1,"content":{"kind":"text","text":"hi"},"copied_at":5,"digest":1234}"#,
+        )
+        .unwrap();
+        assert!(legacy.digest.is_empty());
+    }
+
+    #[test]
+    fn pruning_keeps_pinned_entries_past_both_limits() {
+        let day = 86_400;
+        let mut entries = vec![
+            text_entry(4, "newest", 10 * day),
+            text_entry(3, "pinned", day),
+            text_entry(2, "second", 9 * day),
+            text_entry(1, "stale", 0),
+        ];
+        entries[1].pinned = true;
+        let removed = prune(&mut entries, 2, 5, 10 * day);
+        let ids: Vec<u64> = entries.iter().map(|entry| entry.id).collect();
+        assert_eq!(ids, vec![4, 3, 2]);
+        assert_eq!(removed.len(), 1);
+
+        let removed = prune(&mut entries, 1, 0, 10 * day);
+        assert_eq!(removed[0].id, 2);
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn search_lists_pinned_entries_first() {
+        let mut entries = vec![
+            text_entry(3, "git push origin main", 30),
+            text_entry(2, "hello world", 20),
+            text_entry(1, "git status", 10),
+        ];
+        entries[2].pinned = true;
+        let ids = |query: &str| -> Vec<u64> {
+            search_entries(&entries, query, 10)
+                .iter()
+                .map(|entry| entry.id)
+                .collect()
+        };
+        assert_eq!(ids("GIT"), vec![1, 3]);
+        assert_eq!(ids(""), vec![1, 3, 2]);
+        assert!(ids("missing").is_empty());
+    }
+
+    #[test]
+    fn previews_are_single_line_and_bounded() {
+        let entry = text_entry(1, "first line\n\tsecond", 0);
+        assert_eq!(entry.preview(), "first line second");
+        let long = text_entry(2, &"é".repeat(100), 0);
+        assert_eq!(long.preview().chars().count(), PREVIEW_CHARS + 1);
+        let files = ClipEntry {
+            content: ClipContent::Files {
+                paths: vec!["/tmp/a.txt".into(), "/tmp/b.png".into()],
+            },
+            ..text_entry(3, "", 0)
+        };
+        assert_eq!(files.preview(), "2 files: a.txt, b.png");
+        assert_eq!(files.describe(7_200), "Copied 2h ago");
+    }
+
+    #[test]
+    fn excluded_apps_match_case_insensitively() {
+        let excluded = vec!["KeePassXC".to_string()];
+        assert!(is_excluded(Some("keepassxc"), &excluded));
+        assert!(!is_excluded(Some("Firefox"), &excluded));
+        assert!(!is_excluded(None, &excluded));
+    }
+
+    #[test]
+    fn reads_png_dimensions() {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend([0, 0, 0, 13]);
+        png.extend(b"IHDR");
+        png.extend(640u32.to_be_bytes());
+        png.extend(480u32.to_be_bytes());
+        assert_eq!(png_dimensions(&png), Some((640, 480)));
+        assert_eq!(png_dimensions(b"GIF89a"), None);
+    }
+}
@@ -0,0 +1,533 @@
+#[cfg(any(target_os = "macos", target_os = "windows", test))]
+use base64::Engine;
+#[cfg(any(target_os = "macos", target_os = "windows", test))]
+use serde::Deserialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Images larger than this are skipped rather than recorded.
+pub(super) const MAX_IMAGE_BYTES: usize = 16 * 1024 * 1024;
+
+/// What the system clipboard holds, in the formats the history keeps.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Payload {
+    Text(String),
+    /// PNG-encoded image data.
+    Image(Vec<u8>),
+    Files(Vec<String>),
+}
+
+pub(super) use os::{frontmost_app, read, read_current, send_paste, write};
+
+/// JSON printed by the macOS and Windows clipboard scripts.
+#[cfg(any(target_os = "macos", target_os = "windows", test))]
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ScriptReading {
+    /// Set by password managers that mark their copies as not-for-history.
+    concealed: bool,
+    unchanged: bool,
+    change: Option<i64>,
+    text: Option<String>,
+    files: Vec<String>,
+    png: Option<String>,
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", test))]
+fn parse_script_reading(json: &str) -> Option<ScriptReading> {
+    serde_json::from_str(json.trim()).ok()
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", test))]
+impl ScriptReading {
+    fn into_payload(self) -> Option<Payload> {
+        if self.concealed || self.unchanged {
+            return None;
+        }
+        if !self.files.is_empty() {
+            return Some(Payload::Files(self.files));
+        }
+        if let Some(text) = self.text.filter(|text| !text.is_empty()) {
+            return Some(Payload::Text(text));
+        }
+        let png = base64::engine::general_purpose::STANDARD
+            .decode(self.png?)
+            .ok()?;
+        (png.len() <= MAX_IMAGE_BYTES).then_some(Payload::Image(png))
+    }
+}
+
+/// The JSON accepted on stdin by the macOS and Windows write scripts.
+#[cfg(any(target_os = "macos", target_os = "windows", test))]
+fn script_input(payload: &Payload) -> String {
+    let value = match payload {
+        Payload::Text(text) => serde_json::json!({ "text": text }),
+        Payload::Files(paths) => serde_json::json!({ "files": paths }),
+        Payload::Image(png) => serde_json::json!({
+            "png": base64::engine::general_purpose::STANDARD.encode(png)
+        }),
+    };
+    value.to_string()
+}
+
+fn run_with_input(mut command: Command, input: &[u8]) -> Result<(), String> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {:?}: {}", command.get_program(), e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input).map_err(|e| e.to_string())?;
+    }
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err("Failed to write to the clipboard".into())
+    }
+}
+
+fn output_of(command: &mut Command) -> Option<Vec<u8>> {
+    let output = command.stderr(Stdio::null()).output().ok()?;
+    output.status.success().then_some(output.stdout)
+}
+
+fn trimmed_output(command: &mut Command) -> Option<String> {
+    let output = String::from_utf8(output_of(command)?).ok()?;
+    let output = output.trim();
+    (!output.is_empty()).then(|| output.to_string())
+}
+
+#[cfg(target_os = "macos")]
+mod os {
+    use super::{output_of, run_with_input, script_input, trimmed_output, Payload};
+    use std::process::Command;
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    /// Prints `{"unchanged":true}` while the pasteboard change count matches `argv[0]`,
+    /// so the poll loop does not copy image data out every second.
+    const READ_SCRIPT: &str = r#"ObjC.import('AppKit');
+function run(argv) {
+  var pb = $.NSPasteboard.generalPasteboard;
+  var out = { change: pb.changeCount };
+  if (String(out.change) === argv[0]) { out.unchanged = true; return JSON.stringify(out); }
+  var types = ObjC.deepUnwrap(pb.types) || [];
+  var has = function (type) { return types.indexOf(type) >= 0; };
+  if (has('org.nspasteboard.ConcealedType') || has('org.nspasteboard.TransientType')) {
+    out.concealed = true;
+  } else if (has('public.file-url')) {
+    var urls = pb.readObjectsForClassesOptions($([$.NSURL]), $());
+    out.files = [];
+    for (var i = 0; i < urls.count; i++) out.files.push(ObjC.unwrap(urls.objectAtIndex(i).path));
+  } else if (has('public.utf8-plain-text')) {
+    out.text = ObjC.unwrap(pb.stringForType('public.utf8-plain-text'));
+  } else if (has('public.png')) {
+    out.png = ObjC.unwrap(pb.dataForType('public.png').base64EncodedStringWithOptions(0));
+  } else if (has('public.tiff')) {
+    var rep = $.NSBitmapImageRep.imageRepWithData(pb.dataForType('public.tiff'));
+    var png = rep.representationUsingTypeProperties($.NSBitmapImageFileTypePNG, $());
+    out.png = ObjC.unwrap(png.base64EncodedStringWithOptions(0));
+  }
+  return JSON.stringify(out);
+}"#;
+
+    const WRITE_SCRIPT: &str = r#"ObjC.import('AppKit');
+function run() {
+  var data = $.NSFileHandle.fileHandleWithStandardInput.readDataToEndOfFile;
+  var input = JSON.parse(ObjC.unwrap($.NSString.alloc.initWithDataEncoding(data, $.NSUTF8StringEncoding)));
+  var pb = $.NSPasteboard.generalPasteboard;
+  pb.clearContents;
+  if (input.files) {
+    pb.writeObjects($(input.files.map(function (path) { return $.NSURL.fileURLWithPath(path); })));
+  } else if (input.png) {
+    pb.setDataForType($.NSData.alloc.initWithBase64EncodedStringOptions(input.png, 0), 'public.png');
+  } else {
+    pb.setStringForType(input.text, 'public.utf8-plain-text');
+  }
+}"#;
+
+    static LAST_CHANGE_COUNT: AtomicI64 = AtomicI64::new(-1);
+
+    /// Returns `None` when nothing was copied since the previous call.
+    pub fn read() -> Option<Payload> {
+        read_since(&LAST_CHANGE_COUNT.load(Ordering::SeqCst).to_string())
+    }
+
+    pub fn read_current() -> Option<Payload> {
+        read_since("")
+    }
+
+    fn read_since(last_change: &str) -> Option<Payload> {
+        let output = output_of(Command::new("osascript").args([
+            "-l",
+            "JavaScript",
+            "-e",
+            READ_SCRIPT,
+            last_change,
+        ]))?;
+        let reading = super::parse_script_reading(&String::from_utf8_lossy(&output))?;
+        if let Some(change) = reading.change {
+            LAST_CHANGE_COUNT.store(change, Ordering::SeqCst);
+        }
+        reading.into_payload()
+    }
+
+    pub fn write(payload: &Payload) -> Result<(), String> {
+        let mut command = Command::new("osascript");
+        command.args(["-l", "JavaScript", "-e", WRITE_SCRIPT]);
+        run_with_input(command, script_input(payload).as_bytes())
+    }
+
+    pub fn frontmost_app() -> Option<String> {
+        trimmed_output(Command::new("osascript").args([
+            "-e",
+            "tell application \"System Events\" to get name of first application process whose frontmost is true",
+        ]))
+    }
+
+    pub fn send_paste(cursor_back: usize) -> Result<(), String> {
+        let script = format!(
+            "tell application \"System Events\"\nkeystroke \"v\" using command down\nrepeat {} times\nkey code 123\nend repeat\nend tell",
+            cursor_back
+        );
+        Command::new("osascript")
+            .args(["-e", &script])
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod os {
+    use super::{output_of, run_with_input, script_input, trimmed_output, Payload};
+    use std::process::Command;
+
+    const READ_SCRIPT: &str = r#"Add-Type -AssemblyName System.Windows.Forms, System.Drawing
+[Console]::OutputEncoding = [Text.Encoding]::UTF8
+$out = @{}
+$data = [Windows.Forms.Clipboard]::GetDataObject()
+if ($data -eq $null) { '{}'; exit }
+if ($data.GetDataPresent('ExcludeClipboardContentFromMonitorProcessing') -or $data.GetDataPresent('Clipboard Viewer Ignore')) {
+    $out.concealed = $true
+} elseif ($data.GetDataPresent([Windows.Forms.DataFormats]::FileDrop)) {
+    $out.files = @($data.GetData([Windows.Forms.DataFormats]::FileDrop))
+} elseif ($data.GetDataPresent([Windows.Forms.DataFormats]::UnicodeText)) {
+    $out.text = [string]$data.GetData([Windows.Forms.DataFormats]::UnicodeText)
+} elseif ([Windows.Forms.Clipboard]::ContainsImage()) {
+    $ms = New-Object IO.MemoryStream
+    [Windows.Forms.Clipboard]::GetImage().Save($ms, [Drawing.Imaging.ImageFormat]::Png)
+    $out.png = [Convert]::ToBase64String($ms.ToArray())
+}
+$out | ConvertTo-Json -Compress"#;
+
+    const WRITE_SCRIPT: &str = r#"Add-Type -AssemblyName System.Windows.Forms, System.Drawing
+$request = [Console]::In.ReadToEnd() | ConvertFrom-Json
+if ($request.files) {
+    $list = New-Object Collections.Specialized.StringCollection
+    $request.files | ForEach-Object { [void]$list.Add($_) }
+    [Windows.Forms.Clipboard]::SetFileDropList($list)
+} elseif ($request.png) {
+    $ms = New-Object IO.MemoryStream(,[Convert]::FromBase64String($request.png))
+    [Windows.Forms.Clipboard]::SetImage([Drawing.Image]::FromStream($ms))
+} else {
+    [Windows.Forms.Clipboard]::SetText($request.text)
+}"#;
+
+    const FRONTMOST_SCRIPT: &str = r#"Add-Type -Name Window -Namespace Genie -MemberDefinition '
+[DllImport("user32.dll")] public static extern IntPtr GetForegroundWindow();
+[DllImport("user32.dll")] public static extern uint GetWindowThreadProcessId(IntPtr hWnd, out uint pid);'
+$processId = 0
+[void][Genie.Window]::GetWindowThreadProcessId([Genie.Window]::GetForegroundWindow(), [ref]$processId)
+(Get-Process -Id $processId).ProcessName"#;
+
+    /// Clipboard access from .NET requires a single-threaded apartment.
+    fn powershell(script: &str) -> Command {
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-NonInteractive", "-STA", "-Command", script]);
+        command
+    }
+
+    pub fn read() -> Option<Payload> {
+        let output = output_of(&mut powershell(READ_SCRIPT))?;
+        super::parse_script_reading(&String::from_utf8_lossy(&output))?.into_payload()
+    }
+
+    pub fn read_current() -> Option<Payload> {
+        read()
+    }
+
+    pub fn write(payload: &Payload) -> Result<(), String> {
+        run_with_input(powershell(WRITE_SCRIPT), script_input(payload).as_bytes())
+    }
+
+    pub fn frontmost_app() -> Option<String> {
+        trimmed_output(&mut powershell(FRONTMOST_SCRIPT))
+    }
+
+    pub fn send_paste(cursor_back: usize) -> Result<(), String> {
+        let keys = if cursor_back > 0 {
+            format!("^v{{LEFT {}}}", cursor_back)
+        } else {
+            "^v".to_string()
+        };
+        powershell(&format!(
+            "(New-Object -ComObject WScript.Shell).SendKeys('{}')",
+            keys
+        ))
+        .spawn()
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod os {
+    use super::{output_of, run_with_input, trimmed_output, Payload, MAX_IMAGE_BYTES};
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::{Mutex, OnceLock};
+
+    /// Hints set by KeePassXC and other password managers on secrets they copy.
+    const CONCEALED_TYPES: &[&str] = &[
+        "x-kde-passwordManagerHint",
+        "application/x-nspasteboard-concealed-type",
+    ];
+    const TEXT_TYPES: &[&str] = &[
+        "text/plain;charset=utf-8",
+        "UTF8_STRING",
+        "text/plain",
+        "STRING",
+    ];
+    const FILES_TYPE: &str = "text/uri-list";
+    const PNG_TYPE: &str = "image/png";
+
+    fn is_wayland() -> bool {
+        std::env::var_os("WAYLAND_DISPLAY").is_some()
+    }
+
+    /// Reads one MIME type, or the list of offered types when `mime` is `None`.
+    fn read_type(mime: Option<&str>) -> Option<Vec<u8>> {
+        let mut command;
+        if is_wayland() {
+            command = Command::new("wl-paste");
+            command.arg("--no-newline");
+            match mime {
+                Some(mime) => command.args(["--type", mime]),
+                None => command.arg("--list-types"),
+            };
+        } else {
+            command = Command::new("xclip");
+            command.args([
+                "-selection",
+                "clipboard",
+                "-o",
+                "-t",
+                mime.unwrap_or("TARGETS"),
+            ]);
+        }
+        output_of(&mut command)
+    }
+
+    /// Lines printed by `wl-paste --watch`, one per clipboard change.
+    static WAYLAND_CHANGES: AtomicU64 = AtomicU64::new(0);
+    static WAYLAND_WATCHING: OnceLock<bool> = OnceLock::new();
+    static WAYLAND_WATCH_ENDED: AtomicBool = AtomicBool::new(false);
+    static LAST_CHANGE: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+    /// Returns `None` when nothing was copied since the previous call.
+    pub fn read() -> Option<Payload> {
+        if let Some(change) = change_marker() {
+            let mut last = LAST_CHANGE.lock().ok()?;
+            if last.as_ref() == Some(&change) {
+                return None;
+            }
+            *last = Some(change);
+        }
+        read_current()
+    }
+
+    /// Something that differs whenever the clipboard gets new contents, so unchanged
+    /// contents (images especially) are not read out every poll: the X11 selection
+    /// `TIMESTAMP`, or a count of `wl-paste --watch` notifications. `None` when neither
+    /// is available and the contents must be read to find out.
+    fn change_marker() -> Option<Vec<u8>> {
+        if is_wayland() {
+            return wayland_change_count().map(|count| count.to_le_bytes().to_vec());
+        }
+        let timestamp = output_of(Command::new("xclip").args([
+            "-selection",
+            "clipboard",
+            "-o",
+            "-t",
+            "TIMESTAMP",
+        ]))?;
+        (!timestamp.is_empty()).then_some(timestamp)
+    }
+
+    fn wayland_change_count() -> Option<u64> {
+        let watching = *WAYLAND_WATCHING.get_or_init(|| {
+            let Ok(mut child) = Command::new("wl-paste")
+                .args(["--watch", "echo"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+            else {
+                return false;
+            };
+            let Some(stdout) = child.stdout.take() else {
+                return false;
+            };
+            std::thread::spawn(move || {
+                for _ in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    WAYLAND_CHANGES.fetch_add(1, Ordering::SeqCst);
+                }
+                WAYLAND_WATCH_ENDED.store(true, Ordering::SeqCst);
+                let _ = child.wait();
+            });
+            true
+        });
+        (watching && !WAYLAND_WATCH_ENDED.load(Ordering::SeqCst))
+            .then(|| WAYLAND_CHANGES.load(Ordering::SeqCst))
+    }
+
+    pub fn read_current() -> Option<Payload> {
+        let types = String::from_utf8(read_type(None)?).ok()?;
+        let types: Vec<&str> = types.lines().map(str::trim).collect();
+        if types.iter().any(|kind| CONCEALED_TYPES.contains(kind)) {
+            return None;
+        }
+        if types.contains(&FILES_TYPE) {
+            let uris = String::from_utf8(read_type(Some(FILES_TYPE))?).ok()?;
+            if let Some(files) = super::parse_uri_list(&uris) {
+                return Some(Payload::Files(files));
+            }
+        }
+        if let Some(text_type) = TEXT_TYPES.iter().find(|kind| types.contains(kind)) {
+            let text = String::from_utf8(read_type(Some(text_type))?).ok()?;
+            return (!text.is_empty()).then_some(Payload::Text(text));
+        }
+        if types.contains(&PNG_TYPE) {
+            let png = read_type(Some(PNG_TYPE))?;
+            return (png.len() <= MAX_IMAGE_BYTES).then_some(Payload::Image(png));
+        }
+        None
+    }
+
+    pub fn write(payload: &Payload) -> Result<(), String> {
+        let wayland = is_wayland();
+        let (mime, bytes) = match payload {
+            Payload::Text(text) if wayland => (TEXT_TYPES[0], text.clone().into_bytes()),
+            Payload::Text(text) => ("UTF8_STRING", text.clone().into_bytes()),
+            Payload::Image(png) => (PNG_TYPE, png.clone()),
+            Payload::Files(paths) => (FILES_TYPE, super::uri_list(paths).into_bytes()),
+        };
+        let mut command;
+        if wayland {
+            command = Command::new("wl-copy");
+            command.args(["--type", mime]);
+        } else {
+            command = Command::new("xclip");
+            command.args(["-selection", "clipboard", "-i", "-t", mime]);
+        }
+        run_with_input(command, &bytes)
+    }
+
+    /// The focused window's class via `xdotool`; Wayland offers no portable equivalent.
+    pub fn frontmost_app() -> Option<String> {
+        if is_wayland() {
+            return None;
+        }
+        trimmed_output(Command::new("xdotool").args(["getactivewindow", "getwindowclassname"]))
+    }
+
+    pub fn send_paste(cursor_back: usize) -> Result<(), String> {
+        let mut command;
+        if is_wayland() {
+            command = Command::new("wtype");
+            command.args(["-M", "ctrl", "v", "-m", "ctrl"]);
+            for _ in 0..cursor_back {
+                command.args(["-k", "Left"]);
+            }
+        } else {
+            command = Command::new("xdotool");
+            command.args(["key", "--clearmodifiers", "ctrl+v"]);
+            command.args(std::iter::repeat_n("Left", cursor_back));
+        }
+        command.spawn().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// File paths from a `text/uri-list`; `None` unless every entry is a local file.
+#[cfg(any(target_os = "linux", test))]
+fn parse_uri_list(list: &str) -> Option<Vec<String>> {
+    let paths = list
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let url = url::Url::parse(line).ok()?;
+            if url.scheme() != "file" {
+                return None;
+            }
+            let path = url.to_file_path().ok()?;
+            Some(path.to_string_lossy().to_string())
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!paths.is_empty()).then_some(paths)
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn uri_list(paths: &[String]) -> String {
+    paths
+        .iter()
+        .filter_map(|path| url::Url::from_file_path(path).ok())
+        .map(|url| format!("{}\r\n", url))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_readings_prefer_files_then_text_then_images() {
+        let reading = |json: &str| parse_script_reading(json).and_then(ScriptReading::into_payload);
+        assert_eq!(
+            reading(r#"{"change":3,"files":["/tmp/a.txt"],"text":"a.txt"}"#),
+            Some(Payload::Files(vec!["/tmp/a.txt".into()]))
+        );
+        assert_eq!(
+            reading(r#"{"text":"hello","png":"iVBORw=="}"#),
+            Some(Payload::Text("hello".into()))
+        );
+        assert_eq!(
+            reading(r#"{"png":"iVBORw=="}"#),
+            Some(Payload::Image(vec![0x89, b'P', b'N', b'G']))
+        );
+        assert_eq!(reading(r#"{"concealed":true,"text":"hunter2"}"#), None);
+        assert_eq!(reading(r#"{"change":3,"unchanged":true}"#), None);
+        assert_eq!(reading("{}"), None);
+        assert_eq!(reading("not json"), None);
+        assert_eq!(
+            script_input(&Payload::Image(vec![0x89, b'P', b'N', b'G'])),
+            r#"{"png":"iVBORw=="}"#
+        );
+    }
+
+    #[test]
+    fn uri_lists_round_trip_local_files_only() {
+        let paths = vec!["/tmp/a b.txt".to_string(), "/home/me/ü.png".to_string()];
+        assert_eq!(parse_uri_list(&uri_list(&paths)), Some(paths));
+        assert_eq!(
+            parse_uri_list("# comment\r\nfile:///tmp/x\r\n"),
+            Some(vec!["/tmp/x".into()])
+        );
+        assert_eq!(parse_uri_list("https://example.com/\n"), None);
+        assert_eq!(parse_uri_list(""), None);
+    }
+}
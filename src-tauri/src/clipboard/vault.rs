@@ -0,0 +1,103 @@
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Seals clipboard data with ChaCha20-Poly1305 before it touches the disk. The key is
+/// generated per install and kept in an owner-only file beside the history, so copied
+/// history or backups are unreadable without it.
+pub(super) struct Vault {
+    cipher: ChaCha20Poly1305,
+}
+
+impl Vault {
+    pub fn open(key_path: &Path) -> Result<Self, String> {
+        let key = match fs::read(key_path) {
+            Ok(bytes) if bytes.len() == KEY_LEN => *Key::from_slice(&bytes),
+            Ok(_) => return Err(format!("Corrupt clipboard key at {}", key_path.display())),
+            Err(_) => {
+                let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+                write_private(key_path, &key)?;
+                key
+            }
+        };
+        Ok(Self {
+            cipher: ChaCha20Poly1305::new(&key),
+        })
+    }
+
+    /// Returns the random nonce followed by the ciphertext.
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| "Failed to encrypt clipboard data".to_string())?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        Ok(sealed)
+    }
+
+    pub fn unseal(&self, sealed: &[u8]) -> Result<Vec<u8>, String> {
+        if sealed.len() < NONCE_LEN {
+            return Err("Clipboard data is truncated".into());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Failed to decrypt clipboard data".to_string())
+    }
+
+    pub fn write(&self, path: &Path, plaintext: &[u8]) -> Result<(), String> {
+        write_private(path, &self.seal(plaintext)?)
+    }
+
+    pub fn read(&self, path: &Path) -> Result<Vec<u8>, String> {
+        let sealed = fs::read(path).map_err(|e| e.to_string())?;
+        self.unseal(&sealed)
+    }
+}
+
+/// Writes through a temporary file so a crash never leaves half a history behind.
+fn write_private(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let temp = path.with_extension("tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&temp).map_err(|e| e.to_string())?;
+    file.write_all(bytes).map_err(|e| e.to_string())?;
+    file.sync_all().map_err(|e| e.to_string())?;
+    fs::rename(&temp, path).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_data_round_trips_and_rejects_tampering() {
+        let dir = std::env::temp_dir().join(format!("genie-vault-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let key_path = dir.join("clipboard.key");
+        let vault = Vault::open(&key_path).unwrap();
+        let mut sealed = vault.seal(b"secret").unwrap();
+        assert!(!sealed.windows(6).any(|window| window == b"secret"));
+        assert_eq!(vault.unseal(&sealed).unwrap(), b"secret");
+
+        let reopened = Vault::open(&key_path).unwrap();
+        assert_eq!(reopened.unseal(&sealed).unwrap(), b"secret");
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert!(vault.unseal(&sealed).is_err());
+        assert!(vault.unseal(&[0; 4]).is_err());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
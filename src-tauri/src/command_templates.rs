@@ -0,0 +1,429 @@
+use crate::settings::{self, CommandTemplate};
+
+/// Placeholder that expands to every remaining query argument.
+const REST_PLACEHOLDER: &str = "{*}";
+
+#[derive(Debug, Clone)]
+pub struct RenderedCommand {
+    pub template: CommandTemplate,
+    pub argv: Vec<String>,
+    /// Placeholders still waiting for an argument; the command cannot run until this is empty.
+    pub missing: Vec<String>,
+    /// Why the query arguments cannot be used as given, e.g. an option-like value.
+    pub problem: Option<String>,
+}
+
+impl RenderedCommand {
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.problem.is_none() && !self.argv.is_empty()
+    }
+
+    /// Shell-quoted rendering used for previews; never executed through a shell.
+    pub fn preview(&self) -> String {
+        self.argv
+            .iter()
+            .map(|arg| quote_for_display(arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Matches `<keyword> <args…>` queries against the configured templates.
+pub fn render_query(query: &str) -> Option<RenderedCommand> {
+    render_with(&settings::get().command_templates, query)
+}
+
+pub fn run(query: &str) -> Result<(), String> {
+    let Some(rendered) = render_query(query) else {
+        return Err(format!("No command template matches: {}", query));
+    };
+    if let Some(problem) = rendered.problem {
+        return Err(problem);
+    }
+    if !rendered.is_complete() {
+        return Err(format!(
+            "Missing arguments for {}: {}",
+            rendered.template.keyword,
+            rendered.missing.join(", ")
+        ));
+    }
+    if rendered.template.run_in_terminal {
+        return terminal_command(&rendered.argv)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("Failed to open a terminal for {}: {}", rendered.argv[0], e));
+    }
+    std::process::Command::new(&rendered.argv[0])
+        .args(&rendered.argv[1..])
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", rendered.argv[0], e))?;
+    Ok(())
+}
+
+fn render_with(templates: &[CommandTemplate], query: &str) -> Option<RenderedCommand> {
+    let trimmed = query.trim_start();
+    let (keyword, rest) = trimmed
+        .split_once(char::is_whitespace)
+        .unwrap_or((trimmed, ""));
+    let template = templates
+        .iter()
+        .find(|template| template.keyword.eq_ignore_ascii_case(keyword))?;
+    let tokens = split_args(&template.template).ok()?;
+    let args = split_args(rest).ok()?;
+    let filled = fill_placeholders(&tokens, &args);
+    Some(RenderedCommand {
+        template: template.clone(),
+        argv: filled.argv,
+        missing: filled.missing,
+        problem: filled.problem,
+    })
+}
+
+struct Filled {
+    argv: Vec<String>,
+    missing: Vec<String>,
+    problem: Option<String>,
+}
+
+/// Substitutes `{name}` placeholders in order of first appearance. Each query argument
+/// replaces text inside a single argv entry, so it can never introduce extra arguments.
+/// A value that would begin an argument with `-` is refused, since the program would
+/// read it as an option (`ssh -oProxyCommand=…`); only `{*}` passes options through.
+fn fill_placeholders(tokens: &[String], args: &[String]) -> Filled {
+    let mut names: Vec<String> = Vec::new();
+    for token in tokens {
+        for name in placeholder_names(token) {
+            if name != REST_PLACEHOLDER && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+
+    let mut values = args.iter();
+    let bound: Vec<(String, Option<String>)> = names
+        .into_iter()
+        .map(|name| {
+            let value = values.next().cloned();
+            (name, value)
+        })
+        .collect();
+    let rest: Vec<String> = values.cloned().collect();
+    let takes_rest = tokens.iter().any(|token| token == REST_PLACEHOLDER);
+
+    let mut problem = None;
+    if !takes_rest && !rest.is_empty() {
+        problem = Some(format!("Unexpected arguments: {}", rest.join(" ")));
+    }
+    let mut argv = Vec::new();
+    for token in tokens {
+        if token == REST_PLACEHOLDER {
+            argv.extend(rest.iter().cloned());
+            continue;
+        }
+        let leading_option = bound.iter().find(|(name, value)| {
+            token.starts_with(name.as_str())
+                && value.as_ref().is_some_and(|value| value.starts_with('-'))
+        });
+        if let (None, Some((name, Some(value)))) = (&problem, leading_option) {
+            problem = Some(format!("{} cannot start with '-': {}", name, value));
+        }
+        argv.push(render_token(token, &bound));
+    }
+    let missing = bound
+        .into_iter()
+        .filter(|(_, value)| value.is_none())
+        .map(|(name, _)| name)
+        .collect();
+    Filled {
+        argv,
+        missing,
+        problem,
+    }
+}
+
+/// Replaces placeholders in a single left-to-right pass so substituted values are never rescanned.
+fn render_token(token: &str, bound: &[(String, Option<String>)]) -> String {
+    let mut rendered = String::new();
+    let mut rest = token;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let value = bound.iter().find_map(|(name, value)| {
+            let value = value.as_ref()?;
+            rest[start..]
+                .starts_with(name.as_str())
+                .then_some((name.len(), value))
+        });
+        match value {
+            Some((len, value)) => {
+                rendered.push_str(value);
+                rest = &rest[start + len..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+fn placeholder_names(token: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = token;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start..start + len + 1];
+        if name.len() > 2 && !name[1..name.len() - 1].contains(char::is_whitespace) {
+            names.push(name.to_string());
+        }
+        rest = &rest[start + len + 1..];
+    }
+    names
+}
+
+/// Splits a command line into arguments using POSIX-like quoting rules
+/// (single quotes are literal, double quotes allow `\"` escapes).
+fn split_args(input: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(inner) => current.push(inner),
+                        None => return Err("Unterminated single quote".into()),
+                    }
+                }
+            }
+            '"' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '\\')) => current.push(escaped),
+                            Some(other) => {
+                                current.push('\\');
+                                current.push(other);
+                            }
+                            None => return Err("Unterminated double quote".into()),
+                        },
+                        Some(inner) => current.push(inner),
+                        None => return Err("Unterminated double quote".into()),
+                    }
+                }
+            }
+            '\\' => {
+                in_token = true;
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_token {
+                    args.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            c => {
+                in_token = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_token {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+fn quote_for_display(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_alphanumeric() || "-_./:@=,+%{}*".contains(c));
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Quotes every argument, so `sh` reads the line back as exactly `argv`.
+#[cfg(unix)]
+fn shell_command_line(argv: &[String]) -> String {
+    argv.iter()
+        .map(|arg| format!("'{}'", arg.replace('\'', "'\\''")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Terminals known to run the words after `-e` as the program and its arguments.
+#[cfg(target_os = "linux")]
+const ARGV_TERMINALS: &[&str] = &["alacritty", "kitty", "konsole", "st", "urxvt", "urxvtc"];
+
+#[cfg(target_os = "linux")]
+fn terminal_command(argv: &[String]) -> std::process::Command {
+    let terminal = std::env::var("TERMINAL").unwrap_or_else(|_| "x-terminal-emulator".into());
+    let mut command = std::process::Command::new(&terminal);
+    command.args(terminal_args(&terminal, argv));
+    command
+}
+
+/// Others, like xterm and the `x-terminal-emulator` wrappers, join everything after `-e`
+/// and split it again, which would break a value with spaces into several arguments.
+/// They get one quoted command line that splits back into `argv`.
+#[cfg(target_os = "linux")]
+fn terminal_args(terminal: &str, argv: &[String]) -> Vec<String> {
+    let name = std::path::Path::new(terminal)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(terminal);
+    let mut args = vec!["-e".to_string()];
+    if ARGV_TERMINALS.contains(&name) {
+        args.extend(argv.iter().cloned());
+    } else {
+        args.push(shell_command_line(argv));
+    }
+    args
+}
+
+#[cfg(target_os = "macos")]
+fn terminal_command(argv: &[String]) -> std::process::Command {
+    let command_line = shell_command_line(argv);
+    let script = format!(
+        r#"tell application "Terminal" to do script "{}""#,
+        command_line.replace('\\', "\\\\").replace('"', "\\\"")
+    );
+    let mut command = std::process::Command::new("osascript");
+    command.arg("-e").arg(script);
+    command
+}
+
+/// Starts the program itself in a new console window. Going through `cmd /K` would let
+/// cmd.exe re-parse `&`, `|` and friends inside the filled-in values.
+#[cfg(target_os = "windows")]
+fn terminal_command(argv: &[String]) -> std::process::Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+    let mut command = std::process::Command::new(&argv[0]);
+    command.args(&argv[1..]).creation_flags(CREATE_NEW_CONSOLE);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(keyword: &str, command: &str) -> CommandTemplate {
+        CommandTemplate {
+            keyword: keyword.into(),
+            title: String::new(),
+            template: command.into(),
+            run_in_terminal: false,
+        }
+    }
+
+    #[test]
+    fn split_args_respects_quotes_and_escapes() {
+        assert_eq!(
+            split_args(r#"a 'b c' "d \"e\"" f\ g"#).unwrap(),
+            vec!["a", "b c", "d \"e\"", "f g"]
+        );
+        assert!(split_args("'open").is_err());
+    }
+
+    #[test]
+    fn placeholders_are_filled_in_order() {
+        let templates = vec![template("ssh", "ssh -p {port} {host}")];
+        let rendered = render_with(&templates, "ssh 2222 prod-db").unwrap();
+        assert_eq!(rendered.argv, vec!["ssh", "-p", "2222", "prod-db"]);
+        assert!(rendered.is_complete());
+
+        let partial = render_with(&templates, "SSH 2222").unwrap();
+        assert_eq!(partial.missing, vec!["{host}".to_string()]);
+        assert!(!partial.is_complete());
+    }
+
+    #[test]
+    fn query_text_cannot_inject_arguments_or_shell() {
+        let templates = vec![template("logs", "docker logs -f {arg}")];
+        let rendered = render_with(&templates, r#"logs "web; rm -rf ~""#).unwrap();
+        assert_eq!(rendered.argv, vec!["docker", "logs", "-f", "web; rm -rf ~"]);
+        assert_eq!(rendered.preview(), "docker logs -f 'web; rm -rf ~'");
+    }
+
+    #[test]
+    fn option_like_values_and_extra_words_are_refused() {
+        let templates = vec![
+            template("ssh", "ssh {host}"),
+            template("grep", "grep --regexp={pattern} {file}"),
+        ];
+        let option = render_with(&templates, "ssh -oProxyCommand=calc").unwrap();
+        assert!(!option.is_complete());
+        assert!(option.problem.unwrap().contains("{host}"));
+
+        let embedded = render_with(&templates, "grep -v notes.txt").unwrap();
+        assert_eq!(embedded.argv, vec!["grep", "--regexp=-v", "notes.txt"]);
+        assert!(embedded.is_complete());
+
+        let extra = render_with(&templates, "ssh prod extra words").unwrap();
+        assert_eq!(
+            extra.problem.as_deref(),
+            Some("Unexpected arguments: extra words")
+        );
+        assert!(!extra.is_complete());
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn terminal_receives_values_as_separate_arguments() {
+        let templates = vec![template("logs", "docker logs {arg}")];
+        let rendered = render_with(&templates, "logs x&calc|del").unwrap();
+        let command = terminal_command(&rendered.argv);
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(args, ["logs", "x&calc|del"]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn values_with_spaces_survive_terminals_that_resplit() {
+        let templates = vec![template("grep", "grep -r {pattern} .")];
+        let rendered = render_with(&templates, "grep 'two words; rm -rf ~'").unwrap();
+        assert_eq!(rendered.argv, ["grep", "-r", "two words; rm -rf ~", "."]);
+
+        let args = terminal_args("/usr/bin/x-terminal-emulator", &rendered.argv);
+        assert_eq!(args.len(), 2);
+        assert_eq!(args[0], "-e");
+        assert_eq!(split_args(&args[1]).unwrap(), rendered.argv);
+        let quote = terminal_args("xterm", &["echo".into(), "it's".into()]);
+        assert_eq!(split_args(&quote[1]).unwrap(), ["echo", "it's"]);
+
+        let args = terminal_args("kitty", &rendered.argv);
+        assert_eq!(args[1..], rendered.argv[..]);
+    }
+
+    #[test]
+    fn substituted_values_are_not_rescanned() {
+        let templates = vec![template("cp", "cp {src} {dst}")];
+        let rendered = render_with(&templates, "cp {dst} out").unwrap();
+        assert_eq!(rendered.argv, vec!["cp", "{dst}", "out"]);
+    }
+
+    #[test]
+    fn rest_placeholder_expands_remaining_args() {
+        let templates = vec![template("g", "git {*}")];
+        let rendered = render_with(&templates, "g commit -m 'fix it'").unwrap();
+        assert_eq!(rendered.argv, vec!["git", "commit", "-m", "fix it"]);
+        assert!(render_with(&templates, "gx status").is_none());
+    }
+}
@@ -1,12 +1,20 @@
+use crate::browsers;
+use crate::calculator;
+use crate::clipboard;
+use crate::command_templates;
+use crate::contacts::{self, ContactEntry};
+use crate::hotkeys;
 use crate::indexer;
-use crate::settings;
+use crate::processes;
+use crate::snippets;
+use crate::system_commands;
+use crate::window_switcher;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use tauri::AppHandle;
-use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use tauri::{AppHandle, Emitter};
 use url::Url;
 
 // TODO: split commands.rs into smaller command modules
@@ -16,6 +24,13 @@ pub static CAPTURING_SHORTCUT: AtomicBool = AtomicBool::new(false);
 pub static LAST_TOGGLE: AtomicU64 = AtomicU64::new(0);
 pub static LAST_SHOW_TIME: AtomicU64 = AtomicU64::new(0);
 
+const MAX_WINDOW_SWITCH_APPS: usize = 5;
+const CALC_HISTORY_KEYWORD: &str = "calc history";
+const MAX_CLIPBOARD_RESULTS: usize = 20;
+const MIN_BOOKMARK_QUERY_LEN: usize = 2;
+/// Shorter queries would match the first letters of half the descriptions on the system.
+const MIN_APP_METADATA_QUERY_LEN: usize = 3;
+
 #[tauri::command]
 pub fn set_suppress_hide(suppress: bool) {
     SUPPRESS_HIDE.store(suppress, Ordering::SeqCst);
@@ -28,29 +43,15 @@ pub fn set_capturing_shortcut(capturing: bool) {
 
 #[tauri::command]
 pub fn unregister_global_shortcut(app: AppHandle) -> Result<(), String> {
-    let settings = settings::get();
-    let shortcut_str = settings.hotkey.clone();
-    // NOTE: Tauri accepts string shortcut format directly via parse
-    let shortcut: tauri_plugin_global_shortcut::Shortcut = shortcut_str
-        .parse()
-        .map_err(|_| format!("Invalid shortcut format: {}", shortcut_str))?;
-    app.global_shortcut()
-        .unregister(shortcut)
-        .map_err(|e| format!("Failed to unregister: {}", e))?;
-    Ok(())
+    hotkeys::unregister_all(&app)
 }
 
 #[tauri::command]
 pub fn register_global_shortcut(app: AppHandle) -> Result<(), String> {
-    let settings = settings::get();
-    let shortcut_str = settings.hotkey.clone();
-    let shortcut: tauri_plugin_global_shortcut::Shortcut = shortcut_str
-        .parse()
-        .map_err(|_| format!("Invalid shortcut format: {}", shortcut_str))?;
-    app.global_shortcut()
-        .register(shortcut)
-        .map_err(|e| format!("Failed to register: {}", e))?;
-    Ok(())
+    match hotkeys::register_all(&app).into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,13 +65,6 @@ pub struct SearchResult {
     pub score: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ContactEntry {
-    pub name: String,
-    pub email: Option<String>,
-    pub phone: Option<String>,
-}
-
 #[tauri::command]
 pub fn search(query: String) -> Vec<SearchResult> {
     if query.trim().is_empty() {
@@ -81,6 +75,16 @@ pub fn search(query: String) -> Vec<SearchResult> {
     let matcher = SkimMatcherV2::default();
     let normalized_query = query.trim().to_lowercase();
 
+    if let Some((signal, filter)) = processes::parse_kill_query(&normalized_query) {
+        return search_processes(signal, &filter, &matcher);
+    }
+    if normalized_query == CALC_HISTORY_KEYWORD {
+        return calculator_history();
+    }
+    if let Some(filter) = snippets::parse_query(&query) {
+        return search_snippets(filter, &matcher);
+    }
+
     let apps = indexer::get_apps();
     append_matching_apps(&mut results, &apps, &matcher, &normalized_query);
     let no_app_results = results.iter().all(|entry| entry.category != "APP");
@@ -91,31 +95,98 @@ pub fn search(query: String) -> Vec<SearchResult> {
             append_matching_apps(&mut results, &refreshed_apps, &matcher, &normalized_query);
         }
     }
+    append_open_windows(&mut results);
+
+    if let Some(command_result) = command_template_result(&query) {
+        results.push(command_result);
+    }
 
     let file_results = search_files(&query, &matcher);
     results.extend(file_results);
+    if normalized_query.len() >= MIN_BOOKMARK_QUERY_LEN {
+        let bookmarks = search_bookmarks(&query, &matcher);
+        let history = search_history(&query, &matcher, &bookmarks);
+        results.extend(bookmarks);
+        results.extend(history);
+    }
 
-    if let Some(calc_result) = try_calculate(&query) {
+    let date_answers = calculator::try_date_time(&query);
+    if !date_answers.is_empty() {
+        for (index, answer) in date_answers.into_iter().enumerate() {
+            results.push(SearchResult {
+                id: format!("calc:date:{}", index),
+                title: answer.title,
+                subtitle: answer.subtitle,
+                category: "CALC".into(),
+                icon: "calendar-clock".into(),
+                action_data: answer.value,
+                score: 1000 - index as i64,
+            });
+        }
+    } else if let Some(calculation) = calculator::evaluate(&query) {
         results.push(SearchResult {
             id: "calc:result".into(),
-            title: calc_result.clone(),
-            subtitle: "Inline Calculator".into(),
+            title: calculation.title,
+            subtitle: match &calculation.assignment {
+                Some(name) => format!("Press Enter to store as {}", name),
+                None => "Inline Calculator".into(),
+            },
             category: "CALC".into(),
             icon: "calculator".into(),
-            action_data: calc_result,
+            action_data: calculation.value,
+            score: 1000,
+        });
+        for (index, form) in calculation.radix_forms.into_iter().enumerate() {
+            results.push(SearchResult {
+                id: format!("calc:radix:{}", form.label.to_lowercase()),
+                title: form.value.clone(),
+                subtitle: form.label.into(),
+                category: "CALC".into(),
+                icon: "calculator".into(),
+                action_data: form.value,
+                score: 999 - index as i64,
+            });
+        }
+    } else if let Some(conversion) = calculator::try_convert_units(&query) {
+        results.push(SearchResult {
+            id: "calc:units".into(),
+            title: conversion.title,
+            subtitle: conversion.subtitle,
+            category: "CALC".into(),
+            icon: "calculator".into(),
+            action_data: conversion.value,
+            score: 1000,
+        });
+    } else if let Some(conversion) = calculator::try_convert_currency(&query) {
+        results.push(SearchResult {
+            id: "calc:currency".into(),
+            title: conversion.title,
+            subtitle: conversion.subtitle,
+            category: "CALC".into(),
+            icon: "dollar-sign".into(),
+            action_data: conversion.value,
             score: 1000,
         });
     }
 
-    let system_commands = get_system_commands();
-    for cmd in &system_commands {
+    for cmd in system_commands::registry()
+        .into_iter()
+        .filter(|cmd| cmd.searchable)
+    {
+        let intent = cmd.matches_keyword(&normalized_query);
         let title_score = matcher.fuzzy_match(&cmd.title, &query);
         let sub_score = matcher.fuzzy_match(&cmd.subtitle, &query);
-        if let Some(score) = title_score.or(sub_score) {
-            if let Some(adjusted_score) = adjusted_system_score(score, cmd, &normalized_query) {
+        let keyword_score = intent.then_some(0);
+        if let Some(score) = title_score.or(sub_score).or(keyword_score) {
+            if let Some(adjusted_score) = adjusted_system_score(score, intent) {
                 results.push(SearchResult {
+                    id: format!("sys:{}", cmd.id),
+                    title: cmd.title,
+                    subtitle: cmd.subtitle,
+                    category: "SYS".into(),
+                    icon: cmd.icon,
+                    action_data: cmd.id,
                     score: adjusted_score,
-                    ..cmd.clone()
                 });
             }
         }
@@ -133,7 +204,7 @@ pub fn search(query: String) -> Vec<SearchResult> {
         });
     }
 
-    results.sort_by(|a, b| b.score.cmp(&a.score));
+    results.sort_by_key(|r| std::cmp::Reverse(r.score));
     results.truncate(64);
     results
 }
@@ -144,6 +215,11 @@ pub fn launch_item(action_data: String, category: String) -> Result<(), String>
         "APP" => is_allowed_app_target(&action_data),
         "FILE" => Path::new(&action_data).exists(),
         "WEB" => is_allowed_web_url(&action_data),
+        "CONTACT" => contacts::is_allowed_action(&action_data),
+        "WIN" => window_switcher::is_open_window(&action_data),
+        "CMD" => command_templates::render_query(&action_data)
+            .map(|rendered| rendered.is_complete())
+            .unwrap_or(false),
         _ => return Err(format!("Unsupported category: {}", category)),
     };
 
@@ -154,6 +230,21 @@ pub fn launch_item(action_data: String, category: String) -> Result<(), String>
         ));
     }
 
+    if category == "WIN" {
+        return window_switcher::focus_window(&action_data);
+    }
+    if category == "CMD" {
+        return command_templates::run(&action_data);
+    }
+    if category == "CONTACT" {
+        return contacts::run_action(&action_data);
+    }
+
+    #[cfg(target_os = "linux")]
+    if category == "APP" && indexer::is_appimage(&action_data) {
+        return indexer::launch_appimage(&action_data);
+    }
+
     #[cfg(target_os = "macos")]
     if category == "APP" {
         // Handle system preferences URLs
@@ -203,83 +294,115 @@ pub fn launch_item(action_data: String, category: String) -> Result<(), String>
     Ok(())
 }
 
+#[tauri::command]
+pub fn kill_process(action_data: String, confirmed: bool) -> Result<(), String> {
+    if !confirmed {
+        return Err("Sending a signal to a process requires confirmation".into());
+    }
+    let Some((signal, pid)) = processes::parse_action_data(&action_data) else {
+        return Err(format!("Invalid process target: {}", action_data));
+    };
+    if !processes::is_allowed_kill_target(pid) {
+        return Err(format!("Blocked kill target: {}", pid));
+    }
+    processes::send_signal(pid, signal)
+}
+
 #[tauri::command]
 pub fn calculate(expression: String) -> Option<String> {
-    try_calculate(&expression)
+    calculator::try_calculate(&expression)
 }
 
+/// Called when a calculator result is chosen: updates `ans`, stores assignments and
+/// records the history. Returns the value to copy.
 #[tauri::command]
-pub fn run_system_command(command: String) -> Result<(), String> {
-    match command.as_str() {
-        #[cfg(target_os = "macos")]
-        "sleep" => {
-            std::process::Command::new("pmset")
-                .args(["sleepnow"])
-                .spawn()
-                .map_err(|e| e.to_string())?;
-        }
-        #[cfg(target_os = "macos")]
-        "lock" => {
-            std::process::Command::new("osascript")
-                .args([
-                    "-e",
-                    "tell application \"System Events\" to keystroke \"q\" using {command down, control down}",
-                ])
-                .spawn()
-                .map_err(|e| e.to_string())?;
-        }
-        "spotify_play" => run_spotify_command("play")?,
-        "spotify_pause" => run_spotify_command("pause")?,
-        "spotify_next" => run_spotify_command("next track")?,
-        "spotify_prev" => run_spotify_command("previous track")?,
-        #[cfg(target_os = "linux")]
-        "sleep" => {
-            std::process::Command::new("systemctl")
-                .args(["suspend"])
-                .spawn()
-                .map_err(|e| e.to_string())?;
-        }
-        #[cfg(target_os = "linux")]
-        "lock" => {
-            std::process::Command::new("loginctl")
-                .args(["lock-session"])
-                .spawn()
-                .map_err(|e| e.to_string())?;
-        }
-        #[cfg(target_os = "windows")]
-        "sleep" => {
-            std::process::Command::new("rundll32.exe")
-                .args(["powrprof.dll,SetSuspendState", "0,1,0"])
-                .spawn()
-                .map_err(|e| e.to_string())?;
-        }
-        #[cfg(target_os = "windows")]
-        "lock" => {
-            std::process::Command::new("rundll32.exe")
-                .args(["user32.dll,LockWorkStation"])
-                .spawn()
-                .map_err(|e| e.to_string())?;
-        }
-        _ => return Err(format!("Unknown system command: {}", command)),
-    }
+pub fn commit_calculation(expression: String) -> Option<String> {
+    calculator::commit(&expression).map(|calculation| calculation.value)
+}
+
+#[tauri::command]
+pub fn search_clipboard(query: String) -> Vec<SearchResult> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    clipboard::search(&query, MAX_CLIPBOARD_RESULTS)
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| SearchResult {
+            id: format!("clipboard:{}", entry.id),
+            title: entry.preview(),
+            subtitle: entry.describe(now),
+            category: "CLIP".into(),
+            icon: entry.icon().into(),
+            action_data: entry.id.to_string(),
+            score: 800 - i as i64,
+        })
+        .collect()
+}
+
+/// Restores the entry, hides the launcher and pastes into the app underneath.
+#[tauri::command]
+pub fn paste_clipboard_entry(window: tauri::Window, id: u64) -> Result<(), String> {
+    clipboard::restore(id)?;
+    let _ = window.hide();
+    clipboard::paste_after_delay(0);
     Ok(())
 }
 
-#[cfg(target_os = "macos")]
-fn run_spotify_command(command: &str) -> Result<(), String> {
-    std::process::Command::new("osascript")
-        .args([
-            "-e",
-            &format!("tell application \"Spotify\" to {}", command),
-        ])
-        .spawn()
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+pub fn toggle_clipboard_pin(id: u64) -> Result<bool, String> {
+    clipboard::toggle_pin(id)
+}
+
+#[tauri::command]
+pub fn delete_clipboard_entry(id: u64) -> Result<(), String> {
+    clipboard::delete(id)
+}
+
+#[tauri::command]
+pub fn clear_clipboard_history() -> Result<(), String> {
+    clipboard::clear()
+}
+
+#[tauri::command]
+pub fn get_snippets() -> Vec<snippets::Snippet> {
+    snippets::all()
+}
+
+/// Why the saved snippets could not be loaded, shown above the snippet settings.
+#[tauri::command]
+pub fn get_snippets_error() -> Option<String> {
+    snippets::load_error()
+}
+
+#[tauri::command]
+pub fn save_snippets(snippets: Vec<snippets::Snippet>) -> Result<(), String> {
+    snippets::save(snippets)
+}
+
+#[tauri::command]
+pub fn import_snippets(path: String) -> Result<snippets::ImportSummary, String> {
+    snippets::import(&path)
+}
+
+/// Expands the snippet, copies it and pastes it into the app underneath the launcher,
+/// leaving the caret at `{cursor}`.
+#[tauri::command]
+pub fn paste_snippet(window: tauri::Window, name: String) -> Result<(), String> {
+    let expansion = snippets::expand(&name)?;
+    clipboard::copy_text(&expansion.text)?;
+    let _ = window.hide();
+    clipboard::paste_after_delay(expansion.cursor_back);
     Ok(())
 }
 
-#[cfg(not(target_os = "macos"))]
-fn run_spotify_command(_command: &str) -> Result<(), String> {
-    Err("Spotify controls are currently supported only on macOS".into())
+#[tauri::command]
+pub fn run_system_command(
+    command: String,
+    confirmed: Option<bool>,
+) -> Result<system_commands::RunOutcome, String> {
+    system_commands::run(&command, confirmed.unwrap_or(false))
 }
 
 #[tauri::command]
@@ -292,66 +415,100 @@ pub fn get_settings() -> crate::settings::AppSettings {
     crate::settings::get()
 }
 
+/// Why the saved settings could not be loaded, so the UI can say defaults are in use.
 #[tauri::command]
-pub fn save_settings(settings: crate::settings::AppSettings) -> Result<(), String> {
-    crate::settings::save(settings)
+pub fn get_settings_error() -> Option<String> {
+    crate::settings::load_error()
 }
 
-fn try_calculate(expr: &str) -> Option<String> {
-    let cleaned = expr
-        .chars()
-        .filter(|c| !c.is_whitespace() || *c == ' ')
-        .collect::<String>();
-    if cleaned.is_empty() {
-        return None;
+/// Shortcuts from the saved settings that are not working, such as ones another app
+/// already held at startup.
+#[tauri::command]
+pub fn get_hotkey_errors() -> Vec<String> {
+    hotkeys::errors()
+}
+
+/// Why `save_settings` refused or failed; nothing is saved when hotkeys conflict.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SaveSettingsError {
+    HotkeyConflict {
+        conflicts: Vec<hotkeys::HotkeyConflict>,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+/// Registers the new hotkeys before writing anything, so a shortcut that cannot be
+/// registered leaves both the saved settings and the working shortcuts unchanged.
+#[tauri::command]
+pub fn save_settings(
+    app: AppHandle,
+    settings: crate::settings::AppSettings,
+) -> Result<(), SaveSettingsError> {
+    let previous = crate::settings::get();
+    let applied = hotkeys::apply(&app, &settings)
+        .map_err(|conflicts| SaveSettingsError::HotkeyConflict { conflicts })?;
+    if let Err(message) = crate::settings::save(settings) {
+        hotkeys::revert(&app, &previous, applied);
+        return Err(SaveSettingsError::Failed { message });
+    }
+    settings_changed(&app, &previous, &crate::settings::get());
+    Ok(())
+}
+
+/// Applies a settings change outside the settings themselves and tells the frontend.
+/// Used after `save_settings` and when `settings.json` is edited by hand.
+pub fn settings_changed<R: tauri::Runtime>(
+    app: &AppHandle<R>,
+    previous: &crate::settings::AppSettings,
+    current: &crate::settings::AppSettings,
+) {
+    let rescan_apps = previous.extra_app_dirs != current.extra_app_dirs
+        || previous.excluded_apps != current.excluded_apps
+        || previous.appimage_dir != current.appimage_dir;
+    if rescan_apps {
+        indexer::reload();
     }
-    let has_operator = cleaned.contains('+')
-        || cleaned.contains('-')
-        || cleaned.contains('*')
-        || cleaned.contains('/')
-        || cleaned.contains('^')
-        || cleaned.contains('%')
-        || cleaned.contains('x');
-    if !has_operator {
+    let _ = app.emit("genie:settings-changed", current);
+}
+
+/// The name decides the score; keywords and the description only rescue apps the name
+/// does not match, and always rank below any name match.
+fn score_app_entry(matcher: &SkimMatcherV2, app: &indexer::AppEntry, query: &str) -> Option<i64> {
+    score_app_match(matcher, &app.name, query).or_else(|| score_app_metadata(app, query))
+}
+
+fn score_app_metadata(app: &indexer::AppEntry, query: &str) -> Option<i64> {
+    let normalized_query = normalize_for_match(query);
+    if normalized_query.len() < MIN_APP_METADATA_QUERY_LEN {
         return None;
     }
-    let normalized = cleaned.replace('x', "*");
-    match evaluate_expression(&normalized) {
-        Some(result) if result.is_finite() => Some(format!("{} = {}", expr, result)),
-        _ => None,
+    let words = |text: &str| -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .map(normalize_for_match)
+            .filter(|word| !word.is_empty())
+            .collect()
+    };
+    for keyword in &app.keywords {
+        let normalized_keyword = normalize_for_match(keyword);
+        if normalized_keyword == normalized_query {
+            return Some(1_100);
+        }
+        if normalized_keyword.starts_with(&normalized_query)
+            || words(keyword)
+                .iter()
+                .any(|word| word.starts_with(&normalized_query))
+        {
+            return Some(1_000);
+        }
     }
-}
-
-fn get_system_commands() -> Vec<SearchResult> {
-    vec![
-        SearchResult {
-            id: "sys:settings".into(),
-            title: "Genie Settings".into(),
-            subtitle: "Configure hotkey, theme, and more".into(),
-            category: "SYS".into(),
-            icon: "settings".into(),
-            action_data: "settings".into(),
-            score: 0,
-        },
-        SearchResult {
-            id: "sys:sleep".into(),
-            title: "Sleep Device".into(),
-            subtitle: "System command".into(),
-            category: "SYS".into(),
-            icon: "moon".into(),
-            action_data: "sleep".into(),
-            score: 0,
-        },
-        SearchResult {
-            id: "sys:lock".into(),
-            title: "Lock Screen".into(),
-            subtitle: "System command".into(),
-            category: "SYS".into(),
-            icon: "lock".into(),
-            action_data: "lock".into(),
-            score: 0,
-        },
-    ]
+    let description = app.description.as_deref()?;
+    words(description)
+        .iter()
+        .any(|word| word.starts_with(&normalized_query))
+        .then_some(900)
 }
 
 fn score_app_match(matcher: &SkimMatcherV2, app_name: &str, query: &str) -> Option<i64> {
@@ -404,11 +561,14 @@ fn append_matching_apps(
     normalized_query: &str,
 ) {
     for app in apps {
-        if let Some(score) = score_app_match(matcher, &app.name, normalized_query) {
+        if let Some(score) = score_app_entry(matcher, app, normalized_query) {
             results.push(SearchResult {
                 id: format!("app:{}", app.path),
                 title: app.name.clone(),
-                subtitle: "Application".into(),
+                subtitle: app
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| "Application".into()),
                 category: "APP".into(),
                 icon: app.icon.clone().unwrap_or_else(|| "layout-grid".into()),
                 action_data: app.path.clone(),
@@ -418,6 +578,42 @@ fn append_matching_apps(
     }
 }
 
+/// Adds a "switch to window" result for each open window of the best app matches.
+fn append_open_windows(results: &mut Vec<SearchResult>) {
+    let mut app_matches: Vec<(String, String, i64)> = results
+        .iter()
+        .filter(|result| result.category == "APP")
+        .map(|result| {
+            (
+                result.title.clone(),
+                result.action_data.clone(),
+                result.score,
+            )
+        })
+        .collect();
+    app_matches.sort_by_key(|(_, _, score)| std::cmp::Reverse(*score));
+    app_matches.truncate(MAX_WINDOW_SWITCH_APPS);
+
+    for (app_name, app_path, score) in app_matches {
+        for window in window_switcher::windows_for_app(&app_path) {
+            let title = if window.title.is_empty() {
+                app_name.clone()
+            } else {
+                window.title.clone()
+            };
+            results.push(SearchResult {
+                id: format!("win:{}", window.id),
+                title,
+                subtitle: format!("Switch to {} window", app_name),
+                category: "WIN".into(),
+                icon: "app-window".into(),
+                action_data: window.id,
+                score: score + 10,
+            });
+        }
+    }
+}
+
 fn normalize_for_match(input: &str) -> String {
     input
         .chars()
@@ -449,8 +645,7 @@ fn subsequence_coverage(title: &str, query: &str) -> f64 {
     matched as f64 / query_chars.len() as f64
 }
 
-fn adjusted_system_score(base: i64, cmd: &SearchResult, query: &str) -> Option<i64> {
-    let intent = query_is_for_system_command(query, &cmd.id);
+fn adjusted_system_score(base: i64, intent: bool) -> Option<i64> {
     let score = if intent { base + 220 } else { base - 380 };
     if intent || score >= 120 {
         return Some(score);
@@ -458,20 +653,98 @@ fn adjusted_system_score(base: i64, cmd: &SearchResult, query: &str) -> Option<i
     None
 }
 
-fn query_is_for_system_command(query: &str, command_id: &str) -> bool {
-    match command_id {
-        "sys:settings" => {
-            query.contains("setting")
-                || query.contains("theme")
-                || query.contains("hotkey")
-                || query.contains("shortcut")
-                || query.contains("config")
-                || query.contains("preference")
-        }
-        "sys:sleep" => query.contains("sleep") || query.contains("suspend"),
-        "sys:lock" => query.contains("lock"),
-        _ => false,
-    }
+fn command_template_result(query: &str) -> Option<SearchResult> {
+    let rendered = command_templates::render_query(query)?;
+    let label = if rendered.template.title.is_empty() {
+        rendered.template.keyword.clone()
+    } else {
+        rendered.template.title.clone()
+    };
+    let (subtitle, score) = if let Some(problem) = &rendered.problem {
+        (format!("{} · {}", label, problem), 200)
+    } else if rendered.is_complete() {
+        let mode = if rendered.template.run_in_terminal {
+            "Run in terminal"
+        } else {
+            "Run command"
+        };
+        (format!("{} · {}", label, mode), 1_500)
+    } else {
+        (
+            format!("{} · Missing {}", label, rendered.missing.join(", ")),
+            200,
+        )
+    };
+    Some(SearchResult {
+        id: format!("cmd:{}", rendered.template.keyword),
+        title: rendered.preview(),
+        subtitle,
+        category: "CMD".into(),
+        icon: "terminal".into(),
+        action_data: query.trim().to_string(),
+        score,
+    })
+}
+
+fn search_snippets(filter: &str, matcher: &SkimMatcherV2) -> Vec<SearchResult> {
+    snippets::search(filter, matcher)
+        .into_iter()
+        .map(|(snippet, score)| {
+            let preview = snippet
+                .text
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let subtitle = match &snippet.source {
+                Some(source) => format!("{} · {}", preview, source),
+                None => preview,
+            };
+            SearchResult {
+                id: format!("snip:{}", snippet.name),
+                title: snippet.name.clone(),
+                subtitle,
+                category: "SNIP".into(),
+                icon: "file-text".into(),
+                action_data: snippet.name,
+                score,
+            }
+        })
+        .collect()
+}
+
+fn calculator_history() -> Vec<SearchResult> {
+    calculator::history()
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| SearchResult {
+            id: format!("calc:history:{}", index),
+            title: format!("{} = {}", entry.expression, entry.value),
+            subtitle: "Calculator History".into(),
+            category: "CALC".into(),
+            icon: "calculator".into(),
+            action_data: entry.value,
+            score: 1000 - index as i64,
+        })
+        .collect()
+}
+
+fn search_processes(
+    signal: processes::KillSignal,
+    filter: &str,
+    matcher: &SkimMatcherV2,
+) -> Vec<SearchResult> {
+    processes::search(filter, matcher)
+        .into_iter()
+        .map(|(process, score)| SearchResult {
+            id: format!("proc:{}", process.pid),
+            title: process.name.clone(),
+            subtitle: processes::format_subtitle(&process, signal),
+            category: "PROC".into(),
+            icon: "activity".into(),
+            action_data: processes::action_data(&process, signal),
+            score,
+        })
+        .collect()
 }
 
 fn search_files(query: &str, matcher: &SkimMatcherV2) -> Vec<SearchResult> {
@@ -494,6 +767,53 @@ fn search_files(query: &str, matcher: &SkimMatcherV2) -> Vec<SearchResult> {
         .collect()
 }
 
+/// Browser bookmarks open like any other web result, through `is_allowed_web_url`.
+fn search_bookmarks(query: &str, matcher: &SkimMatcherV2) -> Vec<SearchResult> {
+    browsers::search_bookmarks(query, matcher)
+        .into_iter()
+        .filter(|(bookmark, _)| is_allowed_web_url(&bookmark.url))
+        .map(|(bookmark, score)| SearchResult {
+            id: format!("bookmark:{}", bookmark.url),
+            subtitle: format!(
+                "{} · {} bookmark",
+                browsers::display_url(&bookmark.url),
+                bookmark.browser
+            ),
+            title: bookmark.title,
+            category: "WEB".into(),
+            icon: "bookmark".into(),
+            action_data: bookmark.url,
+            score: score - 40,
+        })
+        .collect()
+}
+
+/// Visited pages not already listed as bookmarks. Empty unless history search is enabled.
+fn search_history(
+    query: &str,
+    matcher: &SkimMatcherV2,
+    bookmarks: &[SearchResult],
+) -> Vec<SearchResult> {
+    browsers::search_history(query, matcher)
+        .into_iter()
+        .filter(|(visit, _)| is_allowed_web_url(&visit.url))
+        .filter(|(visit, _)| !bookmarks.iter().any(|b| b.action_data == visit.url))
+        .map(|(visit, score)| SearchResult {
+            id: format!("history:{}", visit.url),
+            subtitle: format!(
+                "{} · {} history",
+                browsers::display_url(&visit.url),
+                visit.browser
+            ),
+            title: visit.title,
+            category: "WEB".into(),
+            icon: "history".into(),
+            action_data: visit.url,
+            score: score - 45,
+        })
+        .collect()
+}
+
 fn file_icon_for_ext(path: &std::path::Path) -> &'static str {
     let ext = path
         .extension()
@@ -530,166 +850,6 @@ fn urlencoding(s: &str) -> String {
         .collect()
 }
 
-#[derive(Clone, Copy, Debug)]
-enum CalcToken {
-    Number(f64),
-    Operator(char),
-    LeftParen,
-    RightParen,
-}
-
-fn evaluate_expression(expr: &str) -> Option<f64> {
-    let tokens = tokenize_expression(expr)?;
-    let rpn = to_rpn(tokens)?;
-    eval_rpn(rpn)
-}
-
-fn tokenize_expression(expr: &str) -> Option<Vec<CalcToken>> {
-    let chars: Vec<char> = expr.chars().collect();
-    let mut tokens = Vec::new();
-    let mut i = 0;
-
-    while i < chars.len() {
-        let c = chars[i];
-        if c.is_whitespace() {
-            i += 1;
-            continue;
-        }
-
-        let prev = tokens.last().copied();
-        let unary_minus = c == '-'
-            && matches!(
-                prev,
-                None | Some(CalcToken::Operator(_)) | Some(CalcToken::LeftParen)
-            );
-        let starts_number = c.is_ascii_digit() || c == '.';
-        if starts_number || unary_minus {
-            let start = i;
-            i += if unary_minus { 1 } else { 0 };
-            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
-                i += 1;
-            }
-            let num_str: String = chars[start..i].iter().collect();
-            let value = num_str.parse::<f64>().ok()?;
-            tokens.push(CalcToken::Number(value));
-            continue;
-        }
-
-        if is_operator(c) {
-            tokens.push(CalcToken::Operator(c));
-            i += 1;
-            continue;
-        }
-        if c == '(' {
-            tokens.push(CalcToken::LeftParen);
-            i += 1;
-            continue;
-        }
-        if c == ')' {
-            tokens.push(CalcToken::RightParen);
-            i += 1;
-            continue;
-        }
-        return None;
-    }
-
-    if tokens.is_empty() {
-        return None;
-    }
-    Some(tokens)
-}
-
-fn to_rpn(tokens: Vec<CalcToken>) -> Option<Vec<CalcToken>> {
-    let mut output = Vec::new();
-    let mut ops: Vec<CalcToken> = Vec::new();
-
-    for token in tokens {
-        match token {
-            CalcToken::Number(_) => output.push(token),
-            CalcToken::Operator(op1) => {
-                while let Some(CalcToken::Operator(op2)) = ops.last().copied() {
-                    let lower_or_equal = precedence(op1) <= precedence(op2) && is_left_assoc(op1);
-                    let strictly_lower = precedence(op1) < precedence(op2) && !is_left_assoc(op1);
-                    if !(lower_or_equal || strictly_lower) {
-                        break;
-                    }
-                    let popped = ops.pop()?;
-                    output.push(popped);
-                }
-                ops.push(token);
-            }
-            CalcToken::LeftParen => ops.push(token),
-            CalcToken::RightParen => {
-                while let Some(top) = ops.pop() {
-                    match top {
-                        CalcToken::LeftParen => break,
-                        CalcToken::Operator(_) => output.push(top),
-                        _ => return None,
-                    }
-                }
-            }
-        }
-    }
-
-    while let Some(top) = ops.pop() {
-        match top {
-            CalcToken::Operator(_) => output.push(top),
-            _ => return None,
-        }
-    }
-    Some(output)
-}
-
-fn eval_rpn(tokens: Vec<CalcToken>) -> Option<f64> {
-    let mut stack: Vec<f64> = Vec::new();
-    for token in tokens {
-        match token {
-            CalcToken::Number(v) => stack.push(v),
-            CalcToken::Operator(op) => {
-                let right = stack.pop()?;
-                let left = stack.pop()?;
-                let value = apply_operator(left, right, op)?;
-                stack.push(value);
-            }
-            _ => return None,
-        }
-    }
-    if stack.len() == 1 {
-        stack.first().copied()
-    } else {
-        None
-    }
-}
-
-fn is_operator(c: char) -> bool {
-    matches!(c, '+' | '-' | '*' | '/' | '%' | '^')
-}
-
-fn precedence(op: char) -> u8 {
-    match op {
-        '^' => 4,
-        '*' | '/' | '%' => 3,
-        '+' | '-' => 2,
-        _ => 0,
-    }
-}
-
-fn is_left_assoc(op: char) -> bool {
-    op != '^'
-}
-
-fn apply_operator(left: f64, right: f64, op: char) -> Option<f64> {
-    match op {
-        '+' => Some(left + right),
-        '-' => Some(left - right),
-        '*' => Some(left * right),
-        '/' => Some(left / right),
-        '%' => Some(left % right),
-        '^' => Some(left.powf(right)),
-        _ => None,
-    }
-}
-
 fn is_allowed_web_url(target: &str) -> bool {
     let parsed: url::Url = match Url::parse(target) {
         Ok(url) => url,
@@ -718,103 +878,23 @@ pub async fn get_app_icon(app_path: String) -> Option<String> {
 #[tauri::command]
 pub async fn get_contacts() -> Vec<ContactEntry> {
     let result: Result<Vec<ContactEntry>, _> =
-        tauri::async_runtime::spawn_blocking(load_contacts).await;
+        tauri::async_runtime::spawn_blocking(contacts::all).await;
     result.unwrap_or_default()
 }
 
-fn load_contacts() -> Vec<ContactEntry> {
-    #[cfg(target_os = "macos")]
-    {
-        return load_macos_contacts();
-    }
-
-    #[cfg(not(target_os = "macos"))]
-    {
-        Vec::new()
-    }
-}
-
-#[cfg(target_os = "macos")]
-fn load_macos_contacts() -> Vec<ContactEntry> {
-    let script = r#"tell application "Contacts"
-set outLines to {}
-repeat with p in every person
-try
-set personName to (name of p) as text
-set personEmail to ""
-if ((count of emails of p) > 0) then set personEmail to (value of first email of p) as text
-set personPhone to ""
-if ((count of phones of p) > 0) then set personPhone to (value of first phone of p) as text
-set end of outLines to personName & tab & personEmail & tab & personPhone
-if ((count of outLines) >= 500) then exit repeat
-end try
-end repeat
-set AppleScript's text item delimiters to linefeed
-return outLines as text
-end tell"#;
-
-    let Ok(mut child) = std::process::Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::null())
-        .spawn()
-    else {
-        return Vec::new();
-    };
-    let timeout = std::time::Duration::from_secs(8);
-    let start = std::time::Instant::now();
-    loop {
-        match child.try_wait() {
-            Ok(Some(status)) => {
-                if !status.success() {
-                    return Vec::new();
-                }
-                break;
-            }
-            Ok(None) => {
-                if start.elapsed() > timeout {
-                    let _ = child.kill();
-                    return Vec::new();
-                }
-                std::thread::sleep(std::time::Duration::from_millis(100));
-            }
-            Err(_) => return Vec::new(),
-        }
-    }
-    let Ok(output) = child.wait_with_output() else {
-        return Vec::new();
-    };
-
-    let raw = String::from_utf8_lossy(&output.stdout);
-    parse_contacts_output(raw.as_ref())
-}
-
-#[cfg(target_os = "macos")]
-fn parse_contacts_output(raw: &str) -> Vec<ContactEntry> {
-    raw.lines()
-        .filter_map(|line| {
-            let mut parts = line.splitn(3, '\t');
-            let name = parts.next()?.trim();
-            if name.is_empty() {
-                return None;
-            }
-
-            let email = parts
-                .next()
-                .map(str::trim)
-                .filter(|value| !value.is_empty())
-                .map(ToString::to_string);
-            let phone = parts
-                .next()
-                .map(str::trim)
-                .filter(|value| !value.is_empty())
-                .map(ToString::to_string);
-            Some(ContactEntry {
-                name: name.to_string(),
-                email,
-                phone,
-            })
+/// Email, call, copy and open actions for one contact, launched as `CONTACT` results.
+#[tauri::command]
+pub fn get_contact_actions(contact: ContactEntry) -> Vec<SearchResult> {
+    contacts::actions(&contact)
+        .into_iter()
+        .map(|action| SearchResult {
+            id: format!("contact-action:{}", action.target),
+            title: action.title,
+            subtitle: action.subtitle,
+            category: "CONTACT".into(),
+            icon: action.icon.into(),
+            action_data: action.target,
+            score: 0,
         })
         .collect()
 }
@@ -861,6 +941,27 @@ mod tests {
         assert!(score_app_match(&matcher, "System Preferences", "pref").is_some());
     }
 
+    #[test]
+    fn keywords_and_description_match_below_names() {
+        let matcher = SkimMatcherV2::default();
+        let firefox = indexer::AppEntry {
+            name: "Firefox".into(),
+            description: Some("Browse the World Wide Web".into()),
+            keywords: vec!["Internet".into(), "Web Browser".into()],
+            ..Default::default()
+        };
+        let browser = score_app_entry(&matcher, &firefox, "browser").unwrap();
+        let internet = score_app_entry(&matcher, &firefox, "internet").unwrap();
+        let world = score_app_entry(&matcher, &firefox, "world").unwrap();
+        assert!(
+            internet > browser && browser > world,
+            "{internet} {browser} {world}"
+        );
+        assert!(score_app_match(&matcher, "Brave Browser", "browser").unwrap() > browser);
+        assert_eq!(score_app_entry(&matcher, &firefox, "we"), None);
+        assert_eq!(score_app_entry(&matcher, &firefox, "spreadsheet"), None);
+    }
+
     #[test]
     #[cfg(target_os = "macos")]
     fn ghostty_is_discoverable_when_installed() {
@@ -869,7 +970,7 @@ mod tests {
             return;
         }
 
-        crate::indexer::init();
+        crate::indexer::refresh_apps_with_cooldown(std::time::Duration::ZERO);
         let results = search("ghostty".to_string());
         assert!(results.iter().any(|item| {
             item.category == "APP"
@@ -0,0 +1,235 @@
+use super::{ContactEntry, ContactField};
+use std::path::Path;
+
+/// Something to do with a contact; `target` is what `launch_item` receives for the
+/// `CONTACT` category.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactAction {
+    pub title: String,
+    pub subtitle: String,
+    pub icon: &'static str,
+    pub target: String,
+}
+
+/// Email and call actions first, then copies, then the card itself.
+pub fn actions(contact: &ContactEntry) -> Vec<ContactAction> {
+    let mut actions = Vec::new();
+    for email in &contact.emails {
+        actions.push(ContactAction {
+            title: format!("Email {}", contact.name),
+            subtitle: describe(email),
+            icon: "mail",
+            target: format!("mailto:{}", email.value),
+        });
+    }
+    for phone in &contact.phones {
+        actions.push(ContactAction {
+            title: format!("Call {}", contact.name),
+            subtitle: describe(phone),
+            icon: "phone",
+            target: format!("tel:{}", phone.value),
+        });
+    }
+    for email in &contact.emails {
+        actions.push(ContactAction {
+            title: "Copy Email".into(),
+            subtitle: describe(email),
+            icon: "copy",
+            target: format!("copy:{}", email.value),
+        });
+    }
+    for phone in &contact.phones {
+        actions.push(ContactAction {
+            title: "Copy Phone Number".into(),
+            subtitle: describe(phone),
+            icon: "copy",
+            target: format!("copy:{}", phone.value),
+        });
+    }
+    if let Some(source) = contact
+        .source
+        .as_deref()
+        .filter(|source| is_vcard_file(source))
+    {
+        actions.push(ContactAction {
+            title: "Open vCard".into(),
+            subtitle: source.to_string(),
+            icon: "contact",
+            target: format!("vcard:{}", source),
+        });
+    }
+    #[cfg(target_os = "macos")]
+    if let (None, Some(uid)) = (&contact.source, &contact.uid) {
+        actions.push(ContactAction {
+            title: "Open in Contacts".into(),
+            subtitle: contact.name.clone(),
+            icon: "contact",
+            target: format!("addressbook://{}", uid),
+        });
+    }
+    actions
+}
+
+fn describe(field: &ContactField) -> String {
+    match &field.label {
+        Some(label) => format!("{} ({})", field.value, label),
+        None => field.value.clone(),
+    }
+}
+
+/// Only these schemes are accepted. `copy:` and `vcard:` are handled in-process;
+/// everything else is handed to the system URL handler.
+fn is_allowed_scheme(scheme: &str) -> bool {
+    match scheme {
+        "mailto" | "tel" | "copy" | "vcard" => true,
+        #[cfg(target_os = "macos")]
+        "addressbook" => true,
+        _ => false,
+    }
+}
+
+/// Checks a `CONTACT` launch target. `known_vcard` decides whether a `vcard:` path is
+/// one of the files contacts were actually read from.
+pub fn is_allowed_target(target: &str, known_vcard: impl Fn(&str) -> bool) -> bool {
+    let Some((scheme, rest)) = target.split_once(':') else {
+        return false;
+    };
+    if !is_allowed_scheme(scheme) || rest.trim().is_empty() {
+        return false;
+    }
+    match scheme {
+        // NOTE: no query part, so a crafted address cannot add headers or a body
+        "mailto" => {
+            rest.contains('@')
+                && !rest
+                    .chars()
+                    .any(|c| c.is_whitespace() || c.is_control() || "?&%<>\"".contains(c))
+        }
+        "tel" => rest
+            .chars()
+            .all(|c| c.is_ascii_digit() || " +-().*#,;/".contains(c)),
+        "vcard" => is_vcard_file(rest) && known_vcard(rest),
+        "addressbook" => rest.strip_prefix("//").is_some_and(|uid| {
+            uid.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-:".contains(c))
+        }),
+        _ => true,
+    }
+}
+
+/// Runs a target that passed `is_allowed_target`.
+pub fn run(target: &str) -> Result<(), String> {
+    let (scheme, rest) = target
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid contact action: {}", target))?;
+    match scheme {
+        "copy" => crate::clipboard::copy_text(rest),
+        "vcard" => open::that(rest).map_err(|e| e.to_string()),
+        "tel" => open::that(format!("tel:{}", dialable(rest))).map_err(|e| e.to_string()),
+        _ => open::that(target).map_err(|e| e.to_string()),
+    }
+}
+
+/// Strips the spaces, dashes and brackets people use to format numbers.
+fn dialable(number: &str) -> String {
+    number
+        .chars()
+        .filter(|c| c.is_ascii_digit() || "+*#,;".contains(*c))
+        .collect()
+}
+
+fn is_vcard_file(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("vcf") || ext.eq_ignore_ascii_case("vcard"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(value: &str, label: Option<&str>) -> ContactField {
+        ContactField {
+            value: value.into(),
+            label: label.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn builds_actions_for_every_address() {
+        let contact = ContactEntry {
+            name: "Ada".into(),
+            email: Some("ada@example.com".into()),
+            phone: Some("+44 20 7946 0000".into()),
+            emails: vec![field("ada@example.com", Some("work"))],
+            phones: vec![field("+44 20 7946 0000", None)],
+            organization: None,
+            nicknames: Vec::new(),
+            uid: None,
+            source: Some("/cards/ada.vcf".into()),
+        };
+        let targets: Vec<_> = actions(&contact)
+            .into_iter()
+            .map(|action| (action.title, action.subtitle, action.target))
+            .collect();
+        assert_eq!(
+            targets,
+            [
+                (
+                    "Email Ada".to_string(),
+                    "ada@example.com (work)".to_string(),
+                    "mailto:ada@example.com".to_string()
+                ),
+                (
+                    "Call Ada".to_string(),
+                    "+44 20 7946 0000".to_string(),
+                    "tel:+44 20 7946 0000".to_string()
+                ),
+                (
+                    "Copy Email".to_string(),
+                    "ada@example.com (work)".to_string(),
+                    "copy:ada@example.com".to_string()
+                ),
+                (
+                    "Copy Phone Number".to_string(),
+                    "+44 20 7946 0000".to_string(),
+                    "copy:+44 20 7946 0000".to_string()
+                ),
+                (
+                    "Open vCard".to_string(),
+                    "/cards/ada.vcf".to_string(),
+                    "vcard:/cards/ada.vcf".to_string()
+                ),
+            ]
+        );
+        assert_eq!(dialable("+44 (20) 7946-0000"), "+442079460000");
+    }
+
+    #[test]
+    fn only_allowlisted_targets_pass() {
+        let known = |path: &str| path == "/cards/ada.vcf";
+        for allowed in [
+            "mailto:ada@example.com",
+            "tel:+44 (20) 7946-0000",
+            "copy:anything at all",
+            "vcard:/cards/ada.vcf",
+        ] {
+            assert!(is_allowed_target(allowed, known), "{}", allowed);
+        }
+        for blocked in [
+            "mailto:ada@example.com?bcc=eve@example.com",
+            "mailto:not-an-address",
+            "tel:+44; rm -rf /",
+            "tel:",
+            "vcard:/cards/other.vcf",
+            "vcard:/etc/passwd",
+            "https://example.com",
+            "file:///etc/passwd",
+            "sms:+4420",
+            "ada@example.com",
+        ] {
+            assert!(!is_allowed_target(blocked, known), "{}", blocked);
+        }
+    }
+}
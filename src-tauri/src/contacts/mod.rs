@@ -0,0 +1,337 @@
+mod actions;
+mod vcard;
+
+pub use actions::{actions, run as run_action};
+
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use walkdir::WalkDir;
+
+/// vdirsyncer nests `<collection>/<card>.vcf` under its storage path; a little deeper
+/// covers account directories without crawling a whole home folder.
+const MAX_SOURCE_DEPTH: usize = 4;
+/// Evolution keeps each address book as vCards inside an SQLite summary table.
+const EVOLUTION_DB: &str = "contacts.db";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContactEntry {
+    pub name: String,
+    /// Preferred email, kept alongside `emails` for callers that only show one.
+    pub email: Option<String>,
+    /// Preferred phone number, kept alongside `phones` for callers that only show one.
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub emails: Vec<ContactField>,
+    #[serde(default)]
+    pub phones: Vec<ContactField>,
+    #[serde(default)]
+    pub organization: Option<String>,
+    #[serde(default)]
+    pub nicknames: Vec<String>,
+    #[serde(default)]
+    pub uid: Option<String>,
+    /// The vCard file or address book the entry was read from.
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// One email address or phone number with its vCard type, e.g. `work` or `cell`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContactField {
+    pub value: String,
+    pub label: Option<String>,
+}
+
+struct Cache {
+    /// Hash of the configured sources and the size and mtime of every file they hold.
+    fingerprint: u64,
+    loaded_at: u64,
+    contacts: Vec<ContactEntry>,
+}
+
+static CACHE: OnceLock<Mutex<Option<Cache>>> = OnceLock::new();
+
+/// Every contact from the configured vCard sources (and Contacts.app on macOS). Parsed
+/// results are reused until a source file changes.
+pub fn all() -> Vec<ContactEntry> {
+    let sources = settings::get().contacts.sources;
+    let files = source_files(&sources);
+    let fingerprint = fingerprint(&sources, &files);
+    let now = now_secs();
+    let cache = CACHE.get_or_init(|| Mutex::new(None));
+    if let Ok(guard) = cache.lock() {
+        if let Some(cached) = guard.as_ref() {
+            if cached.fingerprint == fingerprint && !system_book_is_stale(cached.loaded_at, now) {
+                return cached.contacts.clone();
+            }
+        }
+    }
+
+    let mut contacts = system_contacts();
+    for file in &files {
+        contacts.extend(read_source(file));
+    }
+    let contacts = dedupe(contacts);
+    if let Ok(mut guard) = cache.lock() {
+        *guard = Some(Cache {
+            fingerprint,
+            loaded_at: now,
+            contacts: contacts.clone(),
+        });
+    }
+    contacts
+}
+
+/// Whether `target` is an allowlisted contact action; `vcard:` targets must name a file
+/// contacts were read from.
+pub fn is_allowed_action(target: &str) -> bool {
+    actions::is_allowed_target(target, |path| {
+        all()
+            .iter()
+            .any(|contact| contact.source.as_deref() == Some(path))
+    })
+}
+
+/// `.vcf`/`.vcard` files and Evolution databases under each source, in a stable order.
+fn source_files(sources: &[String]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for source in sources {
+        let root = Path::new(source);
+        if root.is_file() {
+            files.push(root.to_path_buf());
+            continue;
+        }
+        let mut found: Vec<PathBuf> = WalkDir::new(root)
+            .max_depth(MAX_SOURCE_DEPTH)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.file_type().is_file() && is_contact_file(entry.path()))
+            .map(|entry| entry.into_path())
+            .collect();
+        found.sort();
+        files.extend(found);
+    }
+    files
+}
+
+fn is_contact_file(path: &Path) -> bool {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    matches!(extension.as_str(), "vcf" | "vcard")
+        || path.file_name().and_then(|name| name.to_str()) == Some(EVOLUTION_DB)
+}
+
+fn fingerprint(sources: &[String], files: &[PathBuf]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    sources.hash(&mut hasher);
+    for file in files {
+        file.hash(&mut hasher);
+        if let Ok(metadata) = std::fs::metadata(file) {
+            metadata.len().hash(&mut hasher);
+            metadata.modified().ok().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+fn read_source(path: &Path) -> Vec<ContactEntry> {
+    let source = path.to_string_lossy();
+    if path.file_name().and_then(|name| name.to_str()) == Some(EVOLUTION_DB) {
+        return read_evolution_book(path)
+            .map(|cards| vcard::parse(&cards, Some(&source)))
+            .unwrap_or_default();
+    }
+    match std::fs::read(path) {
+        Ok(bytes) => vcard::parse(&String::from_utf8_lossy(&bytes), Some(&source)),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn read_evolution_book(path: &Path) -> Result<String, String> {
+    let db =
+        rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| e.to_string())?;
+    let mut statement = db
+        .prepare("SELECT vcard FROM folder_id")
+        .map_err(|e| e.to_string())?;
+    let cards = statement
+        .query_map([], |row| row.get::<_, Option<String>>(0))
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .flatten()
+        .collect::<Vec<_>>();
+    Ok(cards.join("\n"))
+}
+
+/// The same person often appears in several sources (a synced collection and an old
+/// export); the first copy wins, matched by UID or else by name and email.
+fn dedupe(contacts: Vec<ContactEntry>) -> Vec<ContactEntry> {
+    let mut seen = HashSet::new();
+    contacts
+        .into_iter()
+        .filter(|contact| {
+            let key = match &contact.uid {
+                Some(uid) => format!("uid:{}", uid),
+                None => format!(
+                    "{}\t{}",
+                    contact.name.to_lowercase(),
+                    contact.email.as_deref().unwrap_or_default().to_lowercase()
+                ),
+            };
+            seen.insert(key)
+        })
+        .collect()
+}
+
+/// Contacts.app changes are not visible in any file we watch, so its copy expires.
+#[cfg(target_os = "macos")]
+fn system_book_is_stale(loaded_at: u64, now: u64) -> bool {
+    const SYSTEM_BOOK_TTL_SECS: u64 = 5 * 60;
+    now.saturating_sub(loaded_at) >= SYSTEM_BOOK_TTL_SECS
+}
+
+#[cfg(not(target_os = "macos"))]
+fn system_book_is_stale(_loaded_at: u64, _now: u64) -> bool {
+    false
+}
+
+/// Reads the whole address book as vCards in one Apple Event instead of walking people
+/// one property at a time, which is what made large books slow enough to need a cap.
+#[cfg(target_os = "macos")]
+fn system_contacts() -> Vec<ContactEntry> {
+    let script = r#"tell application "Contacts"
+set cards to vcard of every person
+end tell
+set AppleScript's text item delimiters to linefeed
+return cards as text"#;
+
+    let Ok(mut child) = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+    else {
+        return Vec::new();
+    };
+    // NOTE: stdout is drained on a thread so a large address book cannot fill the pipe
+    // and stall osascript until the timeout.
+    let mut stdout = child.stdout.take();
+    let reader = std::thread::spawn(move || {
+        let mut raw = Vec::new();
+        if let Some(stdout) = stdout.as_mut() {
+            let _ = std::io::Read::read_to_end(stdout, &mut raw);
+        }
+        raw
+    });
+    let timeout = std::time::Duration::from_secs(20);
+    let start = std::time::Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                if !status.success() {
+                    return Vec::new();
+                }
+                break;
+            }
+            Ok(None) => {
+                if start.elapsed() > timeout {
+                    let _ = child.kill();
+                    return Vec::new();
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            Err(_) => return Vec::new(),
+        }
+    }
+    let raw = reader.join().unwrap_or_default();
+    vcard::parse(&String::from_utf8_lossy(&raw), None)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn system_contacts() -> Vec<ContactEntry> {
+    Vec::new()
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_vcf_directories_and_evolution_books() {
+        let root = std::env::temp_dir().join(format!("genie-contacts-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let collection = root.join("vdirsyncer/default");
+        std::fs::create_dir_all(&collection).unwrap();
+        std::fs::write(
+            collection.join("a.vcf"),
+            "BEGIN:VCARD\nVERSION:3.0\nUID:1\nFN:Ada Lovelace\nEMAIL:ada@example.com\nEND:VCARD\n",
+        )
+        .unwrap();
+        std::fs::write(
+            collection.join("notes.txt"),
+            "BEGIN:VCARD\nFN:Ignored\nEND:VCARD\n",
+        )
+        .unwrap();
+        let export = root.join("export.vcf");
+        std::fs::write(
+            &export,
+            "BEGIN:VCARD\nVERSION:3.0\nUID:1\nFN:Ada L.\nEND:VCARD\n\
+BEGIN:VCARD\nVERSION:3.0\nFN:Grace Hopper\nEND:VCARD\n",
+        )
+        .unwrap();
+        let book = root.join("evolution/system");
+        std::fs::create_dir_all(&book).unwrap();
+        let db = rusqlite::Connection::open(book.join(EVOLUTION_DB)).unwrap();
+        db.execute_batch(
+            "CREATE TABLE folder_id (uid TEXT PRIMARY KEY, vcard TEXT);
+             INSERT INTO folder_id VALUES
+               ('pas-id-1', 'BEGIN:VCARD\nVERSION:3.0\nUID:pas-id-1\nFN:Alan Turing\nEND:VCARD');",
+        )
+        .unwrap();
+        drop(db);
+
+        let sources = vec![
+            root.join("vdirsyncer").to_string_lossy().to_string(),
+            export.to_string_lossy().to_string(),
+            root.join("evolution").to_string_lossy().to_string(),
+            root.join("missing").to_string_lossy().to_string(),
+        ];
+        let files = source_files(&sources);
+        assert_eq!(
+            files,
+            [
+                collection.join("a.vcf"),
+                export.clone(),
+                book.join(EVOLUTION_DB)
+            ]
+        );
+        let contacts = dedupe(files.iter().flat_map(|file| read_source(file)).collect());
+        let names: Vec<_> = contacts.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Ada Lovelace", "Grace Hopper", "Alan Turing"]);
+        assert_eq!(
+            contacts[0].source.as_deref(),
+            Some(collection.join("a.vcf").to_string_lossy().as_ref())
+        );
+
+        let before = fingerprint(&sources, &files);
+        std::fs::write(&export, "BEGIN:VCARD\nFN:Grace Hopper\nEND:VCARD\n").unwrap();
+        assert_ne!(before, fingerprint(&sources, &files));
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,320 @@
+use super::{ContactEntry, ContactField};
+
+/// Parses every `BEGIN:VCARD … END:VCARD` block in `text` (vCard 3.0 and 4.0; 2.1 cards
+/// parse too as long as they are not quoted-printable). Cards without any name are dropped.
+pub fn parse(text: &str, source: Option<&str>) -> Vec<ContactEntry> {
+    let mut contacts = Vec::new();
+    let mut card: Option<Card> = None;
+    for line in unfold(text) {
+        let Some(property) = Property::parse(&line) else {
+            continue;
+        };
+        match property.name.as_str() {
+            "BEGIN" if property.value.eq_ignore_ascii_case("VCARD") => card = Some(Card::default()),
+            "END" if property.value.eq_ignore_ascii_case("VCARD") => {
+                if let Some(entry) = card.take().and_then(|card| card.finish(source)) {
+                    contacts.push(entry);
+                }
+            }
+            _ => {
+                if let Some(card) = card.as_mut() {
+                    card.add(property);
+                }
+            }
+        }
+    }
+    contacts
+}
+
+/// Joins folded lines: a line break followed by a space or tab continues the previous line.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+struct Property {
+    /// Upper-cased property name without its group prefix (`item1.EMAIL` → `EMAIL`).
+    name: String,
+    /// Lower-cased `TYPE` values, including bare vCard 2.1 parameters such as `WORK`.
+    types: Vec<String>,
+    preferred: bool,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        let colon = value_separator(line)?;
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+        let mut parts = head.split(';');
+        let name = parts.next()?;
+        let name = name
+            .rsplit('.')
+            .next()
+            .unwrap_or(name)
+            .trim()
+            .to_uppercase();
+        if name.is_empty() {
+            return None;
+        }
+        let mut types = Vec::new();
+        let mut preferred = false;
+        for param in parts {
+            let (key, values) = match param.split_once('=') {
+                Some((key, values)) => (key.trim().to_uppercase(), values),
+                None => ("TYPE".to_string(), param),
+            };
+            match key.as_str() {
+                "TYPE" => {
+                    for value in values.trim_matches('"').split(',') {
+                        let value = value.trim().to_lowercase();
+                        if value == "pref" {
+                            preferred = true;
+                        } else if !value.is_empty() {
+                            types.push(value);
+                        }
+                    }
+                }
+                "PREF" => preferred = true,
+                _ => {}
+            }
+        }
+        Some(Self {
+            name,
+            types,
+            preferred,
+            value: value.to_string(),
+        })
+    }
+
+    /// The first type that tells two values apart, e.g. `work` rather than `internet`.
+    fn label(&self) -> Option<String> {
+        self.types
+            .iter()
+            .find(|kind| !matches!(kind.as_str(), "internet" | "voice" | "x400"))
+            .cloned()
+    }
+}
+
+/// The first `:` outside a quoted parameter value.
+fn value_separator(line: &str) -> Option<usize> {
+    let mut quoted = false;
+    for (index, ch) in line.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            ':' if !quoted => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits a structured value on unescaped `separator` and unescapes each component.
+fn components(value: &str, separator: char) -> Vec<String> {
+    split_unescaped(value, Some(separator))
+}
+
+fn text(value: &str) -> String {
+    split_unescaped(value, None).concat()
+}
+
+fn split_unescaped(value: &str, separator: Option<char>) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => parts.last_mut().unwrap().push('\n'),
+                Some(escaped) => parts.last_mut().unwrap().push(escaped),
+                None => {}
+            },
+            ch if Some(ch) == separator => parts.push(String::new()),
+            ch => parts.last_mut().unwrap().push(ch),
+        }
+    }
+    parts
+        .into_iter()
+        .map(|part| part.trim().to_string())
+        .collect()
+}
+
+#[derive(Default)]
+struct Card {
+    formatted_name: Option<String>,
+    structured_name: Option<String>,
+    emails: Vec<(bool, ContactField)>,
+    phones: Vec<(bool, ContactField)>,
+    organization: Option<String>,
+    nicknames: Vec<String>,
+    uid: Option<String>,
+}
+
+impl Card {
+    fn add(&mut self, property: Property) {
+        match property.name.as_str() {
+            "FN" => self.formatted_name = Some(text(&property.value)),
+            "N" => {
+                // family;given;additional;prefix;suffix → "prefix given additional family suffix"
+                let parts = components(&property.value, ';');
+                let order = [3, 1, 2, 0, 4];
+                let name = order
+                    .iter()
+                    .filter_map(|index| parts.get(*index))
+                    .filter(|part| !part.is_empty())
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                self.structured_name = Some(name);
+            }
+            "EMAIL" => {
+                let value = text(&property.value);
+                let value = value.strip_prefix("mailto:").unwrap_or(&value).to_string();
+                push_field(&mut self.emails, &property, value);
+            }
+            "TEL" => {
+                let value = text(&property.value);
+                let value = value.strip_prefix("tel:").unwrap_or(&value).to_string();
+                push_field(&mut self.phones, &property, value);
+            }
+            "ORG" => {
+                let units: Vec<String> = components(&property.value, ';')
+                    .into_iter()
+                    .filter(|unit| !unit.is_empty())
+                    .collect();
+                if !units.is_empty() {
+                    self.organization = Some(units.join(", "));
+                }
+            }
+            "NICKNAME" => self.nicknames.extend(
+                components(&property.value, ',')
+                    .into_iter()
+                    .filter(|nickname| !nickname.is_empty()),
+            ),
+            "UID" => self.uid = Some(text(&property.value)).filter(|uid| !uid.is_empty()),
+            // Contacts.app exports its record id here instead of a UID.
+            "X-ABUID" if self.uid.is_none() => {
+                self.uid = Some(text(&property.value)).filter(|uid| !uid.is_empty())
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(self, source: Option<&str>) -> Option<ContactEntry> {
+        let name = [
+            self.formatted_name,
+            self.structured_name,
+            self.organization.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|name| !name.trim().is_empty())?;
+        let emails = preferred_first(self.emails);
+        let phones = preferred_first(self.phones);
+        Some(ContactEntry {
+            name,
+            email: emails.first().map(|field| field.value.clone()),
+            phone: phones.first().map(|field| field.value.clone()),
+            emails,
+            phones,
+            organization: self.organization,
+            nicknames: self.nicknames,
+            uid: self.uid,
+            source: source.map(ToString::to_string),
+        })
+    }
+}
+
+fn push_field(fields: &mut Vec<(bool, ContactField)>, property: &Property, value: String) {
+    if value.is_empty() || fields.iter().any(|(_, field)| field.value == value) {
+        return;
+    }
+    fields.push((
+        property.preferred,
+        ContactField {
+            value,
+            label: property.label(),
+        },
+    ));
+}
+
+fn preferred_first(mut fields: Vec<(bool, ContactField)>) -> Vec<ContactField> {
+    // Stable sort keeps the card's own order among equally preferred values.
+    fields.sort_by_key(|(preferred, _)| !preferred);
+    fields.into_iter().map(|(_, field)| field).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(value: &str, label: Option<&str>) -> ContactField {
+        ContactField {
+            value: value.into(),
+            label: label.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn parses_vcard_3_with_folding_and_preferences() {
+        let text = "BEGIN:VCARD\r\n\
+VERSION:3.0\r\n\
+FN:Ada Lovelace\r\n\
+N:Lovelace;Ada;;Lady;\r\n\
+NICKNAME:Ada,The Enchantress\r\n\
+ORG:Analytical Engines\\, Ltd;Research\r\n\
+EMAIL;TYPE=INTERNET,HOME:ada@home.example\r\n\
+item1.EMAIL;TYPE=INTERNET;TYPE=WORK;TYPE=pref:ada@engines.exa\r\n mple\r\n\
+TEL;TYPE=CELL:+44 20 7946 0000\r\n\
+TEL;TYPE=CELL:+44 20 7946 0000\r\n\
+NOTE:Line one\\nline two\r\n\
+PHOTO;ENCODING=b;TYPE=JPEG:AAAA\r\n\
+UID:ada-1\r\n\
+END:VCARD\r\n";
+        let contacts = parse(text, Some("/cards/ada.vcf"));
+        assert_eq!(
+            contacts,
+            [ContactEntry {
+                name: "Ada Lovelace".into(),
+                email: Some("ada@engines.example".into()),
+                phone: Some("+44 20 7946 0000".into()),
+                emails: vec![
+                    field("ada@engines.example", Some("work")),
+                    field("ada@home.example", Some("home")),
+                ],
+                phones: vec![field("+44 20 7946 0000", Some("cell"))],
+                organization: Some("Analytical Engines, Ltd, Research".into()),
+                nicknames: vec!["Ada".into(), "The Enchantress".into()],
+                uid: Some("ada-1".into()),
+                source: Some("/cards/ada.vcf".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_vcard_4_uris_and_name_fallbacks() {
+        let text = "BEGIN:VCARD\nVERSION:4.0\nN:Hopper;Grace;Brewster;Rear Admiral;\n\
+TEL;VALUE=uri;TYPE=\"voice,work\":tel:+1-555-0100\n\
+TEL;VALUE=uri;PREF=1;TYPE=home:tel:+1-555-0199\n\
+END:VCARD\n\
+BEGIN:VCARD\nVERSION:4.0\nORG:Acme\nEMAIL:sales@acme.example\nEND:VCARD\n\
+BEGIN:VCARD\nVERSION:4.0\nEMAIL:nobody@example.com\nEND:VCARD\n";
+        let contacts = parse(text, None);
+        assert_eq!(contacts.len(), 2);
+        assert_eq!(contacts[0].name, "Rear Admiral Grace Brewster Hopper");
+        assert_eq!(
+            contacts[0].phones,
+            [
+                field("+1-555-0199", Some("home")),
+                field("+1-555-0100", Some("work")),
+            ]
+        );
+        assert_eq!(contacts[1].name, "Acme");
+        assert_eq!(contacts[1].email.as_deref(), Some("sales@acme.example"));
+        assert_eq!(contacts[1].source, None);
+    }
+}
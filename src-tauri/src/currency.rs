@@ -0,0 +1,184 @@
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const RATES_FILE: &str = "currency-rates.json";
+/// Minimum gap between refresh attempts, so an unreachable source is not retried on every keystroke.
+const RETRY_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+const FETCH_TIMEOUT_SECS: &str = "10";
+
+/// Exchange rates relative to `base`, persisted so conversions keep working offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatesTable {
+    pub base: String,
+    /// Unix seconds when the rates were loaded from the source.
+    pub fetched_at: u64,
+    pub rates: HashMap<String, f64>,
+}
+
+impl RatesTable {
+    /// Units of `to` per one unit of `from`; codes are case-insensitive.
+    pub fn rate(&self, from: &str, to: &str) -> Option<f64> {
+        let from = self.units_per_base(from)?;
+        let to = self.units_per_base(to)?;
+        Some(to / from)
+    }
+
+    pub fn has_currency(&self, code: &str) -> bool {
+        self.units_per_base(code).is_some()
+    }
+
+    fn units_per_base(&self, code: &str) -> Option<f64> {
+        let code = code.to_ascii_uppercase();
+        if code == self.base {
+            return Some(1.0);
+        }
+        self.rates
+            .get(&code)
+            .copied()
+            .filter(|rate| rate.is_finite() && *rate > 0.0)
+    }
+}
+
+static RATES: OnceLock<Mutex<Option<RatesTable>>> = OnceLock::new();
+static LAST_ATTEMPT_AT_SECS: AtomicU64 = AtomicU64::new(0);
+
+/// Loads the persisted rates. Nothing is fetched until a conversion asks for rates.
+pub fn init() {
+    let cached = std::fs::read_to_string(rates_path())
+        .ok()
+        .and_then(|json| serde_json::from_str::<RatesTable>(&json).ok());
+    let _ = RATES.set(Mutex::new(cached));
+}
+
+/// The latest known rates, possibly stale; `None` until a source has been read once.
+/// Stale rates are refreshed in the background.
+pub fn rates() -> Option<RatesTable> {
+    refresh_if_stale();
+    RATES.get()?.lock().ok()?.clone()
+}
+
+fn refresh_if_stale() {
+    let now = now_secs();
+    let max_age = settings::get().currency.refresh_hours.max(1) * 3600;
+    let fetched_at = RATES
+        .get()
+        .and_then(|rates| rates.lock().ok()?.as_ref().map(|table| table.fetched_at))
+        .unwrap_or(0);
+    if now.saturating_sub(fetched_at) < max_age {
+        return;
+    }
+    let last_attempt = LAST_ATTEMPT_AT_SECS.load(Ordering::SeqCst);
+    if now.saturating_sub(last_attempt) < RETRY_COOLDOWN.as_secs() {
+        return;
+    }
+    if LAST_ATTEMPT_AT_SECS
+        .compare_exchange(last_attempt, now, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return;
+    }
+    std::thread::spawn(|| {
+        let _ = refresh();
+    });
+}
+
+/// Reads the configured source and replaces the in-memory and persisted rates.
+pub fn refresh() -> Result<RatesTable, String> {
+    let source = settings::get().currency.rates_source;
+    let json = read_source(&source)?;
+    let table = parse_rates(&json, now_secs())?;
+    let serialized = serde_json::to_string_pretty(&table).map_err(|e| e.to_string())?;
+    settings::write_atomic(&rates_path(), serialized.as_bytes())?;
+    if let Some(rates) = RATES.get() {
+        if let Ok(mut guard) = rates.lock() {
+            *guard = Some(table.clone());
+        }
+    }
+    Ok(table)
+}
+
+/// Sources are either an `http(s)://` URL, fetched with `curl`, or a local JSON file
+/// (plain path or `file://`) for machines without network access.
+fn read_source(source: &str) -> Result<String, String> {
+    let source = source.trim();
+    if source.starts_with("https://") || source.starts_with("http://") {
+        let output = std::process::Command::new("curl")
+            .args(["-fsSL", "--max-time", FETCH_TIMEOUT_SECS, source])
+            .output()
+            .map_err(|e| format!("Failed to run curl: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("Failed to fetch currency rates from {}", source));
+        }
+        return String::from_utf8(output.stdout).map_err(|e| e.to_string());
+    }
+    let path = source.strip_prefix("file://").unwrap_or(source);
+    std::fs::read_to_string(Path::new(path))
+        .map_err(|e| format!("Failed to read currency rates from {}: {}", path, e))
+}
+
+/// Accepts the common `{ "base": "USD", "rates": { "EUR": 0.92, … } }` shape
+/// (`base_code` is accepted as an alias for `base`).
+fn parse_rates(json: &str, fetched_at: u64) -> Result<RatesTable, String> {
+    #[derive(Deserialize)]
+    struct RatesResponse {
+        #[serde(alias = "base_code")]
+        base: String,
+        rates: HashMap<String, f64>,
+    }
+
+    let response: RatesResponse =
+        serde_json::from_str(json).map_err(|e| format!("Invalid currency rates: {}", e))?;
+    if response.rates.is_empty() {
+        return Err("Currency rates source returned no rates".into());
+    }
+    Ok(RatesTable {
+        base: response.base.to_ascii_uppercase(),
+        fetched_at,
+        rates: response
+            .rates
+            .into_iter()
+            .map(|(code, rate)| (code.to_ascii_uppercase(), rate))
+            .collect(),
+    })
+}
+
+fn rates_path() -> PathBuf {
+    let dir = dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("spotlight-genie");
+    let _ = std::fs::create_dir_all(&dir);
+    dir.join(RATES_FILE)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_common_rate_payloads() {
+        let table = parse_rates(
+            r#"{"base_code":"usd","time_last_update_unix":1,"rates":{"eur":0.5,"JPY":150}}"#,
+            42,
+        )
+        .unwrap();
+        assert_eq!(table.base, "USD");
+        assert_eq!(table.fetched_at, 42);
+        assert_eq!(table.rate("usd", "EUR"), Some(0.5));
+        assert_eq!(table.rate("EUR", "JPY"), Some(300.0));
+        assert_eq!(table.rate("EUR", "XYZ"), None);
+        assert!(parse_rates(r#"{"base":"USD","rates":{}}"#, 0).is_err());
+        assert!(parse_rates("<html>", 0).is_err());
+    }
+}
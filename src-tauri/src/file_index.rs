@@ -74,7 +74,7 @@ pub fn search(
             score,
         });
     }
-    matches.sort_by(|a, b| b.score.cmp(&a.score));
+    matches.sort_by_key(|m| std::cmp::Reverse(m.score));
     matches.truncate(MAX_RESULTS);
     matches
 }
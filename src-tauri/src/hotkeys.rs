@@ -0,0 +1,537 @@
+//! Global shortcuts from settings: the main `hotkey` toggles the launcher and each entry
+//! in `hotkeys` runs its own action.
+
+use crate::commands;
+use crate::settings::{self, AppSettings, HotkeyAction, HotkeyBinding};
+use crate::system_commands::{self, RunOutcome};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::Ordering;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+/// The registered bindings, keyed by `Shortcut::id`.
+static REGISTERED: OnceLock<Mutex<HashMap<u32, HotkeyBinding>>> = OnceLock::new();
+/// Shortcuts from settings that `register_all` could not register, keyed by
+/// `Shortcut::id`, with the reason. Saves that keep them leave them alone instead of
+/// failing on them again while another app holds them; setting one again retries it.
+static UNAVAILABLE: OnceLock<Mutex<HashMap<u32, String>>> = OnceLock::new();
+
+/// Why a shortcut from settings cannot be used.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictReason {
+    Invalid,
+    /// Bound twice in the same settings.
+    Duplicate,
+    /// Refused by the OS, usually because another app owns it.
+    Unavailable,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HotkeyConflict {
+    pub shortcut: String,
+    pub reason: ConflictReason,
+    pub message: String,
+}
+
+/// The main hotkey first, then the extra bindings in the order they were configured.
+pub fn bindings(settings: &AppSettings) -> Vec<HotkeyBinding> {
+    let mut bindings = vec![HotkeyBinding {
+        shortcut: settings.hotkey.clone(),
+        action: HotkeyAction::OpenLauncher,
+    }];
+    bindings.extend(
+        settings
+            .hotkeys
+            .iter()
+            .filter(|binding| !binding.shortcut.trim().is_empty())
+            .cloned(),
+    );
+    bindings
+}
+
+pub fn parse(shortcut: &str) -> Result<Shortcut, String> {
+    shortcut
+        .parse()
+        .map_err(|_| format!("Invalid shortcut format: {}", shortcut))
+}
+
+/// Registers every binding from settings that is not registered yet. A binding that
+/// fails does not stop the rest; the failures are returned.
+pub fn register_all<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
+    let mut errors = Vec::new();
+    for binding in bindings(&settings::get()) {
+        let shortcut = match parse(&binding.shortcut) {
+            Ok(shortcut) => shortcut,
+            Err(error) => {
+                errors.push(error);
+                continue;
+            }
+        };
+        if app.global_shortcut().is_registered(shortcut) {
+            continue;
+        }
+        match app.global_shortcut().register(shortcut) {
+            Ok(()) => {
+                if let Ok(mut unavailable) = unavailable().lock() {
+                    unavailable.remove(&shortcut.id());
+                }
+                if let Ok(mut registered) = registry().lock() {
+                    registered.insert(shortcut.id(), binding);
+                }
+            }
+            Err(e) => {
+                let error = format!("Failed to register {}: {}", binding.shortcut, e);
+                if let Ok(mut unavailable) = unavailable().lock() {
+                    unavailable.insert(shortcut.id(), error.clone());
+                }
+                errors.push(error);
+            }
+        }
+    }
+    errors
+}
+
+pub fn unregister_all<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    if let Ok(mut registered) = registry().lock() {
+        registered.clear();
+    }
+    if let Ok(mut unavailable) = unavailable().lock() {
+        unavailable.clear();
+    }
+    app.global_shortcut()
+        .unregister_all()
+        .map_err(|e| format!("Failed to unregister: {}", e))
+}
+
+/// Why the shortcuts in the current settings are not working, for the settings panel.
+pub fn errors() -> Vec<String> {
+    let unavailable = unavailable()
+        .lock()
+        .map(|unavailable| unavailable.clone())
+        .unwrap_or_default();
+    bindings(&settings::get())
+        .iter()
+        .filter_map(|binding| match parse(&binding.shortcut) {
+            Ok(shortcut) => unavailable.get(&shortcut.id()).cloned(),
+            Err(error) => Some(error),
+        })
+        .collect()
+}
+
+/// What `apply` replaced, so a save that fails afterwards can put it back.
+pub struct Applied {
+    unavailable: HashMap<u32, String>,
+}
+
+/// Switches the registered shortcuts to the bindings of `settings`. Only shortcuts that
+/// changed are touched, and ones `register_all` could not register are not retried
+/// while they stay in the settings; if any new one cannot be registered, every change
+/// is undone so the previous shortcuts keep working.
+pub fn apply<R: Runtime>(
+    app: &AppHandle<R>,
+    settings: &AppSettings,
+) -> Result<Applied, Vec<HotkeyConflict>> {
+    let current = registry()
+        .lock()
+        .map(|registered| registered.clone())
+        .unwrap_or_default();
+    let previous_unavailable = unavailable()
+        .lock()
+        .map(|unavailable| unavailable.clone())
+        .unwrap_or_default();
+    let unavailable_ids = previous_unavailable.keys().copied().collect();
+    let plan = plan(&current, &unavailable_ids, &bindings(settings))?;
+    let shortcuts = app.global_shortcut();
+    swap(
+        &plan,
+        |shortcut| shortcuts.register(shortcut).map_err(|e| e.to_string()),
+        |shortcut| {
+            let _ = shortcuts.unregister(shortcut);
+        },
+    )?;
+    if let Ok(mut registered) = registry().lock() {
+        *registered = plan.bindings;
+    }
+    if let Ok(mut unavailable) = unavailable().lock() {
+        unavailable.retain(|id, _| plan.unavailable.contains(id));
+    }
+    Ok(Applied {
+        unavailable: previous_unavailable,
+    })
+}
+
+/// Goes back to `previous` after `apply` switched away from it.
+pub fn revert<R: Runtime>(app: &AppHandle<R>, previous: &AppSettings, applied: Applied) {
+    if let Ok(mut unavailable) = unavailable().lock() {
+        *unavailable = applied.unavailable;
+    }
+    let _ = apply(app, previous);
+}
+
+#[derive(Debug)]
+struct Plan {
+    add: Vec<(Shortcut, String)>,
+    remove: Vec<Shortcut>,
+    /// The bindings that will be registered once the plan is carried out.
+    bindings: HashMap<u32, HotkeyBinding>,
+    /// Kept bindings that could not be registered; they stay inactive.
+    unavailable: HashSet<u32>,
+}
+
+/// Validates the new bindings and works out which shortcuts to add and remove.
+fn plan(
+    current: &HashMap<u32, HotkeyBinding>,
+    unavailable: &HashSet<u32>,
+    bindings: &[HotkeyBinding],
+) -> Result<Plan, Vec<HotkeyConflict>> {
+    let mut conflicts = Vec::new();
+    let mut seen = HashSet::new();
+    let mut next = HashMap::new();
+    let mut still_unavailable = HashSet::new();
+    let mut add = Vec::new();
+    for binding in bindings {
+        let shortcut = match parse(&binding.shortcut) {
+            Ok(shortcut) => shortcut,
+            Err(message) => {
+                conflicts.push(HotkeyConflict {
+                    shortcut: binding.shortcut.clone(),
+                    reason: ConflictReason::Invalid,
+                    message,
+                });
+                continue;
+            }
+        };
+        if !seen.insert(shortcut.id()) {
+            conflicts.push(HotkeyConflict {
+                shortcut: binding.shortcut.clone(),
+                reason: ConflictReason::Duplicate,
+                message: format!("{} is bound more than once", binding.shortcut),
+            });
+            continue;
+        }
+        if unavailable.contains(&shortcut.id()) {
+            still_unavailable.insert(shortcut.id());
+            continue;
+        }
+        next.insert(shortcut.id(), binding.clone());
+        if !current.contains_key(&shortcut.id()) {
+            add.push((shortcut, binding.shortcut.clone()));
+        }
+    }
+    if !conflicts.is_empty() {
+        return Err(conflicts);
+    }
+    let remove = current
+        .iter()
+        .filter(|(id, _)| !next.contains_key(id))
+        .filter_map(|(_, binding)| parse(&binding.shortcut).ok())
+        .collect();
+    Ok(Plan {
+        add,
+        remove,
+        bindings: next,
+        unavailable: still_unavailable,
+    })
+}
+
+/// Removes first so two bindings can trade shortcuts, then adds; on failure the added
+/// shortcuts are released and the removed ones restored.
+fn swap(
+    plan: &Plan,
+    mut register: impl FnMut(Shortcut) -> Result<(), String>,
+    mut unregister: impl FnMut(Shortcut),
+) -> Result<(), Vec<HotkeyConflict>> {
+    for shortcut in &plan.remove {
+        unregister(*shortcut);
+    }
+    let mut added = Vec::new();
+    let mut conflicts = Vec::new();
+    for (shortcut, text) in &plan.add {
+        match register(*shortcut) {
+            Ok(()) => added.push(*shortcut),
+            Err(e) => conflicts.push(HotkeyConflict {
+                shortcut: text.clone(),
+                reason: ConflictReason::Unavailable,
+                message: format!("{} could not be registered: {}", text, e),
+            }),
+        }
+    }
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    for shortcut in added {
+        unregister(shortcut);
+    }
+    for shortcut in &plan.remove {
+        let _ = register(*shortcut);
+    }
+    Err(conflicts)
+}
+
+fn registry() -> &'static Mutex<HashMap<u32, HotkeyBinding>> {
+    REGISTERED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn unavailable() -> &'static Mutex<HashMap<u32, String>> {
+    UNAVAILABLE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Runs the action bound to a pressed shortcut.
+pub fn handle<R: Runtime>(app: &AppHandle<R>, shortcut: &Shortcut) {
+    let action = registry()
+        .lock()
+        .ok()
+        .and_then(|registered| {
+            registered
+                .get(&shortcut.id())
+                .map(|binding| binding.action.clone())
+        })
+        .unwrap_or(HotkeyAction::OpenLauncher);
+    match action {
+        HotkeyAction::OpenLauncher => toggle_launcher(app),
+        HotkeyAction::OpenWithQuery { query } => open_with_query(app, &query),
+        HotkeyAction::RunCommand { command } => {
+            let app = app.clone();
+            std::thread::spawn(move || run_command(&app, &command));
+        }
+        HotkeyAction::LaunchApp { path } => {
+            let _ = commands::launch_item(path, "APP".into());
+        }
+    }
+}
+
+/// Commands that need confirmation or the UI open the launcher on the command instead.
+fn run_command<R: Runtime>(app: &AppHandle<R>, id: &str) {
+    if let Ok(RunOutcome::Done) = system_commands::run(id, false) {
+        return;
+    }
+    if let Some(command) = system_commands::registry()
+        .into_iter()
+        .find(|command| command.id == id)
+    {
+        open_with_query(app, &command.title);
+    }
+}
+
+fn toggle_launcher<R: Runtime>(app: &AppHandle<R>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let visible = window.is_visible().unwrap_or(false);
+    let focused = window.is_focused().unwrap_or(false);
+    if visible && focused {
+        let _ = window.hide();
+    } else {
+        show_launcher(app);
+    }
+}
+
+fn open_with_query<R: Runtime>(app: &AppHandle<R>, query: &str) {
+    if let Some(window) = show_launcher(app) {
+        let _ = window.emit("genie:prefill", query);
+    }
+}
+
+fn show_launcher<R: Runtime>(app: &AppHandle<R>) -> Option<tauri::WebviewWindow<R>> {
+    let window = app.get_webview_window("main")?;
+    #[cfg(target_os = "macos")]
+    {
+        let _ = window.set_visible_on_all_workspaces(true);
+        let script = r#"tell application "System Events" to set frontmost of process "Spotlight Genie" to true"#;
+        let _ = std::process::Command::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .spawn();
+        let _ = app.show();
+        let _ = app.set_dock_visibility(false);
+    }
+    crate::show_window(&window);
+    crate::window_switcher::refresh();
+    let _ = window.emit("genie:focus", ());
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    commands::LAST_SHOW_TIME.store(now, Ordering::SeqCst);
+    Some(window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn main_hotkey_comes_first_and_blank_bindings_are_skipped() {
+        let settings = AppSettings {
+            hotkeys: vec![
+                HotkeyBinding {
+                    shortcut: "Alt+Shift+V".into(),
+                    action: HotkeyAction::OpenWithQuery {
+                        query: "cb ".into(),
+                    },
+                },
+                HotkeyBinding {
+                    shortcut: " ".into(),
+                    action: HotkeyAction::OpenLauncher,
+                },
+            ],
+            ..AppSettings::default()
+        };
+        let bindings = bindings(&settings);
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings[0].shortcut, "CommandOrControl+Space");
+        assert_eq!(bindings[0].action, HotkeyAction::OpenLauncher);
+        assert_eq!(bindings[1].shortcut, "Alt+Shift+V");
+        assert!(parse(&bindings[1].shortcut).is_ok());
+        assert!(parse("Shift+Nope").is_err());
+    }
+
+    #[test]
+    fn actions_are_tagged_in_settings_json() {
+        let json = r#"[
+            {"shortcut": "Alt+C", "action": {"type": "run_command", "command": "lock"}},
+            {"shortcut": "Alt+T", "action": {"type": "launch_app", "path": "/usr/bin/kitty"}},
+            {"shortcut": "Alt+Space", "action": {"type": "open_launcher"}}
+        ]"#;
+        let parsed: Vec<HotkeyBinding> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            parsed[0].action,
+            HotkeyAction::RunCommand {
+                command: "lock".into()
+            }
+        );
+        assert_eq!(
+            parsed[1].action,
+            HotkeyAction::LaunchApp {
+                path: "/usr/bin/kitty".into()
+            }
+        );
+        assert_eq!(parsed[2].action, HotkeyAction::OpenLauncher);
+    }
+
+    fn binding(shortcut: &str) -> HotkeyBinding {
+        HotkeyBinding {
+            shortcut: shortcut.into(),
+            action: HotkeyAction::OpenLauncher,
+        }
+    }
+
+    fn registered(bindings: &[HotkeyBinding]) -> HashMap<u32, HotkeyBinding> {
+        bindings
+            .iter()
+            .map(|binding| (parse(&binding.shortcut).unwrap().id(), binding.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn plans_only_the_changed_shortcuts() {
+        let current = registered(&[binding("Alt+Space"), binding("Alt+V")]);
+        let plan = plan(
+            &current,
+            &HashSet::new(),
+            &[binding("Alt+Space"), binding("Alt+C")],
+        )
+        .unwrap();
+        assert_eq!(plan.add.len(), 1);
+        assert_eq!(plan.add[0].1, "Alt+C");
+        assert_eq!(plan.remove, [parse("Alt+V").unwrap()]);
+        assert_eq!(plan.bindings.len(), 2);
+
+        let conflicts = plan_conflicts(
+            &current,
+            &[
+                binding("Alt+Space"),
+                binding("alt+space"),
+                binding("Hyper+Nope"),
+            ],
+        );
+        assert_eq!(
+            conflicts,
+            [
+                ("alt+space".to_string(), ConflictReason::Duplicate),
+                ("Hyper+Nope".to_string(), ConflictReason::Invalid),
+            ]
+        );
+    }
+
+    fn plan_conflicts(
+        current: &HashMap<u32, HotkeyBinding>,
+        bindings: &[HotkeyBinding],
+    ) -> Vec<(String, ConflictReason)> {
+        plan(current, &HashSet::new(), bindings)
+            .unwrap_err()
+            .into_iter()
+            .map(|conflict| (conflict.shortcut, conflict.reason))
+            .collect()
+    }
+
+    #[test]
+    fn failed_registration_restores_the_previous_shortcuts() {
+        let current = registered(&[binding("Alt+Space")]);
+        let plan = plan(
+            &current,
+            &HashSet::new(),
+            &[binding("Alt+Enter"), binding("Alt+C")],
+        )
+        .unwrap();
+        let taken = parse("Alt+C").unwrap();
+        let active = std::cell::RefCell::new(vec![parse("Alt+Space").unwrap()]);
+        let result = swap(
+            &plan,
+            |shortcut| {
+                if shortcut == taken {
+                    return Err("already registered".into());
+                }
+                active.borrow_mut().push(shortcut);
+                Ok(())
+            },
+            |shortcut| active.borrow_mut().retain(|held| *held != shortcut),
+        );
+        let conflicts = result.unwrap_err();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].shortcut, "Alt+C");
+        assert_eq!(conflicts[0].reason, ConflictReason::Unavailable);
+        assert_eq!(active.into_inner(), [parse("Alt+Space").unwrap()]);
+    }
+
+    #[test]
+    fn shortcuts_refused_at_startup_are_not_retried() {
+        let current = registered(&[binding("Alt+Space")]);
+        let unavailable = HashSet::from([parse("Alt+V").unwrap().id()]);
+        let plan = plan(
+            &current,
+            &unavailable,
+            &[binding("Alt+Space"), binding("Alt+V"), binding("Alt+C")],
+        )
+        .unwrap();
+        assert_eq!(plan.add.len(), 1);
+        assert_eq!(plan.add[0].1, "Alt+C");
+        assert!(plan.remove.is_empty());
+        assert_eq!(plan.bindings.len(), 2);
+        assert!(!plan.bindings.contains_key(&parse("Alt+V").unwrap().id()));
+        assert_eq!(plan.unavailable, unavailable);
+    }
+
+    #[test]
+    fn unavailable_shortcuts_set_again_are_retried() {
+        let current = registered(&[binding("Alt+Space")]);
+        let unavailable = HashSet::from([parse("Alt+V").unwrap().id()]);
+        let dropped = plan(&current, &unavailable, &[binding("Alt+Space")]).unwrap();
+        assert!(dropped.unavailable.is_empty());
+
+        let readded = plan(
+            &current,
+            &dropped.unavailable,
+            &[binding("Alt+Space"), binding("Alt+V")],
+        )
+        .unwrap();
+        assert_eq!(
+            readded.add,
+            [(parse("Alt+V").unwrap(), "Alt+V".to_string())]
+        );
+        assert!(readded.unavailable.is_empty());
+    }
+}
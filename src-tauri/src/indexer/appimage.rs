@@ -0,0 +1,132 @@
+//! AppImages are self-contained executables with no desktop file installed, so the
+//! name and version are taken from the file name, e.g. `Obsidian-1.6.7-x86_64.AppImage`.
+
+use super::AppEntry;
+use std::path::Path;
+
+const ARCHITECTURES: &[&str] = &[
+    "x86_64", "x64", "amd64", "aarch64", "arm64", "armhf", "i386", "i686",
+];
+
+pub fn is_appimage(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("appimage"))
+}
+
+/// Every AppImage directly inside `dir`.
+pub fn scan(dir: &Path, entries: &mut Vec<AppEntry>) {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        if !path.is_file() || !is_appimage(&path) {
+            continue;
+        }
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let (name, version) = name_and_version(stem);
+        if name.is_empty() {
+            continue;
+        }
+        entries.push(AppEntry {
+            name,
+            path: path.to_string_lossy().to_string(),
+            version,
+            description: Some("AppImage".into()),
+            ..Default::default()
+        });
+    }
+}
+
+/// Drops the architecture suffix, then splits off the version (the first `-`/`_`
+/// segment starting with a digit); the rest is the name with separators as spaces.
+fn name_and_version(stem: &str) -> (String, Option<String>) {
+    let mut stem = stem;
+    while let Some(stripped) = ARCHITECTURES.iter().find_map(|arch| {
+        let split = stem.len().checked_sub(arch.len() + 1)?;
+        let (rest, suffix) = stem.split_at_checked(split)?;
+        (suffix[1..].eq_ignore_ascii_case(arch) && suffix.starts_with(['-', '_'])).then_some(rest)
+    }) {
+        stem = stripped;
+    }
+    let mut name = Vec::new();
+    let mut version = None;
+    for segment in stem.split(['-', '_']) {
+        let number = segment.trim_start_matches(['v', 'V']);
+        if !name.is_empty() && number.starts_with(|c: char| c.is_ascii_digit()) {
+            version = Some(number.to_string());
+            break;
+        }
+        name.push(segment);
+    }
+    (name.join(" ").trim().to_string(), version)
+}
+
+/// AppImages are often downloaded without the executable bit; the folder they were put
+/// in is opted in through settings, so marking them runnable matches the user's intent.
+#[cfg(target_os = "linux")]
+pub fn launch(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
+    let mode = metadata.permissions().mode();
+    if mode & 0o100 == 0 {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode | 0o100))
+            .map_err(|e| e.to_string())?;
+    }
+    std::process::Command::new(path)
+        .current_dir(dirs::home_dir().unwrap_or_else(|| "/".into()))
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_come_from_the_file_name() {
+        assert_eq!(
+            name_and_version("Obsidian-1.6.7-x86_64"),
+            ("Obsidian".to_string(), Some("1.6.7".to_string()))
+        );
+        assert_eq!(
+            name_and_version("Joplin-v3.0.15"),
+            ("Joplin".to_string(), Some("3.0.15".to_string()))
+        );
+        assert_eq!(
+            name_and_version("Cursor_x86_64"),
+            ("Cursor".to_string(), None)
+        );
+        assert_eq!(
+            name_and_version("Kdenlive-24.05.2-x86_64"),
+            ("Kdenlive".to_string(), Some("24.05.2".to_string()))
+        );
+        assert_eq!(
+            name_and_version("balena_Etcher-1.18.11-x64"),
+            ("balena Etcher".to_string(), Some("1.18.11".to_string()))
+        );
+    }
+
+    #[test]
+    fn scans_appimages_in_a_folder() {
+        let dir = std::env::temp_dir().join(format!("genie-appimage-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Obsidian-1.6.7.AppImage"), b"\x7fELF").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"").unwrap();
+        let mut entries = Vec::new();
+        scan(&dir, &mut entries);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "Obsidian");
+        assert_eq!(entries[0].version.as_deref(), Some("1.6.7"));
+        assert!(entries[0].path.ends_with("Obsidian-1.6.7.AppImage"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -3,19 +3,38 @@ use std::path::PathBuf;
 
 // TODO: split apps.rs into smaller modules (scan, icon extraction, platform adapters)
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppEntry {
     pub name: String,
     pub path: String,
     pub icon: Option<String>,
+    /// What a shortcut launches; `path` stays the shortcut so its arguments apply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<String>,
+    /// Bundle id on macOS, desktop file id on Linux, package or AppUserModelId on Windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Extra search terms; they match with less weight than the name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
 }
 
 pub fn scan_applications_fast() -> Vec<AppEntry> {
+    let settings = crate::settings::get();
+    let extra_dirs = configured_dirs(&settings.extra_app_dirs);
     let mut entries = Vec::new();
 
     #[cfg(target_os = "macos")]
     {
-        for dir in macos_app_scan_roots() {
+        for dir in macos_app_scan_roots().into_iter().chain(extra_dirs) {
             scan_macos_dir_fast(dir.to_string_lossy().as_ref(), &mut entries);
         }
         scan_macos_prefpanes(&mut entries);
@@ -23,19 +42,94 @@ pub fn scan_applications_fast() -> Vec<AppEntry> {
 
     #[cfg(target_os = "windows")]
     {
-        scan_windows_start_menu(&mut entries);
+        scan_windows_start_menu(&mut entries, &extra_dirs);
     }
 
     #[cfg(target_os = "linux")]
     {
-        scan_linux_desktop_files(&mut entries);
+        scan_linux_desktop_files(&mut entries, &extra_dirs);
+        for dir in configured_dirs(std::slice::from_ref(&settings.appimage_dir)) {
+            super::appimage::scan(&dir, &mut entries);
+        }
     }
 
-    entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    entries.retain(|entry| !super::exclude::is_excluded(entry, &settings.excluded_apps));
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
     entries.dedup_by(|a, b| a.name.eq_ignore_ascii_case(&b.name));
     entries
 }
 
+/// Non-empty directories from settings, with a leading `~` expanded.
+fn configured_dirs(values: &[String]) -> Vec<PathBuf> {
+    values
+        .iter()
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(
+            |value| match value.strip_prefix("~/").zip(dirs::home_dir()) {
+                Some((rest, home)) => home.join(rest),
+                None => PathBuf::from(value),
+            },
+        )
+        .collect()
+}
+
+/// Directories whose changes mean installed apps changed, and how deep to watch them.
+/// App bundles are directories themselves, so macOS roots are watched one level deep.
+/// Some may not exist yet.
+pub fn watch_roots() -> Vec<(PathBuf, notify::RecursiveMode)> {
+    let settings = crate::settings::get();
+    let mut roots = Vec::new();
+
+    #[cfg(target_os = "macos")]
+    roots.extend(
+        macos_app_scan_roots()
+            .into_iter()
+            .map(|dir| (dir, notify::RecursiveMode::NonRecursive)),
+    );
+
+    #[cfg(target_os = "windows")]
+    {
+        roots.extend(
+            windows_start_menu_dirs()
+                .into_iter()
+                .map(|dir| (dir, notify::RecursiveMode::Recursive)),
+        );
+        if let Ok(local_app_data) = std::env::var("LOCALAPPDATA") {
+            roots.push((
+                PathBuf::from(local_app_data).join("Microsoft\\WindowsApps"),
+                notify::RecursiveMode::NonRecursive,
+            ));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    roots.extend(
+        linux_application_dirs()
+            .into_iter()
+            .map(|dir| (dir, notify::RecursiveMode::Recursive)),
+    );
+
+    #[cfg(target_os = "linux")]
+    roots.extend(
+        configured_dirs(std::slice::from_ref(&settings.appimage_dir))
+            .into_iter()
+            .map(|dir| (dir, notify::RecursiveMode::NonRecursive)),
+    );
+
+    let extra_mode = if cfg!(target_os = "macos") {
+        notify::RecursiveMode::NonRecursive
+    } else {
+        notify::RecursiveMode::Recursive
+    };
+    roots.extend(
+        configured_dirs(&settings.extra_app_dirs)
+            .into_iter()
+            .map(|dir| (dir, extra_mode)),
+    );
+    roots
+}
+
 #[cfg(target_os = "macos")]
 fn macos_app_scan_roots() -> Vec<PathBuf> {
     let mut roots = vec![
@@ -90,11 +184,16 @@ fn scan_macos_dir_fast(dir: &str, entries: &mut Vec<AppEntry>) {
                     .unwrap_or("")
                     .to_string();
                 if !name.is_empty() {
-                    entries.push(AppEntry {
+                    let mut app = AppEntry {
                         name,
                         path: p.to_string_lossy().to_string(),
                         icon: None,
-                    });
+                        ..Default::default()
+                    };
+                    if let Some(info) = super::plist::read_xml(&p.join("Contents/Info.plist")) {
+                        super::plist::apply_bundle_info(&info, &mut app);
+                    }
+                    entries.push(app);
                 }
             } else if p.is_dir() {
                 // Recursively scan subdirectories (e.g., for Microsoft Edge.app in /Applications/Microsoft Edge.app)
@@ -181,28 +280,40 @@ fn scan_macos_prefpanes(entries: &mut Vec<AppEntry>) {
             name: format!("{} Settings", name),
             path: url.to_string(),
             icon: None,
+            identifier: url.split_once(':').map(|(_, id)| id.to_string()),
+            description: Some("System Settings".into()),
+            ..Default::default()
         });
     }
 }
 
 #[cfg(target_os = "windows")]
-fn scan_windows_start_menu(entries: &mut Vec<AppEntry>) {
-    let dirs_to_scan = vec![
+fn windows_start_menu_dirs() -> Vec<PathBuf> {
+    vec![
         std::env::var("ProgramData")
             .ok()
             .map(|d| PathBuf::from(d).join("Microsoft\\Windows\\Start Menu\\Programs")),
         dirs::home_dir()
             .map(|d| d.join("AppData\\Roaming\\Microsoft\\Windows\\Start Menu\\Programs")),
-    ];
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
 
+#[cfg(target_os = "windows")]
+fn scan_windows_start_menu(entries: &mut Vec<AppEntry>, extra_dirs: &[PathBuf]) {
     // Also scan System32 for common executables like notepad, calc, etc.
     let system32 = std::env::var("SystemRoot")
         .ok()
         .map(|d| PathBuf::from(d).join("System32"));
 
-    let exe_extensions = ["exe", "lnk"];
-
-    for dir in dirs_to_scan.into_iter().flatten() {
+    // NOTE: several shortcuts often point at one program (per-user and all-users copies)
+    let mut seen_targets = std::collections::HashSet::new();
+    for dir in windows_start_menu_dirs()
+        .into_iter()
+        .chain(extra_dirs.iter().cloned())
+    {
         if !dir.exists() {
             continue;
         }
@@ -211,10 +322,11 @@ fn scan_windows_start_menu(entries: &mut Vec<AppEntry>) {
             .filter_map(|e| e.ok())
         {
             let p = entry.path();
-            let ext = p.extension().and_then(|e| e.to_str()).unwrap_or("");
-            if !exe_extensions.contains(&ext) {
-                continue;
-            }
+            let ext = p
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("")
+                .to_lowercase();
             let name = p
                 .file_stem()
                 .and_then(|s| s.to_str())
@@ -223,10 +335,41 @@ fn scan_windows_start_menu(entries: &mut Vec<AppEntry>) {
             if name.is_empty() {
                 continue;
             }
+            let (target, arguments, description) = match ext.as_str() {
+                "lnk" => match super::lnk::read(p) {
+                    Ok(link) => (
+                        link.target.map(|target| {
+                            super::lnk::expand_environment(&target, |var| std::env::var(var).ok())
+                        }),
+                        link.arguments,
+                        link.description.filter(|description| description != &name),
+                    ),
+                    Err(_) => (None, None, None),
+                },
+                "exe" => (Some(p.to_string_lossy().to_string()), None, None),
+                _ => continue,
+            };
+            if super::lnk::is_noise(&name, target.as_deref()) {
+                continue;
+            }
+            if let Some(target) = &target {
+                let key = format!(
+                    "{}\t{}",
+                    target.to_lowercase(),
+                    arguments.as_deref().unwrap_or_default()
+                );
+                if !seen_targets.insert(key) {
+                    continue;
+                }
+            }
             entries.push(AppEntry {
                 name,
                 path: p.to_string_lossy().to_string(),
                 icon: None,
+                target: target.filter(|_| ext == "lnk"),
+                arguments,
+                description,
+                ..Default::default()
             });
         }
     }
@@ -271,6 +414,7 @@ fn scan_windows_start_menu(entries: &mut Vec<AppEntry>) {
                                 .to_string(),
                             path: p.to_string_lossy().to_string(),
                             icon: None,
+                            ..Default::default()
                         });
                     }
                 }
@@ -312,6 +456,7 @@ fn scan_windows_start_menu(entries: &mut Vec<AppEntry>) {
                     name: name.to_string(),
                     path: path.to_string_lossy().to_string(),
                     icon: None,
+                    ..Default::default()
                 });
             }
         }
@@ -339,6 +484,7 @@ fn scan_windows_start_menu(entries: &mut Vec<AppEntry>) {
                         name: name.to_string(),
                         path: p.to_string_lossy().to_string(),
                         icon: None,
+                        ..Default::default()
                     });
                 }
             }
@@ -387,10 +533,17 @@ fn scan_windows_start_menu(entries: &mut Vec<AppEntry>) {
                             } else {
                                 name
                             };
+                            let (identifier, version) = super::uwp::package_identity(pkg_name)
+                                .map_or((None, None), |(family, version)| {
+                                    (Some(family), Some(version))
+                                });
                             entries.push(AppEntry {
                                 name: friendly_name.to_string(),
                                 path: p.to_string_lossy().to_string(),
                                 icon: None,
+                                identifier,
+                                version,
+                                ..Default::default()
                             });
                         }
                     }
@@ -420,21 +573,30 @@ fn scan_windows_start_menu(entries: &mut Vec<AppEntry>) {
                 name: name.to_string(),
                 path: format!("shell:AppsFolder\\{}", app_id),
                 icon: None,
+                identifier: Some(app_id.to_string()),
+                ..Default::default()
             });
         }
     }
 }
 
 #[cfg(target_os = "linux")]
-fn scan_linux_desktop_files(entries: &mut Vec<AppEntry>) {
-    let xdg_dirs = vec![
+fn linux_application_dirs() -> Vec<PathBuf> {
+    vec![
         PathBuf::from("/usr/share/applications"),
         PathBuf::from("/usr/local/share/applications"),
         dirs::home_dir()
             .map(|d| d.join(".local/share/applications"))
             .unwrap_or_default(),
-    ];
-    for dir in xdg_dirs {
+    ]
+}
+
+#[cfg(target_os = "linux")]
+fn scan_linux_desktop_files(entries: &mut Vec<AppEntry>, extra_dirs: &[PathBuf]) {
+    for dir in linux_application_dirs()
+        .into_iter()
+        .chain(extra_dirs.iter().cloned())
+    {
         if !dir.exists() {
             continue;
         }
@@ -450,16 +612,9 @@ fn scan_linux_desktop_files(entries: &mut Vec<AppEntry>) {
             let Ok(content) = std::fs::read_to_string(p) else {
                 continue;
             };
-            let name = content
-                .lines()
-                .find(|l| l.starts_with("Name="))
-                .map(|l| l.trim_start_matches("Name=").to_string());
-            if let Some(name) = name {
-                entries.push(AppEntry {
-                    name,
-                    path: p.to_string_lossy().to_string(),
-                    icon: None,
-                });
+            let id = super::desktop::desktop_id(&dir, p);
+            if let Some(app) = super::desktop::parse(&content, p, &id) {
+                entries.push(app);
             }
         }
     }
@@ -567,6 +722,10 @@ if ($app) {{
     if !app_path.exists() {
         return None;
     }
+    // Packaged apps: the shell only has a generic icon for their executables
+    if let Some(icon) = super::uwp::logo_data_uri(app_path) {
+        return Some(icon);
+    }
 
     // Use PowerShell to extract icon from exe
     let script = format!(
@@ -716,25 +875,9 @@ fn read_plist_icon_name(plist_path: &std::path::Path) -> Option<String> {
 
 #[cfg(target_os = "macos")]
 fn parse_plist_icon_name(content: &str) -> Option<String> {
-    let keys = ["CFBundleIconFile", "CFBundleIconName"];
-    for key in keys {
-        let marker = format!("<key>{}</key>", key);
-        let Some(start) = content.find(&marker) else {
-            continue;
-        };
-        let rest = &content[start + marker.len()..];
-        let Some(s) = rest.find("<string>") else {
-            continue;
-        };
-        let Some(e) = rest[s + 8..].find("</string>") else {
-            continue;
-        };
-        let value = rest[s + 8..s + 8 + e].trim().to_string();
-        if !value.is_empty() {
-            return Some(value);
-        }
-    }
-    None
+    ["CFBundleIconFile", "CFBundleIconName"]
+        .into_iter()
+        .find_map(|key| super::plist::string(content, key))
 }
 
 #[cfg(target_os = "macos")]
@@ -0,0 +1,192 @@
+//! Freedesktop `.desktop` entries: only the `[Desktop Entry]` group is read, and
+//! localized keys (`Name[de]=`) are ignored in favour of the untranslated value.
+
+use super::AppEntry;
+use std::path::Path;
+
+/// The app a desktop file launches, or `None` for hidden entries, links and
+/// directories. `id` is the desktop file id, e.g. `org.gnome.Nautilus`.
+pub fn parse(content: &str, path: &Path, id: &str) -> Option<AppEntry> {
+    let mut in_entry = false;
+    let mut entry = AppEntry {
+        path: path.to_string_lossy().to_string(),
+        identifier: Some(id.to_string()),
+        ..Default::default()
+    };
+    let mut generic_name = None;
+    let mut is_application = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "Type" => is_application = value == "Application",
+            "Name" => entry.name = unescape(value),
+            "GenericName" => generic_name = Some(unescape(value)),
+            "Comment" => entry.description = Some(unescape(value)),
+            "Keywords" => entry.keywords = list(value),
+            "Categories" => entry.categories = list(value),
+            "X-AppImage-Version" => entry.version = Some(unescape(value)),
+            "NoDisplay" | "Hidden" if value == "true" => return None,
+            _ => {}
+        }
+    }
+    if !is_application || entry.name.is_empty() {
+        return None;
+    }
+    // NOTE: "Web Browser" style generic names are both a fallback description and a
+    // search term
+    if let Some(generic_name) = generic_name.filter(|name| !name.is_empty()) {
+        if entry.description.is_none() {
+            entry.description = Some(generic_name.clone());
+        }
+        entry.keywords.push(generic_name);
+    }
+    Some(entry)
+}
+
+/// Desktop file ids replace the path separators below an `applications` dir with `-`.
+pub fn desktop_id(applications_dir: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(applications_dir).unwrap_or(file);
+    let id = relative
+        .with_extension("")
+        .to_string_lossy()
+        .replace(std::path::MAIN_SEPARATOR, "-");
+    id.trim_start_matches('-').to_string()
+}
+
+fn list(value: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    if next == ';' {
+                        items.last_mut().unwrap().push(';');
+                    } else {
+                        items
+                            .last_mut()
+                            .unwrap()
+                            .push_str(&unescape(&format!("\\{}", next)));
+                    }
+                }
+            }
+            ';' => items.push(String::new()),
+            ch => items.last_mut().unwrap().push(ch),
+        }
+    }
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            unescaped.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_metadata_from_the_desktop_entry_group() {
+        let content = "\
+# comment
+[Desktop Entry]
+Type=Application
+Name=Firefox
+Name[de]=Firefox Browser
+GenericName=Web Browser
+Comment=Browse the World Wide Web
+Keywords=Internet;WWW;Browser;Web;Explorer;
+Categories=GNOME;GTK;Network;WebBrowser;
+X-AppImage-Version=128.0
+Exec=firefox %u
+
+[Desktop Action new-window]
+Name=Open a New Window
+Comment=Not the app description
+";
+        let entry = parse(
+            content,
+            Path::new("/usr/share/applications/firefox.desktop"),
+            "firefox",
+        )
+        .unwrap();
+        assert_eq!(entry.name, "Firefox");
+        assert_eq!(entry.identifier.as_deref(), Some("firefox"));
+        assert_eq!(entry.version.as_deref(), Some("128.0"));
+        assert_eq!(
+            entry.description.as_deref(),
+            Some("Browse the World Wide Web")
+        );
+        assert_eq!(
+            entry.keywords,
+            [
+                "Internet",
+                "WWW",
+                "Browser",
+                "Web",
+                "Explorer",
+                "Web Browser"
+            ]
+        );
+        assert_eq!(entry.categories, ["GNOME", "GTK", "Network", "WebBrowser"]);
+    }
+
+    #[test]
+    fn skips_hidden_entries_and_unescapes_values() {
+        let hidden = "[Desktop Entry]\nType=Application\nName=Helper\nNoDisplay=true\n";
+        assert!(parse(hidden, Path::new("h.desktop"), "h").is_none());
+        let link = "[Desktop Entry]\nType=Link\nName=Docs\nURL=https://example.com\n";
+        assert!(parse(link, Path::new("l.desktop"), "l").is_none());
+
+        let escaped =
+            "[Desktop Entry]\nType=Application\nName=A\\sB\nKeywords=semi\\;colon;tab\\tstop\n";
+        let entry = parse(escaped, Path::new("a.desktop"), "a").unwrap();
+        assert_eq!(entry.name, "A B");
+        assert_eq!(entry.keywords, ["semi;colon", "tab\tstop"]);
+        assert_eq!(entry.description, None);
+    }
+
+    #[test]
+    fn desktop_ids_flatten_subdirectories() {
+        let dir = Path::new("/usr/share/applications");
+        assert_eq!(
+            desktop_id(dir, &dir.join("kde4/konsole.desktop")),
+            "kde4-konsole"
+        );
+        assert_eq!(
+            desktop_id(dir, &dir.join("org.gnome.Nautilus.desktop")),
+            "org.gnome.Nautilus"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! `excluded_apps` patterns. A pattern with a path separator is compared with the app's
+//! path (and shortcut target); anything else with its name and file name. Matching is
+//! case-insensitive, and `*` and `?` are the only wildcards.
+
+use super::AppEntry;
+
+pub fn is_excluded(app: &AppEntry, patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .map(|pattern| pattern.trim())
+        .filter(|pattern| !pattern.is_empty())
+        .any(|pattern| matches_app(app, pattern))
+}
+
+fn matches_app(app: &AppEntry, pattern: &str) -> bool {
+    let pattern = normalize(pattern);
+    if pattern.contains('/') {
+        return [Some(&app.path), app.target.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|path| matches_path(&pattern, &normalize(path)));
+    }
+    let file_name = app
+        .path
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    glob_match(&pattern, &app.name.to_lowercase()) || glob_match(&pattern, &file_name)
+}
+
+/// A literal directory excludes everything below it.
+fn matches_path(pattern: &str, path: &str) -> bool {
+    if glob_match(pattern, path) {
+        return true;
+    }
+    let is_literal = !pattern.contains(['*', '?']);
+    is_literal && path.starts_with(&format!("{}/", pattern.trim_end_matches('/')))
+}
+
+fn normalize(value: &str) -> String {
+    value.replace('\\', "/").to_lowercase()
+}
+
+/// `*` matches any run of characters (separators included), `?` any single one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str, path: &str) -> AppEntry {
+        AppEntry {
+            name: name.into(),
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn globs_match_whole_strings() {
+        assert!(glob_match("*uninstall*", "firefox uninstaller"));
+        assert!(glob_match("java?", "java8"));
+        assert!(glob_match("a*b*c", "axxbyybzc"));
+        assert!(!glob_match("java?", "java"));
+        assert!(!glob_match("fire", "firefox"));
+    }
+
+    #[test]
+    fn excludes_by_name_or_path() {
+        let patterns = vec![
+            "Steam*".to_string(),
+            "/opt/vendor".to_string(),
+            "C:\\Program Files\\Tools\\*.exe".to_string(),
+            "  ".to_string(),
+        ];
+        assert!(is_excluded(
+            &app(
+                "Steam Linux Runtime",
+                "/usr/share/applications/steam-runtime.desktop"
+            ),
+            &patterns
+        ));
+        assert!(is_excluded(
+            &app("Vendor Tool", "/opt/vendor/bin/tool.desktop"),
+            &patterns
+        ));
+        assert!(!is_excluded(
+            &app("Vendorless", "/opt/vendorless/tool.desktop"),
+            &patterns
+        ));
+        assert!(is_excluded(
+            &app("Helper", "c:/program files/tools/helper.exe"),
+            &patterns
+        ));
+        let shortcut = AppEntry {
+            target: Some("C:\\Program Files\\Tools\\cli.exe".into()),
+            ..app("CLI", "C:\\ProgramData\\Start Menu\\CLI.lnk")
+        };
+        assert!(is_excluded(&shortcut, &patterns));
+        assert!(is_excluded(
+            &app("Calculator", "/usr/share/applications/calc.desktop"),
+            &["calc.desktop".to_string()]
+        ));
+        assert!(!is_excluded(
+            &app("Firefox", "/usr/share/applications/firefox.desktop"),
+            &patterns
+        ));
+    }
+}
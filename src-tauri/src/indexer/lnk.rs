@@ -0,0 +1,390 @@
+//! Minimal reader for Windows shell links (`.lnk`, [MS-SHLLINK]). Only the parts the
+//! Start Menu scan needs are decoded: the target path, arguments and description.
+
+use std::path::Path;
+
+const HEADER_SIZE: usize = 0x4C;
+const LINK_CLSID: [u8; 16] = [
+    0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x01;
+const HAS_LINK_INFO: u32 = 0x02;
+const HAS_NAME: u32 = 0x04;
+const HAS_RELATIVE_PATH: u32 = 0x08;
+const HAS_WORKING_DIR: u32 = 0x10;
+const HAS_ARGUMENTS: u32 = 0x20;
+const HAS_ICON_LOCATION: u32 = 0x40;
+const IS_UNICODE: u32 = 0x80;
+
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x01;
+const ENVIRONMENT_VARIABLE_BLOCK: u32 = 0xA000_0001;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShellLink {
+    /// Absolute target path; may still contain `%VARIABLES%` when the link only
+    /// stores an environment-relative target.
+    pub target: Option<String>,
+    pub arguments: Option<String>,
+    pub description: Option<String>,
+}
+
+pub fn read(path: &Path) -> Result<ShellLink, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let Parsed {
+        mut link,
+        relative_target,
+    } = parse(&bytes)?;
+    if link.target.is_none() {
+        link.target = relative_target.map(|relative| resolve_relative(path, &relative));
+    }
+    Ok(link)
+}
+
+struct Parsed {
+    link: ShellLink,
+    relative_target: Option<String>,
+}
+
+fn parse(bytes: &[u8]) -> Result<Parsed, String> {
+    let mut reader = Reader { bytes, offset: 0 };
+    if reader.u32()? as usize != HEADER_SIZE || reader.take(16)? != LINK_CLSID {
+        return Err("Not a shell link".into());
+    }
+    let flags = reader.u32()?;
+    reader.offset = HEADER_SIZE;
+
+    if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+        let size = reader.u16()? as usize;
+        reader.take(size)?;
+    }
+    let mut link = ShellLink::default();
+    if flags & HAS_LINK_INFO != 0 {
+        let start = reader.offset;
+        let size = reader.u32()? as usize;
+        link.target = local_path(reader.slice(start, size)?);
+        reader.offset = start + size;
+    }
+
+    let unicode = flags & IS_UNICODE != 0;
+    let mut string = |flag: u32| -> Result<Option<String>, String> {
+        if flags & flag == 0 {
+            return Ok(None);
+        }
+        let value = reader.counted_string(unicode)?;
+        Ok(Some(value).filter(|value| !value.is_empty()))
+    };
+    // NOTE: the strings are stored back to back, so unused ones still have to be read past
+    link.description = string(HAS_NAME)?;
+    let relative_target = string(HAS_RELATIVE_PATH)?;
+    string(HAS_WORKING_DIR)?;
+    link.arguments = string(HAS_ARGUMENTS)?;
+    string(HAS_ICON_LOCATION)?;
+
+    if link.target.is_none() {
+        link.target = environment_target(&mut reader);
+    }
+    Ok(Parsed {
+        link,
+        relative_target,
+    })
+}
+
+/// `LocalBasePath` followed by `CommonPathSuffix` from a LinkInfo structure.
+fn local_path(info: &[u8]) -> Option<String> {
+    let mut reader = Reader {
+        bytes: info,
+        offset: 4,
+    };
+    let header_size = reader.u32().ok()? as usize;
+    let flags = reader.u32().ok()?;
+    if flags & VOLUME_ID_AND_LOCAL_BASE_PATH == 0 {
+        return None;
+    }
+    let _volume_id_offset = reader.u32().ok()?;
+    let base_offset = reader.u32().ok()? as usize;
+    let _network_offset = reader.u32().ok()?;
+    let suffix_offset = reader.u32().ok()? as usize;
+    let (base, suffix) = if header_size >= 0x24 {
+        let base_unicode = reader.u32().ok()? as usize;
+        let suffix_unicode = reader.u32().ok()? as usize;
+        (
+            utf16_z(info.get(base_unicode..)?),
+            utf16_z(info.get(suffix_unicode..)?),
+        )
+    } else {
+        (
+            ansi_z(info.get(base_offset..)?),
+            ansi_z(info.get(suffix_offset..)?),
+        )
+    };
+    let path = format!("{}{}", base, suffix);
+    Some(path).filter(|path| !path.is_empty())
+}
+
+/// Links made by installers often store only `%ProgramFiles%\…` in an
+/// EnvironmentVariableDataBlock instead of a LinkInfo.
+fn environment_target(reader: &mut Reader) -> Option<String> {
+    loop {
+        let start = reader.offset;
+        let size = reader.u32().ok()? as usize;
+        if size < 8 {
+            return None;
+        }
+        let signature = reader.u32().ok()?;
+        let block = reader.slice(start, size).ok()?;
+        if signature == ENVIRONMENT_VARIABLE_BLOCK && size >= 8 + 260 + 520 {
+            let unicode = utf16_z(&block[8 + 260..8 + 260 + 520]);
+            let target = if unicode.is_empty() {
+                ansi_z(&block[8..8 + 260])
+            } else {
+                unicode
+            };
+            return Some(target).filter(|target| !target.is_empty());
+        }
+        reader.offset = start + size;
+    }
+}
+
+/// Joins a `..\..\Program Files\App\app.exe` style path onto the link's directory.
+fn resolve_relative(link: &Path, relative: &str) -> String {
+    let mut resolved = link.parent().map(Path::to_path_buf).unwrap_or_default();
+    for part in relative.split(['\\', '/']).filter(|part| !part.is_empty()) {
+        match part {
+            "." => {}
+            ".." => {
+                resolved.pop();
+            }
+            part => resolved.push(part),
+        }
+    }
+    resolved.to_string_lossy().to_string()
+}
+
+/// `%NAME%` references replaced from the environment; unknown names are left as-is.
+pub fn expand_environment(value: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('%') {
+            Some(end) if end > 0 => {
+                let name = &after[..end];
+                match lookup(name) {
+                    Some(found) => expanded.push_str(&found),
+                    None => {
+                        expanded.push('%');
+                        expanded.push_str(name);
+                        expanded.push('%');
+                    }
+                }
+                rest = &after[end + 1..];
+            }
+            _ => {
+                expanded.push('%');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// File extensions of shortcuts that open documents rather than programs.
+const DOCUMENT_EXTENSIONS: &[&str] = &[
+    "txt", "rtf", "pdf", "htm", "html", "chm", "hlp", "url", "md", "doc", "docx", "xml", "log",
+];
+/// Link names installers add next to the real launcher.
+const NON_APP_NAMES: &[&str] = &[
+    "uninstall",
+    "uninstaller",
+    "readme",
+    "read me",
+    "release notes",
+    "license",
+    "documentation",
+    "manual",
+    "changelog",
+];
+
+/// Whether a Start Menu shortcut is an uninstaller, documentation or web link rather
+/// than something worth launching from search.
+pub fn is_noise(name: &str, target: Option<&str>) -> bool {
+    let name = name.to_lowercase();
+    let noisy_name = NON_APP_NAMES.iter().any(|noise| {
+        name == *noise
+            || name.starts_with(&format!("{} ", noise))
+            || name.ends_with(&format!(" {}", noise))
+    });
+    if noisy_name || name.contains("uninstall") {
+        return true;
+    }
+    let Some(target) = target else {
+        return false;
+    };
+    let target = target.to_lowercase();
+    if target.starts_with("http://") || target.starts_with("https://") {
+        return true;
+    }
+    let file_name = target.rsplit(['\\', '/']).next().unwrap_or(&target);
+    let (stem, extension) = file_name.rsplit_once('.').unwrap_or((file_name, ""));
+    stem.starts_with("unins")
+        || stem.starts_with("uninstall")
+        || DOCUMENT_EXTENSIONS.contains(&extension)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .ok_or("Shell link is truncated")?;
+        let slice = self
+            .bytes
+            .get(self.offset..end)
+            .ok_or("Shell link is truncated")?;
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn slice(&self, start: usize, len: usize) -> Result<&'a [u8], String> {
+        self.bytes
+            .get(start..start.checked_add(len).ok_or("Shell link is truncated")?)
+            .ok_or_else(|| "Shell link is truncated".to_string())
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// StringData entries: a character count followed by UTF-16LE or ANSI text.
+    fn counted_string(&mut self, unicode: bool) -> Result<String, String> {
+        let count = self.u16()? as usize;
+        if unicode {
+            let bytes = self.take(count * 2)?;
+            Ok(utf16(bytes))
+        } else {
+            Ok(ansi(self.take(count)?))
+        }
+    }
+}
+
+fn utf16(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn utf16_z(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|unit| *unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// ANSI strings use the system code page; Latin-1 is a lossless stand-in for paths.
+fn ansi(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| *byte as char).collect()
+}
+
+fn ansi_z(bytes: &[u8]) -> String {
+    let end = bytes
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(bytes.len());
+    ansi(&bytes[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/indexer/fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn reads_targets_from_link_info_and_string_data() {
+        let firefox = read(&fixture("firefox.lnk")).unwrap();
+        assert_eq!(
+            firefox,
+            ShellLink {
+                target: Some(r"C:\Program Files\Mozilla Firefox\firefox.exe".into()),
+                arguments: None,
+                description: Some("Browse the World Wide Web".into()),
+            }
+        );
+
+        let code = read(&fixture("code.lnk")).unwrap();
+        assert_eq!(
+            code.target.as_deref(),
+            Some(r"C:\Users\ada\AppData\Local\Programs\Microsoft VS Code\Code.exe")
+        );
+        assert_eq!(code.arguments.as_deref(), Some("--new-window"));
+    }
+
+    #[test]
+    fn falls_back_to_environment_and_relative_targets() {
+        let terminal = read(&fixture("terminal.lnk")).unwrap();
+        assert_eq!(
+            terminal.target.as_deref(),
+            Some(r"%SystemRoot%\System32\cmd.exe")
+        );
+        let readme = read(&fixture("readme.lnk")).unwrap();
+        let expected = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/indexer/Foo/README.txt");
+        assert_eq!(
+            readme.target.as_deref(),
+            Some(expected.to_string_lossy().as_ref())
+        );
+        assert!(parse(b"not a link").is_err());
+        assert!(parse(&std::fs::read(fixture("firefox.lnk")).unwrap()[..90]).is_err());
+    }
+
+    #[test]
+    fn filters_uninstallers_and_documents() {
+        let uninstall = read(&fixture("uninstall.lnk")).unwrap();
+        assert!(is_noise("Foo", uninstall.target.as_deref()));
+        assert!(is_noise("Uninstall Foo", Some(r"C:\Foo\foo.exe")));
+        assert!(is_noise("Foo Manual", None));
+        assert!(is_noise("Foo Readme", Some(r"C:\Foo\README.txt")));
+        assert!(is_noise("Foo Website", Some("https://foo.example")));
+        assert!(!is_noise(
+            "Firefox",
+            Some(r"C:\Program Files\Mozilla Firefox\firefox.exe")
+        ));
+        assert!(!is_noise("Advertised App", None));
+        assert!(!is_noise("Licensing Helper", Some(r"C:\Foo\helper.exe")));
+    }
+
+    #[test]
+    fn expands_environment_references() {
+        let lookup = |name: &str| (name == "SystemRoot").then(|| r"C:\Windows".to_string());
+        assert_eq!(
+            expand_environment(r"%SystemRoot%\System32\cmd.exe", lookup),
+            r"C:\Windows\System32\cmd.exe"
+        );
+        assert_eq!(
+            expand_environment("%Missing%\\a 100%", lookup),
+            "%Missing%\\a 100%"
+        );
+    }
+}
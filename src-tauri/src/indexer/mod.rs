@@ -1,18 +1,87 @@
+#[cfg(any(target_os = "linux", test))]
+mod appimage;
 mod apps;
+#[cfg(any(target_os = "linux", test))]
+mod desktop;
+mod exclude;
+#[cfg(any(target_os = "windows", test))]
+mod lnk;
+#[cfg(any(target_os = "macos", test))]
+mod plist;
+#[cfg(any(target_os = "windows", test))]
+mod uwp;
 
+use notify::{RecommendedWatcher, Watcher};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Mutex, OnceLock};
+use std::sync::{mpsc, Mutex, OnceLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub use apps::{get_app_icon, AppEntry};
 
 static APP_INDEX: OnceLock<Mutex<Vec<AppEntry>>> = OnceLock::new();
 static LAST_REFRESH_AT_MS: AtomicU64 = AtomicU64::new(0);
+static WATCHER: OnceLock<Mutex<AppWatcher>> = OnceLock::new();
+/// Startup, the watcher and searches can all ask for a rescan; one runs at a time so
+/// the newest scan is the one that ends up in the index and the cache file.
+static RESCAN: Mutex<()> = Mutex::new(());
 
+struct AppWatcher {
+    watcher: RecommendedWatcher,
+    /// Every path handed to the watcher, so they can all be unwatched.
+    watched: Vec<PathBuf>,
+    /// Scan roots that exist and are watched directly.
+    roots: Vec<PathBuf>,
+    /// Scan roots that do not exist yet; their nearest existing parent is watched.
+    pending: Vec<PathBuf>,
+}
+
+/// Whether a change at `path` can affect the scan, rather than being an unrelated file
+/// next to a pending root.
+fn affects_scan(path: &Path, roots: &[PathBuf], pending: &[PathBuf]) -> bool {
+    roots.iter().any(|root| path.starts_with(root))
+        || pending
+            .iter()
+            .any(|root| root.starts_with(path) || path.starts_with(root))
+}
+
+const CACHE_FILE: &str = "apps.json";
+/// Installers touch many files in a burst; rescan once the directories go quiet.
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Serves the index saved by the last run straight away, then rescans and keeps
+/// watching the application directories on a background thread.
 pub fn init() {
-    let fast_entries = apps::scan_applications_fast();
+    let cached = load_cache(&cache_path()).unwrap_or_default();
     LAST_REFRESH_AT_MS.store(now_millis(), Ordering::SeqCst);
-    let _ = APP_INDEX.set(Mutex::new(fast_entries));
+    let _ = APP_INDEX.set(Mutex::new(cached));
+    std::thread::spawn(|| {
+        rescan();
+        watch();
+    });
+}
+
+/// Rescans in the background after the app scan settings changed.
+pub fn reload() {
+    LAST_REFRESH_AT_MS.store(now_millis(), Ordering::SeqCst);
+    std::thread::spawn(|| {
+        rescan();
+        watch_scan_roots();
+    });
+}
+
+/// Launches an AppImage from the index; anything else is refused.
+#[cfg(target_os = "linux")]
+pub fn launch_appimage(path: &str) -> Result<(), String> {
+    if !get_apps().iter().any(|app| app.path == path) {
+        return Err(format!("Not an indexed AppImage: {}", path));
+    }
+    appimage::launch(Path::new(path))
+}
+
+#[cfg(target_os = "linux")]
+pub fn is_appimage(path: &str) -> bool {
+    appimage::is_appimage(Path::new(path))
 }
 
 pub fn get_apps() -> Vec<AppEntry> {
@@ -40,15 +109,123 @@ pub fn refresh_apps_with_cooldown(cooldown: Duration) -> Option<Vec<AppEntry>> {
         return None;
     }
 
-    let refreshed = apps::scan_applications_fast();
+    Some(rescan())
+}
+
+fn rescan() -> Vec<AppEntry> {
+    let _running = RESCAN.lock();
+    let apps = apps::scan_applications_fast();
     if let Some(mutex) = APP_INDEX.get() {
         if let Ok(mut guard) = mutex.lock() {
-            *guard = refreshed.clone();
-            return Some(refreshed);
+            *guard = apps.clone();
+        }
+    } else {
+        let _ = APP_INDEX.set(Mutex::new(apps.clone()));
+    }
+    let _ = save_cache(&cache_path(), &apps);
+    apps
+}
+
+/// Blocks for as long as the watcher lives, rescanning after each burst of changes.
+fn watch() {
+    let (sender, receiver) = mpsc::channel();
+    let Ok(watcher) = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            if !event.kind.is_access() {
+                let _ = sender.send(event.paths);
+            }
+        }
+    }) else {
+        return;
+    };
+    let _ = WATCHER.set(Mutex::new(AppWatcher {
+        watcher,
+        watched: Vec::new(),
+        roots: Vec::new(),
+        pending: Vec::new(),
+    }));
+    watch_scan_roots();
+    while let Ok(paths) = receiver.recv() {
+        if !is_relevant(&paths) {
+            continue;
+        }
+        while receiver.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+        LAST_REFRESH_AT_MS.store(now_millis(), Ordering::SeqCst);
+        rescan();
+        // NOTE: a pending root may have been created; watch it instead of its parent
+        watch_scan_roots();
+    }
+}
+
+fn is_relevant(paths: &[PathBuf]) -> bool {
+    let Some(Ok(state)) = WATCHER.get().map(Mutex::lock) else {
+        return true;
+    };
+    paths
+        .iter()
+        .any(|path| affects_scan(path, &state.roots, &state.pending))
+}
+
+/// Points the watcher at the current scan roots, which follow `extra_app_dirs`. A root
+/// that does not exist yet, like `~/.local/share/applications` on a fresh profile, is
+/// noticed through its nearest existing parent.
+fn watch_scan_roots() {
+    let Some(Ok(mut guard)) = WATCHER.get().map(Mutex::lock) else {
+        return;
+    };
+    let state = &mut *guard;
+    for path in state.watched.drain(..) {
+        let _ = state.watcher.unwatch(&path);
+    }
+    state.roots.clear();
+    state.pending.clear();
+    for (root, mode) in apps::watch_roots() {
+        if root.is_dir() {
+            if state.watcher.watch(&root, mode).is_ok() {
+                state.watched.push(root.clone());
+                state.roots.push(root);
+            }
+            continue;
+        }
+        let Some(parent) = root.ancestors().skip(1).find(|dir| dir.is_dir()) else {
+            continue;
+        };
+        let parent = parent.to_path_buf();
+        if !state.watched.contains(&parent) {
+            if state
+                .watcher
+                .watch(&parent, notify::RecursiveMode::NonRecursive)
+                .is_err()
+            {
+                continue;
+            }
+            state.watched.push(parent);
         }
+        state.pending.push(root);
+    }
+}
+
+fn cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("spotlight-genie")
+        .join(CACHE_FILE)
+}
+
+fn load_cache(path: &Path) -> Option<Vec<AppEntry>> {
+    let json = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Writes through a temporary file so a crash mid-write cannot leave an unreadable cache.
+fn save_cache(path: &Path, apps: &[AppEntry]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
     }
-    let _ = APP_INDEX.set(Mutex::new(refreshed.clone()));
-    Some(refreshed)
+    let json = serde_json::to_string(apps).map_err(|e| e.to_string())?;
+    let temp = path.with_extension("tmp");
+    std::fs::write(&temp, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&temp, path).map_err(|e| e.to_string())
 }
 
 fn now_millis() -> u64 {
@@ -57,3 +234,49 @@ fn now_millis() -> u64 {
         .unwrap_or_default()
         .as_millis() as u64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_round_trips_app_entries() {
+        let dir = std::env::temp_dir().join(format!("genie-app-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join(CACHE_FILE);
+        assert!(load_cache(&path).is_none());
+
+        let apps = vec![AppEntry {
+            name: "Firefox".into(),
+            path: "/usr/share/applications/firefox.desktop".into(),
+            identifier: Some("firefox".into()),
+            keywords: vec!["Web Browser".into()],
+            ..Default::default()
+        }];
+        save_cache(&path, &apps).unwrap();
+        let loaded = load_cache(&path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].name, "Firefox");
+        assert_eq!(loaded[0].identifier.as_deref(), Some("firefox"));
+        assert_eq!(loaded[0].keywords, ["Web Browser"]);
+        assert!(!path.with_extension("tmp").exists());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(load_cache(&path).is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn only_changes_on_the_way_to_a_root_trigger_rescans() {
+        let roots = [PathBuf::from("/usr/share/applications")];
+        let pending = [PathBuf::from("/home/u/.local/share/applications")];
+        let affects = |path: &str| affects_scan(Path::new(path), &roots, &pending);
+
+        assert!(affects("/usr/share/applications/firefox.desktop"));
+        assert!(affects("/home/u/.local/share/applications"));
+        assert!(affects("/home/u/.local/share/applications/foo.desktop"));
+        assert!(affects("/home/u/.local/share"));
+        assert!(!affects("/home/u/.local/share/recently-used.xbel"));
+        assert!(!affects("/usr/share/applications-old"));
+    }
+}
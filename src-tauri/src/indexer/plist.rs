@@ -0,0 +1,125 @@
+//! Just enough of the XML property list format to read string keys from an app
+//! bundle's `Info.plist`.
+
+use super::AppEntry;
+
+const CATEGORY_PREFIX: &str = "public.app-category.";
+
+/// The `<string>` value following `<key>key</key>`, if it is non-empty.
+pub fn string(content: &str, key: &str) -> Option<String> {
+    let marker = format!("<key>{}</key>", key);
+    let rest = &content[content.find(&marker)? + marker.len()..];
+    let rest = rest.trim_start().strip_prefix("<string>")?;
+    let value = unescape(rest[..rest.find("</string>")?].trim());
+    Some(value).filter(|value| !value.is_empty())
+}
+
+/// Fills the identifier, version, category and alternate names of a bundle.
+pub fn apply_bundle_info(content: &str, entry: &mut AppEntry) {
+    entry.identifier = string(content, "CFBundleIdentifier");
+    entry.version = string(content, "CFBundleShortVersionString")
+        .or_else(|| string(content, "CFBundleVersion"));
+    entry.description = string(content, "CFBundleGetInfoString");
+    if let Some(category) = string(content, "LSApplicationCategoryType") {
+        entry.categories = vec![category_name(&category)];
+    }
+    // NOTE: the bundle's own names cover apps whose folder is renamed or abbreviated,
+    // e.g. "Code.app" shown as "Visual Studio Code"
+    for key in ["CFBundleDisplayName", "CFBundleName"] {
+        if let Some(name) = string(content, key) {
+            if !name.eq_ignore_ascii_case(&entry.name) && !entry.keywords.contains(&name) {
+                entry.keywords.push(name);
+            }
+        }
+    }
+}
+
+/// `public.app-category.developer-tools` becomes `Developer Tools`.
+fn category_name(uti: &str) -> String {
+    uti.strip_prefix(CATEGORY_PREFIX)
+        .unwrap_or(uti)
+        .split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// `Info.plist` as XML; binary plists are converted with `plutil`.
+#[cfg(target_os = "macos")]
+pub fn read_xml(path: &std::path::Path) -> Option<String> {
+    let bytes = std::fs::read(path).ok()?;
+    if !bytes.starts_with(b"bplist") {
+        return String::from_utf8(bytes).ok();
+    }
+    let output = std::process::Command::new("plutil")
+        .args(["-convert", "xml1", "-o", "-"])
+        .arg(path)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_bundle_info() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+	<key>CFBundleDisplayName</key>
+	<string>Visual Studio Code</string>
+	<key>CFBundleIdentifier</key>
+	<string>com.microsoft.VSCode</string>
+	<key>CFBundleName</key>
+	<string>Code</string>
+	<key>CFBundleShortVersionString</key>
+	<string>1.92.0</string>
+	<key>CFBundleVersion</key>
+	<string>1.92.0.2024</string>
+	<key>LSApplicationCategoryType</key>
+	<string>public.app-category.developer-tools</string>
+	<key>NSHumanReadableCopyright</key>
+	<string>Tom &amp; Jerry</string>
+	<key>LSUIElement</key>
+	<true/>
+</dict>
+</plist>"#;
+        let mut entry = AppEntry {
+            name: "Code".into(),
+            ..Default::default()
+        };
+        apply_bundle_info(content, &mut entry);
+        assert_eq!(entry.identifier.as_deref(), Some("com.microsoft.VSCode"));
+        assert_eq!(entry.version.as_deref(), Some("1.92.0"));
+        assert_eq!(entry.categories, ["Developer Tools"]);
+        assert_eq!(entry.keywords, ["Visual Studio Code"]);
+        assert_eq!(entry.description, None);
+        assert_eq!(
+            string(content, "NSHumanReadableCopyright").as_deref(),
+            Some("Tom & Jerry")
+        );
+        assert_eq!(string(content, "LSUIElement"), None);
+        assert_eq!(string(content, "CFBundleIconFile"), None);
+    }
+}
@@ -0,0 +1,141 @@
+//! Icons for packaged (UWP/MSIX) apps, read from the package's own logo assets instead
+//! of asking the shell, which returns a generic icon for `WindowsApps` executables.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::path::{Path, PathBuf};
+
+const MANIFEST: &str = "AppxManifest.xml";
+/// Logo attributes in order of preference; the small tile logo is the app-list icon.
+const LOGO_ATTRIBUTES: &[&str] = &["Square44x44Logo", "Square150x150Logo"];
+/// Asset qualifiers closest to the 32px the result list draws.
+const PREFERRED_QUALIFIERS: &[&str] = &["targetsize-32", "scale-100", "targetsize-48"];
+
+/// A `data:` URI for the logo of the package `exe` belongs to, if it is packaged.
+pub fn logo_data_uri(exe: &Path) -> Option<String> {
+    let logo = logo_path(&package_root(exe)?)?;
+    let bytes = std::fs::read(logo).ok()?;
+    Some(format!("data:image/png;base64,{}", STANDARD.encode(bytes)))
+}
+
+/// Package family name and version from a package folder named with the full package
+/// name, `Name_Version_Architecture_ResourceId_PublisherId`.
+pub fn package_identity(folder_name: &str) -> Option<(String, String)> {
+    let parts: Vec<&str> = folder_name.split('_').collect();
+    let [name, version, _, _, publisher] = parts[..] else {
+        return None;
+    };
+    if name.is_empty() || publisher.is_empty() || !version.contains('.') {
+        return None;
+    }
+    Some((format!("{}_{}", name, publisher), version.to_string()))
+}
+
+/// The nearest ancestor directory holding an `AppxManifest.xml`.
+fn package_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join(MANIFEST).is_file())
+        .map(Path::to_path_buf)
+}
+
+fn logo_path(root: &Path) -> Option<PathBuf> {
+    let manifest = std::fs::read_to_string(root.join(MANIFEST)).ok()?;
+    let logo = manifest_logo(&manifest)?;
+    resolve_asset(&root.join(logo.replace('\\', "/")))
+}
+
+fn manifest_logo(manifest: &str) -> Option<String> {
+    LOGO_ATTRIBUTES.iter().find_map(|attribute| {
+        let marker = format!("{}=\"", attribute);
+        let start = manifest.find(&marker)? + marker.len();
+        let end = manifest[start..].find('"')?;
+        Some(manifest[start..start + end].to_string()).filter(|logo| !logo.is_empty())
+    })
+}
+
+/// Manifests name `Assets\Logo.png` but packages ship `Logo.scale-200.png`,
+/// `Logo.targetsize-32.png` and so on; pick the variant nearest the list icon size.
+fn resolve_asset(logo: &Path) -> Option<PathBuf> {
+    if logo.is_file() {
+        return Some(logo.to_path_buf());
+    }
+    let stem = logo.file_stem()?.to_str()?.to_lowercase();
+    let extension = logo.extension()?.to_str()?.to_lowercase();
+    let mut variants: Vec<PathBuf> = std::fs::read_dir(logo.parent()?)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default()
+                .to_lowercase();
+            name.starts_with(&format!("{}.", stem)) && name.ends_with(&format!(".{}", extension))
+        })
+        .collect();
+    variants.sort();
+    let qualifier_rank = |path: &PathBuf| {
+        let name = path.to_string_lossy().to_lowercase();
+        PREFERRED_QUALIFIERS
+            .iter()
+            .position(|qualifier| name.contains(&format!(".{}.", qualifier)))
+            .unwrap_or(PREFERRED_QUALIFIERS.len())
+    };
+    variants.into_iter().min_by_key(qualifier_rank)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_closest_logo_variant() {
+        let root = std::env::temp_dir().join(format!("genie-uwp-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let package = root.join("Microsoft.WindowsTerminal_1.0.0.0_x64__8wekyb3d8bbwe");
+        std::fs::create_dir_all(package.join("Images")).unwrap();
+        std::fs::write(
+            package.join(MANIFEST),
+            r#"<Package><Applications><Application Id="App" Executable="WindowsTerminal.exe">
+<uap:VisualElements DisplayName="Terminal" Square150x150Logo="Images\Square150x150Logo.png"
+  Square44x44Logo="Images\Square44x44Logo.png" /></Application></Applications></Package>"#,
+        )
+        .unwrap();
+        for variant in [
+            "Square44x44Logo.scale-200.png",
+            "Square44x44Logo.targetsize-32.png",
+            "Square44x44Logo.targetsize-256.png",
+            "Square150x150Logo.scale-100.png",
+        ] {
+            std::fs::write(package.join("Images").join(variant), variant).unwrap();
+        }
+        let exe = package.join("WindowsTerminal.exe");
+        std::fs::write(&exe, b"MZ").unwrap();
+
+        assert_eq!(package_root(&exe), Some(package.clone()));
+        assert_eq!(
+            logo_path(&package),
+            Some(package.join("Images/Square44x44Logo.targetsize-32.png"))
+        );
+        let uri = logo_data_uri(&exe).unwrap();
+        assert_eq!(
+            uri,
+            format!(
+                "data:image/png;base64,{}",
+                STANDARD.encode("Square44x44Logo.targetsize-32.png")
+            )
+        );
+        assert_eq!(logo_data_uri(&root.join("plain.exe")), None);
+        assert_eq!(
+            package_identity("Microsoft.WindowsTerminal_1.0.0.0_x64__8wekyb3d8bbwe"),
+            Some((
+                "Microsoft.WindowsTerminal_8wekyb3d8bbwe".to_string(),
+                "1.0.0.0".to_string()
+            ))
+        );
+        assert_eq!(package_identity("Mozilla Firefox"), None);
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -1,7 +1,18 @@
+mod browsers;
+mod calculator;
+mod clipboard;
+mod command_templates;
 mod commands;
+mod contacts;
+mod currency;
 mod file_index;
+mod hotkeys;
 mod indexer;
+mod processes;
 mod settings;
+mod snippets;
+mod system_commands;
+mod window_switcher;
 
 use std::sync::atomic::Ordering;
 use tauri::{
@@ -10,7 +21,7 @@ use tauri::{
     AppHandle, Emitter, Manager, Runtime, WebviewWindow, Window, WindowEvent,
 };
 
-fn show_window<R: Runtime>(window: &WebviewWindow<R>) {
+pub(crate) fn show_window<R: Runtime>(window: &WebviewWindow<R>) {
     let _ = window.unminimize();
     // Center on the monitor where the cursor currently is
     if let Ok(cursor_pos) = window.cursor_position() {
@@ -45,7 +56,7 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
-                .with_handler(move |app: &AppHandle<tauri::Wry>, _shortcut, event| {
+                .with_handler(move |app: &AppHandle<tauri::Wry>, shortcut, event| {
                     if event.state != tauri_plugin_global_shortcut::ShortcutState::Pressed {
                         return;
                     }
@@ -63,45 +74,11 @@ pub fn run() {
                         return;
                     }
                     commands::LAST_TOGGLE.store(now, Ordering::SeqCst);
-
-                    let w: Option<tauri::WebviewWindow<tauri::Wry>> = app.get_webview_window("main");
-                    if let Some(window) = w {
-                        let visible = window.is_visible().unwrap_or(false);
-                        let focused = window.is_focused().unwrap_or(false);
-                        if visible && focused {
-                            let _ = window.hide();
-                        } else {
-                            #[cfg(target_os = "macos")]
-                            {
-                                let _ = window.set_visible_on_all_workspaces(true);
-                                let script = r#"tell application "System Events" to set frontmost of process "Spotlight Genie" to true"#;
-                                let _ = std::process::Command::new("osascript")
-                                    .arg("-e")
-                                    .arg(script)
-                                    .spawn();
-                                let _ = app.show();
-                                let _ = app.set_dock_visibility(false);
-                            }
-                            show_window(&window);
-                            let _ = window.emit("genie:focus", ());
-                            let now2 = std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_millis() as u64;
-                            commands::LAST_SHOW_TIME.store(now2, Ordering::SeqCst);
-                        }
-                    }
+                    hotkeys::handle(app, shortcut);
                 })
                 .build(),
         )
         .setup(|app: &mut tauri::App<tauri::Wry>| {
-            #[cfg(desktop)]
-            {
-                use tauri_plugin_global_shortcut::GlobalShortcutExt;
-                app.global_shortcut()
-                    .register("CommandOrControl+Space")
-                    .expect("failed to register global shortcut");
-            }
             #[cfg(target_os = "macos")]
             {
                 app.set_activation_policy(tauri::ActivationPolicy::Accessory);
@@ -203,7 +180,29 @@ pub fn run() {
             }
 
             settings::init();
+            #[cfg(desktop)]
+            {
+                // NOTE: failures are kept for the settings panel, see `hotkeys::errors`
+                let _ = hotkeys::register_all(app.handle());
+            }
+            currency::init();
+            clipboard::init();
+            snippets::init();
+            browsers::init();
             indexer::init();
+            let handle = app.handle().clone();
+            let hotkey_handle = handle.clone();
+            settings::watch(
+                // NOTE: a hand-edited shortcut that is taken keeps the previous settings
+                move |next| {
+                    hotkeys::apply(&hotkey_handle, next).map(|_| ()).map_err(|conflicts| {
+                        let messages: Vec<_> =
+                            conflicts.into_iter().map(|conflict| conflict.message).collect();
+                        format!("settings.json was not applied: {}", messages.join("; "))
+                    })
+                },
+                move |previous, current| commands::settings_changed(&handle, previous, current),
+            );
             Ok(())
         })
         .on_window_event(|window: &Window<tauri::Wry>, event: &WindowEvent| {
@@ -221,10 +220,10 @@ pub fn run() {
                 let window_clone: Window<tauri::Wry> = window.clone();
                 std::thread::spawn(move || {
                     std::thread::sleep(std::time::Duration::from_millis(150));
-                    if !window_clone.is_focused().unwrap_or(true) {
-                        if !commands::SUPPRESS_HIDE.load(std::sync::atomic::Ordering::SeqCst) {
-                            let _ = window_clone.hide();
-                        }
+                    if !window_clone.is_focused().unwrap_or(true)
+                        && !commands::SUPPRESS_HIDE.load(std::sync::atomic::Ordering::SeqCst)
+                    {
+                        let _ = window_clone.hide();
                     }
                 });
             }
@@ -232,10 +231,14 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             commands::search,
             commands::launch_item,
+            commands::kill_process,
             commands::calculate,
+            commands::commit_calculation,
             commands::run_system_command,
             commands::hide_window,
             commands::get_settings,
+            commands::get_settings_error,
+            commands::get_hotkey_errors,
             commands::save_settings,
             commands::set_suppress_hide,
             commands::set_capturing_shortcut,
@@ -243,6 +246,17 @@ pub fn run() {
             commands::register_global_shortcut,
             commands::get_app_icon,
             commands::get_contacts,
+            commands::get_contact_actions,
+            commands::search_clipboard,
+            commands::paste_clipboard_entry,
+            commands::toggle_clipboard_pin,
+            commands::delete_clipboard_entry,
+            commands::clear_clipboard_history,
+            commands::get_snippets,
+            commands::get_snippets_error,
+            commands::save_snippets,
+            commands::import_snippets,
+            commands::paste_snippet,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
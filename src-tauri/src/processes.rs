@@ -0,0 +1,422 @@
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+
+const MAX_RESULTS: usize = 20;
+const KILL_KEYWORD: &str = "kill";
+
+#[derive(Clone, Debug)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    /// Share of one core used recently; `None` until a second reading exists.
+    pub cpu_percent: Option<f64>,
+    pub memory_bytes: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KillSignal {
+    Term,
+    Kill,
+}
+
+impl KillSignal {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KillSignal::Term => "TERM",
+            KillSignal::Kill => "KILL",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "TERM" => Some(KillSignal::Term),
+            "KILL" => Some(KillSignal::Kill),
+            _ => None,
+        }
+    }
+}
+
+/// Parses `kill <name>` / `kill -9 <name>` queries into the signal and the name filter.
+/// The keyword alone is an ordinary search, so typing "kill" does not list every process.
+pub fn parse_kill_query(query: &str) -> Option<(KillSignal, String)> {
+    let rest = query.trim_start().strip_prefix(KILL_KEYWORD)?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = rest.trim();
+    let (signal, name) = match rest.strip_prefix("-9") {
+        Some(name) if name.is_empty() || name.starts_with(char::is_whitespace) => {
+            (KillSignal::Kill, name.trim())
+        }
+        _ => (KillSignal::Term, rest),
+    };
+    if name.is_empty() {
+        return None;
+    }
+    Some((signal, name.to_string()))
+}
+
+/// Returns running processes fuzzy-matched by name, best matches first.
+pub fn search(filter: &str, matcher: &SkimMatcherV2) -> Vec<(ProcessInfo, i64)> {
+    let mut matches: Vec<(ProcessInfo, i64)> = list_processes()
+        .into_iter()
+        .filter_map(|process| {
+            matcher
+                .fuzzy_match(&process.name, filter)
+                .map(|score| (process, score))
+        })
+        .collect();
+    matches.sort_by(|(a, a_score), (b, b_score)| {
+        b_score.cmp(a_score).then(
+            b.cpu_percent
+                .unwrap_or(0.0)
+                .total_cmp(&a.cpu_percent.unwrap_or(0.0)),
+        )
+    });
+    matches.truncate(MAX_RESULTS);
+    matches
+}
+
+pub fn format_subtitle(process: &ProcessInfo, signal: KillSignal) -> String {
+    let cpu = process
+        .cpu_percent
+        .map(|cpu| format!("CPU {:.1}% · ", cpu))
+        .unwrap_or_default();
+    format!(
+        "PID {} · {}{} · Enter to send SIG{}",
+        process.pid,
+        cpu,
+        format_memory(process.memory_bytes),
+        signal.as_str()
+    )
+}
+
+/// Action data for process results has the form `<SIGNAL>:<pid>`.
+pub fn action_data(process: &ProcessInfo, signal: KillSignal) -> String {
+    format!("{}:{}", signal.as_str(), process.pid)
+}
+
+pub fn parse_action_data(action_data: &str) -> Option<(KillSignal, u32)> {
+    let (signal, pid) = action_data.split_once(':')?;
+    Some((KillSignal::parse(signal)?, pid.parse().ok()?))
+}
+
+fn format_memory(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    let mib = bytes as f64 / MIB;
+    if mib >= 1024.0 {
+        format!("{:.1} GB", mib / 1024.0)
+    } else {
+        format!("{:.1} MB", mib)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{KillSignal, ProcessInfo};
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    // NOTE: USER_HZ is 100 on every mainstream Linux architecture
+    const CLOCK_TICKS_PER_SEC: f64 = 100.0;
+    /// Gap between two CPU readings; shorter ones are dominated by tick rounding.
+    const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+    /// The sampler stops once nobody has listed processes for this long.
+    const MAX_SAMPLE_AGE: Duration = Duration::from_secs(5);
+
+    /// CPU ticks per process keyed by `(pid, start_ticks)`, so a reused pid starts over.
+    struct Sample {
+        taken_at: Instant,
+        ticks: HashMap<(u32, u64), u64>,
+    }
+
+    struct Snapshot {
+        taken_at: Instant,
+        processes: Vec<ProcessInfo>,
+    }
+
+    /// The processes with CPU usage, as of the sampler's latest reading.
+    static SNAPSHOT: Mutex<Option<Snapshot>> = Mutex::new(None);
+    static LAST_REQUEST: Mutex<Option<Instant>> = Mutex::new(None);
+    static SAMPLING: AtomicBool = AtomicBool::new(false);
+
+    struct ProcessReading {
+        pid: u32,
+        fields: StatFields,
+        memory_bytes: u64,
+    }
+
+    /// Never blocks: returns the sampler's latest snapshot, or, until it has one, the
+    /// processes without CPU usage. Listing keeps the background sampler running.
+    pub fn list_processes() -> Vec<ProcessInfo> {
+        if let Ok(mut last_request) = LAST_REQUEST.lock() {
+            *last_request = Some(Instant::now());
+        }
+        start_sampler();
+        let snapshot = SNAPSHOT.lock().ok().and_then(|snapshot| {
+            snapshot
+                .as_ref()
+                .filter(|snapshot| snapshot.taken_at.elapsed() < MAX_SAMPLE_AGE)
+                .map(|snapshot| snapshot.processes.clone())
+        });
+        snapshot.unwrap_or_else(|| {
+            read_processes()
+                .into_iter()
+                .map(|reading| process_info(reading, None))
+                .collect()
+        })
+    }
+
+    fn start_sampler() {
+        if SAMPLING.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        std::thread::spawn(|| loop {
+            let mut previous = sample(&read_processes());
+            while is_wanted() {
+                std::thread::sleep(SAMPLE_INTERVAL);
+                let readings = read_processes();
+                let current = sample(&readings);
+                let elapsed_secs = current
+                    .taken_at
+                    .duration_since(previous.taken_at)
+                    .as_secs_f64();
+                let processes = readings
+                    .into_iter()
+                    .map(|reading| {
+                        let key = (reading.pid, reading.fields.start_ticks);
+                        let cpu_percent = previous.ticks.get(&key).map(|&before| {
+                            recent_cpu_percent(before, reading.fields.cpu_ticks, elapsed_secs)
+                        });
+                        process_info(reading, cpu_percent)
+                    })
+                    .collect();
+                if let Ok(mut snapshot) = SNAPSHOT.lock() {
+                    *snapshot = Some(Snapshot {
+                        taken_at: current.taken_at,
+                        processes,
+                    });
+                }
+                previous = current;
+            }
+            SAMPLING.store(false, Ordering::SeqCst);
+            // NOTE: a listing may have arrived after the last check; keep sampling for it
+            if !is_wanted() || SAMPLING.swap(true, Ordering::SeqCst) {
+                return;
+            }
+        });
+    }
+
+    fn is_wanted() -> bool {
+        LAST_REQUEST
+            .lock()
+            .ok()
+            .and_then(|last_request| *last_request)
+            .is_some_and(|at| at.elapsed() < MAX_SAMPLE_AGE)
+    }
+
+    fn process_info(reading: ProcessReading, cpu_percent: Option<f64>) -> ProcessInfo {
+        ProcessInfo {
+            pid: reading.pid,
+            name: reading.fields.name,
+            cpu_percent,
+            memory_bytes: reading.memory_bytes,
+        }
+    }
+
+    fn sample(readings: &[ProcessReading]) -> Sample {
+        Sample {
+            taken_at: Instant::now(),
+            ticks: readings
+                .iter()
+                .map(|reading| {
+                    (
+                        (reading.pid, reading.fields.start_ticks),
+                        reading.fields.cpu_ticks,
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn read_processes() -> Vec<ProcessReading> {
+        let own_uid = process_uid(Path::new("/proc/self"));
+        let Ok(read_dir) = std::fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+        read_dir
+            .flatten()
+            .filter_map(|entry| {
+                let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+                let dir = entry.path();
+                if own_uid.is_some() && process_uid(&dir) != own_uid {
+                    return None;
+                }
+                let stat = std::fs::read_to_string(dir.join("stat")).ok()?;
+                let fields = parse_stat(&stat)?;
+                let memory_bytes = std::fs::read_to_string(dir.join("status"))
+                    .ok()
+                    .and_then(|status| parse_status_field(&status, "VmRSS:"))
+                    .unwrap_or(0)
+                    * 1024;
+                Some(ProcessReading {
+                    pid,
+                    fields,
+                    memory_bytes,
+                })
+            })
+            .collect()
+    }
+
+    /// Only processes owned by the current user (excluding init and Genie itself) may be signalled.
+    pub fn is_allowed_kill_target(pid: u32) -> bool {
+        if pid <= 1 || pid == std::process::id() {
+            return false;
+        }
+        let dir = Path::new("/proc").join(pid.to_string());
+        if !dir.exists() {
+            return false;
+        }
+        let own_uid = process_uid(Path::new("/proc/self"));
+        own_uid.is_some() && process_uid(&dir) == own_uid
+    }
+
+    pub fn send_signal(pid: u32, signal: KillSignal) -> Result<(), String> {
+        let status = std::process::Command::new("kill")
+            .args(["-s", signal.as_str(), &pid.to_string()])
+            .status()
+            .map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err(format!("Failed to send SIG{} to {}", signal.as_str(), pid));
+        }
+        Ok(())
+    }
+
+    pub(super) struct StatFields {
+        pub name: String,
+        pub cpu_ticks: u64,
+        pub start_ticks: u64,
+    }
+
+    /// Parses `/proc/<pid>/stat`. The command name is wrapped in parentheses and may
+    /// itself contain spaces or parentheses, so fields are read after the last `)`.
+    pub(super) fn parse_stat(stat: &str) -> Option<StatFields> {
+        let open = stat.find('(')?;
+        let close = stat.rfind(')')?;
+        let name = stat.get(open + 1..close)?.to_string();
+        let fields: Vec<&str> = stat.get(close + 1..)?.split_whitespace().collect();
+        // NOTE: indices are offset by 3 from proc(5) numbering (pid, comm, state precede them)
+        let utime: u64 = fields.get(11)?.parse().ok()?;
+        let stime: u64 = fields.get(12)?.parse().ok()?;
+        let start_ticks: u64 = fields.get(19)?.parse().ok()?;
+        Some(StatFields {
+            name,
+            cpu_ticks: utime + stime,
+            start_ticks,
+        })
+    }
+
+    pub(super) fn recent_cpu_percent(
+        before_ticks: u64,
+        after_ticks: u64,
+        elapsed_secs: f64,
+    ) -> f64 {
+        if elapsed_secs <= 0.0 {
+            return 0.0;
+        }
+        (after_ticks.saturating_sub(before_ticks) as f64 / CLOCK_TICKS_PER_SEC) / elapsed_secs
+            * 100.0
+    }
+
+    fn parse_status_field(status: &str, key: &str) -> Option<u64> {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(key))?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    }
+
+    fn process_uid(dir: &Path) -> Option<u32> {
+        let status = std::fs::read_to_string(dir.join("status")).ok()?;
+        parse_status_field(&status, "Uid:").map(|uid| uid as u32)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use super::{KillSignal, ProcessInfo};
+
+    pub fn list_processes() -> Vec<ProcessInfo> {
+        Vec::new()
+    }
+
+    pub fn is_allowed_kill_target(_pid: u32) -> bool {
+        false
+    }
+
+    pub fn send_signal(_pid: u32, _signal: KillSignal) -> Result<(), String> {
+        Err("Process management is currently supported only on Linux".into())
+    }
+}
+
+pub use platform::{is_allowed_kill_target, list_processes, send_signal};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kill_query_requires_keyword_boundary() {
+        assert_eq!(
+            parse_kill_query("kill fire"),
+            Some((KillSignal::Term, "fire".into()))
+        );
+        assert_eq!(
+            parse_kill_query("kill -9 fire"),
+            Some((KillSignal::Kill, "fire".into()))
+        );
+        assert_eq!(parse_kill_query("kill"), None);
+        assert_eq!(parse_kill_query("kill   "), None);
+        assert_eq!(parse_kill_query("kill -9"), None);
+        assert_eq!(parse_kill_query("killall"), None);
+        assert_eq!(parse_kill_query("skill"), None);
+    }
+
+    #[test]
+    fn action_data_round_trips() {
+        let process = ProcessInfo {
+            pid: 4242,
+            name: "firefox".into(),
+            cpu_percent: Some(1.5),
+            memory_bytes: 300 * 1024 * 1024,
+        };
+        let data = action_data(&process, KillSignal::Kill);
+        assert_eq!(data, "KILL:4242");
+        assert_eq!(parse_action_data(&data), Some((KillSignal::Kill, 4242)));
+        assert_eq!(parse_action_data("HUP:4242"), None);
+        assert!(format_subtitle(&process, KillSignal::Term).contains("CPU 1.5% · 300.0 MB"));
+        let unsampled = ProcessInfo {
+            cpu_percent: None,
+            ..process
+        };
+        assert!(!format_subtitle(&unsampled, KillSignal::Term).contains("CPU"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn stat_parsing_handles_parenthesised_names() {
+        let stat = "1234 (Web Content (x)) S 1 1234 1234 0 -1 4194560 100 0 0 0 250 50 0 0 20 0 30 0 1000 123456 789";
+        let fields = platform::parse_stat(stat).expect("stat should parse");
+        assert_eq!(fields.name, "Web Content (x)");
+        assert_eq!(fields.cpu_ticks, 300);
+        assert_eq!(fields.start_ticks, 1000);
+        let cpu = platform::recent_cpu_percent(fields.cpu_ticks, fields.cpu_ticks + 25, 0.5);
+        assert!((cpu - 50.0).abs() < 1e-9, "unexpected cpu {cpu}");
+        assert_eq!(platform::recent_cpu_percent(300, 300, 0.0), 0.0);
+    }
+}
@@ -1,31 +1,216 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
+    /// Schema version of the saved file; see `MIGRATIONS`.
+    #[serde(default = "current_version")]
+    pub version: u32,
+    /// Toggles the launcher.
     pub hotkey: String,
+    /// More global shortcuts, each bound to its own action.
+    #[serde(default)]
+    pub hotkeys: Vec<HotkeyBinding>,
     pub max_results: usize,
     pub launch_at_login: bool,
     pub theme: String,
     pub show_recent_apps: bool,
     #[serde(default = "default_search_folders")]
     pub search_folders: Vec<String>,
+    /// Scanned for apps in addition to the platform's own application folders.
+    #[serde(default)]
+    pub extra_app_dirs: Vec<String>,
+    /// Apps left out of the index, by name or path; `*` and `?` wildcards are allowed.
+    #[serde(default)]
+    pub excluded_apps: Vec<String>,
+    /// AppImage files in this folder are listed as apps (Linux only; empty disables).
+    #[serde(default = "default_appimage_dir")]
+    pub appimage_dir: String,
     #[serde(default)]
     pub disabled_plugins: Vec<String>,
+    #[serde(default)]
+    pub custom_commands: Vec<CustomCommand>,
+    #[serde(default)]
+    pub command_templates: Vec<CommandTemplate>,
+    #[serde(default)]
+    pub currency: CurrencySettings,
+    #[serde(default)]
+    pub clipboard: ClipboardSettings,
+    #[serde(default)]
+    pub browser_history: BrowserHistorySettings,
+    #[serde(default)]
+    pub contacts: ContactsSettings,
+}
+
+/// A global shortcut such as `Alt+Shift+V` and what pressing it does.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HotkeyBinding {
+    pub shortcut: String,
+    pub action: HotkeyAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HotkeyAction {
+    OpenLauncher,
+    /// Opens the launcher with the query filled in, e.g. `cb ` for clipboard history.
+    OpenWithQuery {
+        query: String,
+    },
+    /// Runs a system or custom command by id.
+    RunCommand {
+        command: String,
+    },
+    LaunchApp {
+        path: String,
+    },
+}
+
+/// A user-defined shell command listed alongside the built-in system commands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomCommand {
+    pub id: String,
+    pub title: String,
+    pub command: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub requires_confirmation: bool,
+}
+
+/// A keyword-triggered command such as `ssh {host}`; placeholders are filled from the query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandTemplate {
+    pub keyword: String,
+    #[serde(default)]
+    pub title: String,
+    pub template: String,
+    #[serde(default)]
+    pub run_in_terminal: bool,
+}
+
+/// Where exchange rates come from and how long a downloaded copy stays fresh.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurrencySettings {
+    /// An `https://` JSON endpoint, or a path to a local rates file for air-gapped machines.
+    pub rates_source: String,
+    pub refresh_hours: u64,
+}
+
+impl Default for CurrencySettings {
+    fn default() -> Self {
+        Self {
+            rates_source: "https://api.exchangerate-api.com/v4/latest/USD".into(),
+            refresh_hours: 12,
+        }
+    }
+}
+
+/// Clipboard history recording; pinned entries ignore both limits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardSettings {
+    pub enabled: bool,
+    pub max_entries: usize,
+    pub retention_days: u64,
+    /// Copies made while one of these apps is frontmost are never recorded (case-insensitive).
+    pub excluded_apps: Vec<String>,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: 200,
+            retention_days: 30,
+            excluded_apps: vec![
+                "1Password".into(),
+                "Bitwarden".into(),
+                "KeePassXC".into(),
+                "Keychain Access".into(),
+            ],
+        }
+    }
+}
+
+/// Searching visited pages is opt-in because history is more private than bookmarks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrowserHistorySettings {
+    pub enabled: bool,
+    /// Pages not visited within this many days are left out.
+    pub max_age_days: u64,
+}
+
+impl Default for BrowserHistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_age_days: 90,
+        }
+    }
+}
+
+/// vCard files or directories to read contacts from (vdirsyncer storage, Evolution
+/// address books, exported `.vcf` files).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContactsSettings {
+    pub sources: Vec<String>,
+}
+
+impl Default for ContactsSettings {
+    fn default() -> Self {
+        Self {
+            sources: default_contact_sources(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn default_contact_sources() -> Vec<String> {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
+    let data = dirs::data_dir().unwrap_or_else(|| home.join(".local/share"));
+    [
+        home.join(".contacts"),
+        data.join("vdirsyncer"),
+        data.join("evolution/addressbook"),
+    ]
+    .iter()
+    .map(|path| path.to_string_lossy().to_string())
+    .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn default_contact_sources() -> Vec<String> {
+    Vec::new()
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
             hotkey: "CommandOrControl+Space".into(),
+            hotkeys: Vec::new(),
             max_results: 8,
             launch_at_login: false,
             theme: "dark".into(),
             show_recent_apps: true,
             search_folders: default_search_folders(),
+            extra_app_dirs: Vec::new(),
+            excluded_apps: Vec::new(),
+            appimage_dir: default_appimage_dir(),
             disabled_plugins: Vec::new(),
+            custom_commands: Vec::new(),
+            command_templates: Vec::new(),
+            currency: CurrencySettings::default(),
+            clipboard: ClipboardSettings::default(),
+            browser_history: BrowserHistorySettings::default(),
+            contacts: ContactsSettings::default(),
         }
     }
 }
@@ -39,23 +224,64 @@ fn default_search_folders() -> Vec<String> {
     ]
 }
 
+#[cfg(target_os = "linux")]
+fn default_appimage_dir() -> String {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("/"))
+        .join("Applications")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn default_appimage_dir() -> String {
+    String::new()
+}
+
+fn current_version() -> u32 {
+    SETTINGS_VERSION
+}
+
+/// Bump together with a new entry in `MIGRATIONS` whenever a saved field is renamed,
+/// reshaped or given a new meaning. Added fields with a serde default need neither.
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` turns a version `n` file into version `n + 1`; they run in order.
+type Migration = fn(&mut serde_json::Map<String, serde_json::Value>);
+const MIGRATIONS: &[Migration] = &[
+    // NOTE: files from before versioning already have the version 1 layout
+    |_| {},
+];
+
 static SETTINGS: std::sync::OnceLock<Mutex<AppSettings>> = std::sync::OnceLock::new();
+/// Why `settings.json` could not be loaded; defaults are in use until the next save.
+static LOAD_ERROR: Mutex<Option<String>> = Mutex::new(None);
+/// What this process last wrote, so the watcher can tell its own saves from edits.
+static LAST_WRITTEN: Mutex<Option<String>> = Mutex::new(None);
+
+const SETTINGS_FILE: &str = "settings.json";
+/// Editors save in several steps (truncate, write, rename); wait for them to finish.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
 
 fn settings_path() -> PathBuf {
     let dir = dirs::config_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("spotlight-genie");
     let _ = fs::create_dir_all(&dir);
-    dir.join("settings.json")
+    dir.join(SETTINGS_FILE)
 }
 
+/// Loads `settings.json`, migrating older versions after backing the file up. A file
+/// that cannot be read is left alone and the error is kept for the settings panel.
 pub fn init() {
     let path = settings_path();
     let settings = if path.exists() {
-        fs::read_to_string(&path)
-            .ok()
-            .and_then(|s| serde_json::from_str(&s).ok())
-            .unwrap_or_default()
+        load(&path).unwrap_or_else(|error| {
+            if let Ok(mut load_error) = LOAD_ERROR.lock() {
+                *load_error = Some(error);
+            }
+            AppSettings::default()
+        })
     } else {
         AppSettings::default()
     };
@@ -69,12 +295,276 @@ pub fn get() -> AppSettings {
         .unwrap_or_default()
 }
 
-pub fn save(settings: AppSettings) -> Result<(), String> {
+/// The error that made `init` fall back to defaults, if any.
+pub fn load_error() -> Option<String> {
+    LOAD_ERROR.lock().ok().and_then(|error| error.clone())
+}
+
+pub fn save(mut settings: AppSettings) -> Result<(), String> {
     let path = settings_path();
+    // NOTE: keep a copy of a file we could not read instead of overwriting it
+    if load_error().is_some() && path.exists() {
+        fs::copy(&path, backup_path(&path, "invalid")).map_err(|e| e.to_string())?;
+    }
+    settings.version = SETTINGS_VERSION;
     let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    fs::write(&path, json).map_err(|e| e.to_string())?;
+    write_settings(&path, &json)?;
+    if let Ok(mut load_error) = LOAD_ERROR.lock() {
+        *load_error = None;
+    }
     if let Some(m) = SETTINGS.get() {
         *m.lock().unwrap() = settings;
     }
     Ok(())
 }
+
+/// Writes through a temporary file so a crash mid-write cannot leave half a file behind.
+/// Each write gets its own temporary file, so overlapping writes cannot mix.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    static WRITES: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let write = WRITES.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(".{}-{}.tmp", std::process::id(), write));
+    let written = fs::File::create(&temp)
+        .and_then(|mut file| {
+            std::io::Write::write_all(&mut file, contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written.map_err(|e| e.to_string())
+}
+
+fn write_settings(path: &Path, json: &str) -> Result<(), String> {
+    write_atomic(path, json.as_bytes())?;
+    if let Ok(mut last_written) = LAST_WRITTEN.lock() {
+        *last_written = Some(json.to_string());
+    }
+    Ok(())
+}
+
+/// Watches `settings.json` for edits made outside the app. A valid edit is first passed
+/// to `accept`; once accepted it replaces the current settings and
+/// `on_change(previous, current)` is called. Invalid or refused edits are kept as the
+/// load error and the current settings stay in effect.
+pub fn watch(
+    accept: impl Fn(&AppSettings) -> Result<(), String> + Send + 'static,
+    on_change: impl Fn(&AppSettings, &AppSettings) + Send + 'static,
+) {
+    let path = settings_path();
+    std::thread::spawn(move || {
+        let (sender, receiver) = mpsc::channel();
+        let watched = move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let is_settings_file = event
+                .paths
+                .iter()
+                .any(|path| path.file_name().is_some_and(|name| name == SETTINGS_FILE));
+            if is_settings_file && !event.kind.is_access() {
+                let _ = sender.send(());
+            }
+        };
+        let Ok(mut watcher) = notify::recommended_watcher(watched) else {
+            return;
+        };
+        // NOTE: the directory is watched because editors replace the file by renaming
+        let Some(dir) = path.parent() else {
+            return;
+        };
+        if notify::Watcher::watch(&mut watcher, dir, notify::RecursiveMode::NonRecursive).is_err() {
+            return;
+        }
+        while receiver.recv().is_ok() {
+            while receiver.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+            reload_external(&path, &accept, &on_change);
+        }
+    });
+}
+
+fn reload_external(
+    path: &Path,
+    accept: &impl Fn(&AppSettings) -> Result<(), String>,
+    on_change: &impl Fn(&AppSettings, &AppSettings),
+) {
+    // NOTE: a deleted file keeps the current settings; the next save recreates it
+    let Ok(text) = fs::read_to_string(path) else {
+        return;
+    };
+    if LAST_WRITTEN
+        .lock()
+        .is_ok_and(|last| last.as_deref() == Some(text.as_str()))
+    {
+        return;
+    }
+    match load(path).and_then(|settings| accept(&settings).map(|()| settings)) {
+        Ok(settings) => {
+            if let Ok(mut last_written) = LAST_WRITTEN.lock() {
+                *last_written = fs::read_to_string(path).ok();
+            }
+            if let Ok(mut load_error) = LOAD_ERROR.lock() {
+                *load_error = None;
+            }
+            let previous = get();
+            if let Some(m) = SETTINGS.get() {
+                *m.lock().unwrap() = settings.clone();
+            }
+            on_change(&previous, &settings);
+        }
+        Err(error) => {
+            if let Ok(mut load_error) = LOAD_ERROR.lock() {
+                *load_error = Some(error);
+            }
+        }
+    }
+}
+
+fn load(path: &Path) -> Result<AppSettings, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Could not read settings: {}", e))?;
+    let value: serde_json::Value = serde_json::from_str(&text)
+        .map_err(|e| format!("settings.json is not valid JSON: {}", e))?;
+    let version = file_version(&value)?;
+    let value = if version < SETTINGS_VERSION {
+        fs::copy(path, backup_path(path, &format!("v{}", version)))
+            .map_err(|e| format!("Could not back up settings before migrating: {}", e))?;
+        migrate(value, version)?
+    } else {
+        value
+    };
+    let settings: AppSettings = serde_json::from_value(value)
+        .map_err(|e| format!("settings.json has an invalid value: {}", e))?;
+    if version < SETTINGS_VERSION {
+        let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        write_settings(path, &json)?;
+    }
+    Ok(settings)
+}
+
+/// Files from before versioning have no `version` and count as version 0.
+fn file_version(value: &serde_json::Value) -> Result<u32, String> {
+    let version = match value.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| format!("settings.json has an invalid version: {}", version))?,
+    };
+    if version > SETTINGS_VERSION {
+        return Err(format!(
+            "settings.json is version {}, newer than this app supports ({}); it was left unchanged",
+            version, SETTINGS_VERSION
+        ));
+    }
+    Ok(version)
+}
+
+fn migrate(mut value: serde_json::Value, from: u32) -> Result<serde_json::Value, String> {
+    let settings = value
+        .as_object_mut()
+        .ok_or("settings.json does not contain an object")?;
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        migration(settings);
+        settings.insert("version".into(), (version as u32 + 1).into());
+    }
+    Ok(value)
+}
+
+/// `settings.json` becomes `settings.<label>.bak.json` next to it.
+fn backup_path(path: &Path, label: &str) -> PathBuf {
+    path.with_extension(format!("{}.bak.json", label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_reach_the_current_version() {
+        assert_eq!(MIGRATIONS.len(), SETTINGS_VERSION as usize);
+        let legacy = serde_json::json!({
+            "hotkey": "Alt+Space",
+            "max_results": 5,
+            "launch_at_login": false,
+            "theme": "light",
+            "show_recent_apps": true
+        });
+        assert_eq!(file_version(&legacy), Ok(0));
+        let migrated = migrate(legacy, 0).unwrap();
+        assert_eq!(migrated["version"], SETTINGS_VERSION);
+        let settings: AppSettings = serde_json::from_value(migrated).unwrap();
+        assert_eq!(settings.hotkey, "Alt+Space");
+        assert_eq!(settings.max_results, 5);
+
+        let future = serde_json::json!({ "version": SETTINGS_VERSION + 1 });
+        assert!(file_version(&future).unwrap_err().contains("newer"));
+        assert!(file_version(&serde_json::json!({ "version": "one" })).is_err());
+    }
+
+    #[test]
+    fn legacy_files_are_backed_up_and_rewritten() {
+        let dir = std::env::temp_dir().join(format!("genie-settings-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        let legacy = r#"{"hotkey":"Alt+Space","max_results":5,"launch_at_login":false,"theme":"dark","show_recent_apps":true}"#;
+        fs::write(&path, legacy).unwrap();
+
+        let settings = load(&path).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.hotkey, "Alt+Space");
+        assert_eq!(
+            fs::read_to_string(dir.join("settings.v0.bak.json")).unwrap(),
+            legacy
+        );
+        let rewritten: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(rewritten["version"], SETTINGS_VERSION);
+
+        let leftovers: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "tmp"))
+            .collect();
+        assert!(leftovers.is_empty(), "{:?}", leftovers);
+
+        fs::write(&path, r#"{"hotkey": 42}"#).unwrap();
+        let error = load(&path).unwrap_err();
+        assert!(error.contains("invalid value"), "{}", error);
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"hotkey": 42}"#);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn refused_edits_keep_the_current_settings() {
+        let dir = std::env::temp_dir().join(format!("genie-settings-edit-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        let edited = AppSettings {
+            hotkey: "Alt+V".into(),
+            ..AppSettings::default()
+        };
+        fs::write(&path, serde_json::to_string(&edited).unwrap()).unwrap();
+
+        reload_external(
+            &path,
+            &|_| Err("Alt+V could not be registered".into()),
+            &|_, _| panic!("a refused edit must not be applied"),
+        );
+        assert_eq!(
+            load_error().as_deref(),
+            Some("Alt+V could not be registered")
+        );
+
+        let applied = std::cell::RefCell::new(None);
+        reload_external(&path, &|_| Ok(()), &|_, current| {
+            *applied.borrow_mut() = Some(current.hotkey.clone());
+        });
+        assert_eq!(applied.into_inner().as_deref(), Some("Alt+V"));
+        assert_eq!(load_error(), None);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,414 @@
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+const SNIPPETS_FILE: &str = "snippets.json";
+const SNIPPET_KEYWORD: &str = "snip";
+const MAX_RESULTS: usize = 20;
+const CURSOR_PLACEHOLDER: &str = "cursor";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub name: String,
+    /// Short abbreviation such as `sig`; `snip sig` lists this snippet first.
+    #[serde(default)]
+    pub keyword: String,
+    pub text: String,
+    /// File name of the team file an imported snippet came from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Team files are either a bare list or a `snippets` table, the only shape TOML allows.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SnippetFile {
+    List(Vec<Snippet>),
+    Table { snippets: Vec<Snippet> },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+}
+
+/// Expanded snippet text ready to paste.
+#[derive(Debug, PartialEq)]
+pub struct Expansion {
+    pub text: String,
+    /// Characters between `{cursor}` and the end of `text`; the caret is moved back this far.
+    pub cursor_back: usize,
+}
+
+static SNIPPETS: OnceLock<Mutex<Vec<Snippet>>> = OnceLock::new();
+/// Why `snippets.json` could not be loaded; the list is empty until the next save.
+static LOAD_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+fn snippets_path() -> PathBuf {
+    let dir = dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("spotlight-genie");
+    let _ = std::fs::create_dir_all(&dir);
+    dir.join(SNIPPETS_FILE)
+}
+
+/// A file that cannot be read is left alone and the error is kept for the settings panel.
+pub fn init() {
+    let path = snippets_path();
+    let snippets = if path.exists() {
+        load(&path).unwrap_or_else(|error| {
+            if let Ok(mut load_error) = LOAD_ERROR.lock() {
+                *load_error = Some(error);
+            }
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+    let _ = SNIPPETS.set(Mutex::new(snippets));
+}
+
+fn load(path: &Path) -> Result<Vec<Snippet>, String> {
+    let json =
+        std::fs::read_to_string(path).map_err(|e| format!("Could not read snippets: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("snippets.json is not valid: {}", e))
+}
+
+/// The error that made `init` start with no snippets, if any.
+pub fn load_error() -> Option<String> {
+    LOAD_ERROR.lock().ok().and_then(|error| error.clone())
+}
+
+pub fn all() -> Vec<Snippet> {
+    SNIPPETS
+        .get()
+        .and_then(|snippets| snippets.lock().ok().map(|snippets| snippets.clone()))
+        .unwrap_or_default()
+}
+
+/// Replaces every snippet; names must be unique and non-empty.
+pub fn save(snippets: Vec<Snippet>) -> Result<(), String> {
+    validate(&snippets)?;
+    let path = snippets_path();
+    // NOTE: keep a copy of a file we could not read instead of overwriting it
+    if load_error().is_some() && path.exists() {
+        std::fs::copy(&path, path.with_extension("invalid.bak.json")).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&snippets).map_err(|e| e.to_string())?;
+    crate::settings::write_atomic(&path, json.as_bytes())?;
+    if let Ok(mut load_error) = LOAD_ERROR.lock() {
+        *load_error = None;
+    }
+    if let Some(stored) = SNIPPETS.get() {
+        if let Ok(mut stored) = stored.lock() {
+            *stored = snippets;
+        }
+    }
+    Ok(())
+}
+
+/// Merges a shared JSON or TOML snippet file; snippets with the same name are replaced.
+pub fn import(path: &str) -> Result<ImportSummary, String> {
+    let path = Path::new(path);
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let is_toml = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+    let source = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string());
+    let imported = parse_file(&content, is_toml, source)?;
+    let mut snippets = all();
+    let summary = merge(&mut snippets, imported);
+    save(snippets)?;
+    Ok(summary)
+}
+
+/// Parses `snip <filter>` queries; `snip` alone lists every snippet.
+pub fn parse_query(query: &str) -> Option<&str> {
+    let rest = query.trim_start().strip_prefix(SNIPPET_KEYWORD)?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim())
+}
+
+/// Snippets whose keyword, name or text match `filter`, best matches first.
+pub fn search(filter: &str, matcher: &SkimMatcherV2) -> Vec<(Snippet, i64)> {
+    search_in(all(), filter, matcher)
+}
+
+/// Expands the named snippet's placeholders against the current time and clipboard.
+pub fn expand(name: &str) -> Result<Expansion, String> {
+    let snippet = all()
+        .into_iter()
+        .find(|snippet| snippet.name == name)
+        .ok_or_else(|| format!("Unknown snippet: {}", name))?;
+    Ok(expand_template(
+        &snippet.text,
+        &jiff::Zoned::now(),
+        crate::clipboard::current_text,
+    ))
+}
+
+fn validate(snippets: &[Snippet]) -> Result<(), String> {
+    for (index, snippet) in snippets.iter().enumerate() {
+        if snippet.name.trim().is_empty() {
+            return Err("Snippet names cannot be empty".into());
+        }
+        if snippets[..index]
+            .iter()
+            .any(|other| other.name == snippet.name)
+        {
+            return Err(format!("Duplicate snippet name: {}", snippet.name));
+        }
+    }
+    Ok(())
+}
+
+fn parse_file(
+    content: &str,
+    is_toml: bool,
+    source: Option<String>,
+) -> Result<Vec<Snippet>, String> {
+    let file: SnippetFile = if is_toml {
+        toml::from_str(content).map_err(|e| format!("Invalid snippet file: {}", e))?
+    } else {
+        serde_json::from_str(content).map_err(|e| format!("Invalid snippet file: {}", e))?
+    };
+    let snippets = match file {
+        SnippetFile::List(snippets) | SnippetFile::Table { snippets } => snippets,
+    };
+    validate(&snippets)?;
+    Ok(snippets
+        .into_iter()
+        .map(|snippet| Snippet {
+            source: source.clone(),
+            ..snippet
+        })
+        .collect())
+}
+
+fn merge(snippets: &mut Vec<Snippet>, imported: Vec<Snippet>) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    for snippet in imported {
+        match snippets
+            .iter_mut()
+            .find(|existing| existing.name == snippet.name)
+        {
+            Some(existing) => {
+                *existing = snippet;
+                summary.updated += 1;
+            }
+            None => {
+                snippets.push(snippet);
+                summary.added += 1;
+            }
+        }
+    }
+    summary
+}
+
+fn search_in(snippets: Vec<Snippet>, filter: &str, matcher: &SkimMatcherV2) -> Vec<(Snippet, i64)> {
+    let filter = filter.to_lowercase();
+    let mut matches: Vec<(Snippet, i64)> = snippets
+        .into_iter()
+        .filter_map(|snippet| {
+            if filter.is_empty() {
+                return Some((snippet, 0));
+            }
+            if snippet.keyword.eq_ignore_ascii_case(&filter) {
+                return Some((snippet, 10_000));
+            }
+            let in_text = || snippet.text.to_lowercase().contains(&filter).then_some(0);
+            let score = matcher
+                .fuzzy_match(&snippet.name, &filter)
+                .or_else(|| matcher.fuzzy_match(&snippet.keyword, &filter))
+                .or_else(in_text)?;
+            Some((snippet, score))
+        })
+        .collect();
+    matches.sort_by(|(a, a_score), (b, b_score)| b_score.cmp(a_score).then(a.name.cmp(&b.name)));
+    matches.truncate(MAX_RESULTS);
+    matches
+}
+
+/// Replaces `{date}`, `{time}`, `{datetime}`, `{date:<strftime>}`, `{clipboard}` and
+/// `{cursor}` in one left-to-right pass; unknown placeholders are kept verbatim.
+fn expand_template(
+    template: &str,
+    now: &jiff::Zoned,
+    clipboard: impl FnOnce() -> Option<String>,
+) -> Expansion {
+    let mut clipboard = Some(clipboard);
+    let mut clipboard_text: Option<String> = None;
+    let mut text = String::new();
+    let mut cursor: Option<usize> = None;
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        text.push_str(&rest[..start]);
+        let name = &rest[start + 1..start + len];
+        let value = match name {
+            "date" => strftime(now, "%Y-%m-%d"),
+            "time" => strftime(now, "%H:%M"),
+            "datetime" => strftime(now, "%Y-%m-%d %H:%M"),
+            "clipboard" => {
+                if let Some(read) = clipboard.take() {
+                    clipboard_text = Some(read().unwrap_or_default());
+                }
+                clipboard_text.clone()
+            }
+            CURSOR_PLACEHOLDER => {
+                cursor.get_or_insert(text.chars().count());
+                Some(String::new())
+            }
+            _ => name
+                .strip_prefix("date:")
+                .and_then(|format| strftime(now, format)),
+        };
+        match value {
+            Some(value) => text.push_str(&value),
+            None => text.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    text.push_str(rest);
+    let cursor_back = cursor.map_or(0, |cursor| text.chars().count() - cursor);
+    Expansion { text, cursor_back }
+}
+
+fn strftime(now: &jiff::Zoned, format: &str) -> Option<String> {
+    jiff::fmt::strtime::format(format, now).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(name: &str, keyword: &str, text: &str) -> Snippet {
+        Snippet {
+            name: name.into(),
+            keyword: keyword.into(),
+            text: text.into(),
+            source: None,
+        }
+    }
+
+    fn now() -> jiff::Zoned {
+        "2026-10-18T14:30:00[Europe/Berlin]".parse().unwrap()
+    }
+
+    #[test]
+    fn expands_placeholders_in_one_pass() {
+        let expansion = expand_template(
+            "On {date} at {time}: {clipboard} ({date:%d.%m.%Y}) {unknown}",
+            &now(),
+            || Some("{date}".into()),
+        );
+        assert_eq!(
+            expansion.text,
+            "On 2026-10-18 at 14:30: {date} (18.10.2026) {unknown}"
+        );
+        assert_eq!(expansion.cursor_back, 0);
+    }
+
+    #[test]
+    fn cursor_marks_the_caret_position() {
+        let expansion = expand_template("<b>{cursor}</b>", &now(), || None);
+        assert_eq!(expansion.text, "<b></b>");
+        assert_eq!(expansion.cursor_back, 4);
+        let unterminated = expand_template("Hi {name", &now(), || None);
+        assert_eq!(unterminated.text, "Hi {name");
+    }
+
+    #[test]
+    fn clipboard_is_read_at_most_once() {
+        let mut reads = 0;
+        let expansion = expand_template("{clipboard}-{clipboard}", &now(), || {
+            reads += 1;
+            Some("x".into())
+        });
+        assert_eq!(expansion.text, "x-x");
+        assert_eq!(reads, 1);
+    }
+
+    #[test]
+    fn imports_json_lists_and_toml_tables() {
+        let json = r#"[{"name":"Signature","keyword":"sig","text":"Best,\nTeam"}]"#;
+        let imported = parse_file(json, false, Some("team.json".into())).unwrap();
+        assert_eq!(imported[0].keyword, "sig");
+        assert_eq!(imported[0].source.as_deref(), Some("team.json"));
+
+        let toml = "[[snippets]]\nname = \"Greeting\"\ntext = \"Hello {cursor}\"\n";
+        let imported = parse_file(toml, true, None).unwrap();
+        assert_eq!(imported, vec![snippet("Greeting", "", "Hello {cursor}")]);
+
+        let duplicate = r#"{"snippets":[{"name":"a","text":"1"},{"name":"a","text":"2"}]}"#;
+        assert!(parse_file(duplicate, false, None).is_err());
+        assert!(parse_file("name = 1", true, None).is_err());
+    }
+
+    #[test]
+    fn malformed_files_report_an_error() {
+        let dir = std::env::temp_dir().join(format!("genie-snippets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(SNIPPETS_FILE);
+        std::fs::write(&path, r#"[{"name": "sig", "text": "Cheers"}"#).unwrap();
+        assert!(load(&path)
+            .unwrap_err()
+            .starts_with("snippets.json is not valid"));
+        std::fs::write(&path, r#"[{"name": "sig", "text": "Cheers"}]"#).unwrap();
+        assert_eq!(load(&path).unwrap()[0].text, "Cheers");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn merging_replaces_snippets_by_name() {
+        let mut snippets = vec![snippet("Signature", "sig", "old")];
+        let summary = merge(
+            &mut snippets,
+            vec![
+                snippet("Signature", "sig", "new"),
+                snippet("Address", "", "Main St"),
+            ],
+        );
+        assert_eq!(
+            summary,
+            ImportSummary {
+                added: 1,
+                updated: 1
+            }
+        );
+        assert_eq!(snippets[0].text, "new");
+        assert_eq!(snippets.len(), 2);
+    }
+
+    #[test]
+    fn search_prefers_exact_keywords() {
+        let matcher = SkimMatcherV2::default();
+        let snippets = vec![
+            snippet("Signature", "sig", "Best"),
+            snippet("Sign-off", "so", "Cheers"),
+            snippet("Address", "addr", "Main St"),
+        ];
+        let names = |filter: &str| -> Vec<String> {
+            search_in(snippets.clone(), filter, &matcher)
+                .into_iter()
+                .map(|(snippet, _)| snippet.name)
+                .collect()
+        };
+        assert_eq!(names("sig")[0], "Signature");
+        assert_eq!(names("main"), vec!["Address"]);
+        assert_eq!(names("").len(), 3);
+        assert_eq!(parse_query("snip  sig "), Some("sig"));
+        assert_eq!(parse_query("snip"), Some(""));
+        assert_eq!(parse_query("snippets"), None);
+    }
+}
@@ -0,0 +1,480 @@
+use crate::settings::{self, CustomCommand};
+use serde::Serialize;
+
+type Runner = fn() -> Result<(), String>;
+
+#[derive(Clone, Debug)]
+pub struct SystemCommand {
+    pub id: String,
+    pub title: String,
+    pub subtitle: String,
+    pub icon: String,
+    pub keywords: Vec<String>,
+    pub requires_confirmation: bool,
+    pub searchable: bool,
+    action: CommandAction,
+}
+
+#[derive(Clone, Debug)]
+enum CommandAction {
+    /// Handled entirely by the frontend (e.g. opening the settings panel).
+    Frontend,
+    Builtin(Runner),
+    Shell(String),
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RunOutcome {
+    Done,
+    NeedsConfirmation { message: String },
+}
+
+struct BuiltinSpec {
+    id: &'static str,
+    title: &'static str,
+    subtitle: &'static str,
+    icon: &'static str,
+    keywords: &'static [&'static str],
+    requires_confirmation: bool,
+    searchable: bool,
+}
+
+const BUILTINS: &[BuiltinSpec] = &[
+    BuiltinSpec {
+        id: "sleep",
+        title: "Sleep Device",
+        subtitle: "System command",
+        icon: "moon",
+        keywords: &["sleep", "suspend"],
+        requires_confirmation: false,
+        searchable: true,
+    },
+    BuiltinSpec {
+        id: "lock",
+        title: "Lock Screen",
+        subtitle: "System command",
+        icon: "lock",
+        keywords: &["lock"],
+        requires_confirmation: false,
+        searchable: true,
+    },
+    BuiltinSpec {
+        id: "shutdown",
+        title: "Shut Down",
+        subtitle: "System command",
+        icon: "power",
+        keywords: &["shutdown", "shut down", "power off", "poweroff", "turn off"],
+        requires_confirmation: true,
+        searchable: true,
+    },
+    BuiltinSpec {
+        id: "restart",
+        title: "Restart",
+        subtitle: "System command",
+        icon: "rotate-cw",
+        keywords: &["restart", "reboot"],
+        requires_confirmation: true,
+        searchable: true,
+    },
+    BuiltinSpec {
+        id: "logout",
+        title: "Log Out",
+        subtitle: "System command",
+        icon: "log-out",
+        keywords: &["logout", "log out", "sign out", "signout"],
+        requires_confirmation: true,
+        searchable: true,
+    },
+    BuiltinSpec {
+        id: "empty_trash",
+        title: "Empty Trash",
+        subtitle: "System command",
+        icon: "trash-2",
+        keywords: &["trash", "recycle bin"],
+        requires_confirmation: true,
+        searchable: true,
+    },
+    BuiltinSpec {
+        id: "toggle_dark_mode",
+        title: "Toggle Dark Mode",
+        subtitle: "System appearance",
+        icon: "sun-moon",
+        keywords: &["dark mode", "light mode", "appearance"],
+        requires_confirmation: false,
+        searchable: true,
+    },
+    BuiltinSpec {
+        id: "eject",
+        title: "Eject All Volumes",
+        subtitle: "System command",
+        icon: "hard-drive",
+        keywords: &["eject", "unmount"],
+        requires_confirmation: false,
+        searchable: true,
+    },
+    BuiltinSpec {
+        id: "spotify_play",
+        title: "Spotify Play",
+        subtitle: "Spotify control",
+        icon: "music",
+        keywords: &[],
+        requires_confirmation: false,
+        searchable: false,
+    },
+    BuiltinSpec {
+        id: "spotify_pause",
+        title: "Spotify Pause",
+        subtitle: "Spotify control",
+        icon: "music",
+        keywords: &[],
+        requires_confirmation: false,
+        searchable: false,
+    },
+    BuiltinSpec {
+        id: "spotify_next",
+        title: "Spotify Next Track",
+        subtitle: "Spotify control",
+        icon: "music",
+        keywords: &[],
+        requires_confirmation: false,
+        searchable: false,
+    },
+    BuiltinSpec {
+        id: "spotify_prev",
+        title: "Spotify Previous Track",
+        subtitle: "Spotify control",
+        icon: "music",
+        keywords: &[],
+        requires_confirmation: false,
+        searchable: false,
+    },
+];
+
+const CUSTOM_PREFIX: &str = "custom:";
+
+impl SystemCommand {
+    /// True when the query mentions one of the command's keywords, signalling clear intent.
+    pub fn matches_keyword(&self, normalized_query: &str) -> bool {
+        self.keywords
+            .iter()
+            .any(|keyword| !keyword.is_empty() && normalized_query.contains(keyword.as_str()))
+    }
+}
+
+/// Returns every command available on this platform, followed by the user's custom commands.
+pub fn registry() -> Vec<SystemCommand> {
+    build_registry(&settings::get().custom_commands)
+}
+
+pub fn run(id: &str, confirmed: bool) -> Result<RunOutcome, String> {
+    let Some(command) = registry().into_iter().find(|command| command.id == id) else {
+        return Err(format!("Unknown system command: {}", id));
+    };
+    if command.requires_confirmation && !confirmed {
+        return Ok(RunOutcome::NeedsConfirmation {
+            message: format!("Are you sure you want to run \"{}\"?", command.title),
+        });
+    }
+    match command.action {
+        CommandAction::Frontend => {
+            return Err(format!("System command {} is handled by the UI", id));
+        }
+        CommandAction::Builtin(runner) => runner()?,
+        CommandAction::Shell(command_line) => run_shell(&command_line)?,
+    }
+    Ok(RunOutcome::Done)
+}
+
+fn build_registry(custom_commands: &[CustomCommand]) -> Vec<SystemCommand> {
+    let mut commands = vec![SystemCommand {
+        id: "settings".into(),
+        title: "Genie Settings".into(),
+        subtitle: "Configure hotkey, theme, and more".into(),
+        icon: "settings".into(),
+        keywords: [
+            "setting",
+            "theme",
+            "hotkey",
+            "shortcut",
+            "config",
+            "preference",
+        ]
+        .iter()
+        .map(|k| k.to_string())
+        .collect(),
+        requires_confirmation: false,
+        searchable: true,
+        action: CommandAction::Frontend,
+    }];
+
+    for spec in BUILTINS {
+        let Some(runner) = builtin_runner(spec.id) else {
+            continue;
+        };
+        commands.push(SystemCommand {
+            id: spec.id.into(),
+            title: spec.title.into(),
+            subtitle: spec.subtitle.into(),
+            icon: spec.icon.into(),
+            keywords: spec.keywords.iter().map(|k| k.to_string()).collect(),
+            requires_confirmation: spec.requires_confirmation,
+            searchable: spec.searchable,
+            action: CommandAction::Builtin(runner),
+        });
+    }
+
+    for custom in custom_commands {
+        if custom.id.trim().is_empty() || custom.command.trim().is_empty() {
+            continue;
+        }
+        commands.push(SystemCommand {
+            id: format!("{}{}", CUSTOM_PREFIX, custom.id.trim()),
+            title: custom.title.clone(),
+            subtitle: "Custom command".into(),
+            icon: "terminal".into(),
+            keywords: custom
+                .keywords
+                .iter()
+                .map(|k| k.trim().to_lowercase())
+                .collect(),
+            requires_confirmation: custom.requires_confirmation,
+            searchable: true,
+            action: CommandAction::Shell(custom.command.clone()),
+        });
+    }
+    commands
+}
+
+fn builtin_runner(id: &str) -> Option<Runner> {
+    let runner: Runner = match id {
+        "spotify_play" => || run_spotify_command("play"),
+        "spotify_pause" => || run_spotify_command("pause"),
+        "spotify_next" => || run_spotify_command("next track"),
+        "spotify_prev" => || run_spotify_command("previous track"),
+        _ => return platform::runner(id),
+    };
+    Some(runner)
+}
+
+fn spawn(program: &str, args: &[&str]) -> Result<(), String> {
+    std::process::Command::new(program)
+        .args(args)
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn run_shell(command_line: &str) -> Result<(), String> {
+    spawn("cmd", &["/C", command_line])
+}
+
+#[cfg(not(target_os = "windows"))]
+fn run_shell(command_line: &str) -> Result<(), String> {
+    spawn("sh", &["-c", command_line])
+}
+
+#[cfg(target_os = "macos")]
+fn run_spotify_command(command: &str) -> Result<(), String> {
+    spawn(
+        "osascript",
+        &[
+            "-e",
+            &format!("tell application \"Spotify\" to {}", command),
+        ],
+    )
+}
+
+#[cfg(not(target_os = "macos"))]
+fn run_spotify_command(_command: &str) -> Result<(), String> {
+    Err("Spotify controls are currently supported only on macOS".into())
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{spawn, Runner};
+
+    pub fn runner(id: &str) -> Option<Runner> {
+        let runner: Runner = match id {
+            "sleep" => || spawn("pmset", &["sleepnow"]),
+            "lock" => || {
+                osascript("tell application \"System Events\" to keystroke \"q\" using {command down, control down}")
+            },
+            "shutdown" => || osascript("tell application \"System Events\" to shut down"),
+            "restart" => || osascript("tell application \"System Events\" to restart"),
+            "logout" => || osascript("tell application \"System Events\" to log out"),
+            "empty_trash" => || osascript("tell application \"Finder\" to empty trash"),
+            "toggle_dark_mode" => || {
+                osascript("tell application \"System Events\" to tell appearance preferences to set dark mode to not dark mode")
+            },
+            "eject" => || {
+                osascript(
+                    "tell application \"Finder\" to eject (every disk whose ejectable is true)",
+                )
+            },
+            _ => return None,
+        };
+        Some(runner)
+    }
+
+    fn osascript(script: &str) -> Result<(), String> {
+        spawn("osascript", &["-e", script])
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{spawn, Runner};
+
+    const TOGGLE_THEME_SCRIPT: &str = r#"$p = 'HKCU:\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize'
+$light = (Get-ItemProperty -Path $p -Name AppsUseLightTheme).AppsUseLightTheme
+Set-ItemProperty -Path $p -Name AppsUseLightTheme -Value (1 - $light)
+Set-ItemProperty -Path $p -Name SystemUsesLightTheme -Value (1 - $light)"#;
+
+    pub fn runner(id: &str) -> Option<Runner> {
+        let runner: Runner = match id {
+            "sleep" => || spawn("rundll32.exe", &["powrprof.dll,SetSuspendState", "0,1,0"]),
+            "lock" => || spawn("rundll32.exe", &["user32.dll,LockWorkStation"]),
+            "shutdown" => || spawn("shutdown", &["/s", "/t", "0"]),
+            "restart" => || spawn("shutdown", &["/r", "/t", "0"]),
+            "logout" => || spawn("shutdown", &["/l"]),
+            "empty_trash" => || powershell("Clear-RecycleBin -Force"),
+            "toggle_dark_mode" => || powershell(TOGGLE_THEME_SCRIPT),
+            _ => return None,
+        };
+        Some(runner)
+    }
+
+    fn powershell(script: &str) -> Result<(), String> {
+        spawn("powershell", &["-NoProfile", "-Command", script])
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{spawn, Runner};
+
+    const GNOME_INTERFACE_SCHEMA: &str = "org.gnome.desktop.interface";
+
+    pub fn runner(id: &str) -> Option<Runner> {
+        let runner: Runner = match id {
+            "sleep" => || spawn("systemctl", &["suspend"]),
+            "lock" => || spawn("loginctl", &["lock-session"]),
+            "shutdown" => || spawn("systemctl", &["poweroff"]),
+            "restart" => || spawn("systemctl", &["reboot"]),
+            "logout" => log_out,
+            "empty_trash" => || spawn("gio", &["trash", "--empty"]),
+            "toggle_dark_mode" => toggle_dark_mode,
+            "eject" => eject_volumes,
+            _ => return None,
+        };
+        Some(runner)
+    }
+
+    fn log_out() -> Result<(), String> {
+        let session = std::env::var("XDG_SESSION_ID")
+            .map_err(|_| "No login session found to log out of".to_string())?;
+        spawn("loginctl", &["terminate-session", &session])
+    }
+
+    fn toggle_dark_mode() -> Result<(), String> {
+        let output = std::process::Command::new("gsettings")
+            .args(["get", GNOME_INTERFACE_SCHEMA, "color-scheme"])
+            .output()
+            .map_err(|e| e.to_string())?;
+        let current = String::from_utf8_lossy(&output.stdout);
+        let next = if current.contains("prefer-dark") {
+            "default"
+        } else {
+            "prefer-dark"
+        };
+        spawn(
+            "gsettings",
+            &["set", GNOME_INTERFACE_SCHEMA, "color-scheme", next],
+        )
+    }
+
+    fn eject_volumes() -> Result<(), String> {
+        let mounts = std::fs::read_to_string("/proc/mounts").map_err(|e| e.to_string())?;
+        let mount_points = removable_mount_points(&mounts);
+        if mount_points.is_empty() {
+            return Err("No removable volumes are mounted".into());
+        }
+        for mount_point in mount_points {
+            spawn("gio", &["mount", "--eject", &mount_point])?;
+        }
+        Ok(())
+    }
+
+    /// Mount points created by udisks for removable media (`/run/media/<user>/…`, `/media/…`).
+    pub(super) fn removable_mount_points(mounts: &str) -> Vec<String> {
+        mounts
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .map(|raw| raw.replace("\\040", " "))
+            .filter(|mount_point| {
+                mount_point.starts_with("/run/media/") || mount_point.starts_with("/media/")
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(id: &str, keywords: &[&str], requires_confirmation: bool) -> CustomCommand {
+        CustomCommand {
+            id: id.into(),
+            title: format!("Run {}", id),
+            command: "true".into(),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            requires_confirmation,
+        }
+    }
+
+    #[test]
+    fn registry_includes_settings_and_custom_commands() {
+        let registry =
+            build_registry(&[custom("backup", &["Backup"], false), custom("", &[], false)]);
+        assert!(registry.iter().any(|command| command.id == "settings"));
+        let backup = registry
+            .iter()
+            .find(|command| command.id == "custom:backup")
+            .expect("custom command should be registered");
+        assert!(backup.matches_keyword("run backup now"));
+        assert!(!registry.iter().any(|command| command.id == "custom:"));
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    fn destructive_builtins_require_confirmation() {
+        let registry = build_registry(&[]);
+        for id in ["shutdown", "restart", "logout", "empty_trash"] {
+            let command = registry
+                .iter()
+                .find(|command| command.id == id)
+                .unwrap_or_else(|| panic!("{id} should be registered"));
+            assert!(
+                command.requires_confirmation,
+                "{id} should require confirmation"
+            );
+        }
+        let lock = registry
+            .iter()
+            .find(|command| command.id == "lock")
+            .unwrap();
+        assert!(!lock.requires_confirmation);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn removable_mounts_are_detected() {
+        let mounts = "/dev/nvme0n1p2 / ext4 rw 0 0\n/dev/sdb1 /run/media/ada/USB\\040Stick vfat rw 0 0\ntmpfs /run/user/1000 tmpfs rw 0 0\n";
+        assert_eq!(
+            platform::removable_mount_points(mounts),
+            vec!["/run/media/ada/USB Stick".to_string()]
+        );
+    }
+}
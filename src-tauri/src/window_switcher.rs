@@ -0,0 +1,412 @@
+#[cfg(target_os = "linux")]
+use std::collections::HashMap;
+#[cfg(target_os = "linux")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_os = "linux")]
+use std::sync::{Mutex, OnceLock};
+#[cfg(target_os = "linux")]
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "linux")]
+const SNAPSHOT_TTL: Duration = Duration::from_millis(1_500);
+
+#[derive(Clone, Debug)]
+pub struct OpenWindow {
+    pub id: String,
+    #[cfg(target_os = "linux")]
+    pub pid: u32,
+    #[cfg(target_os = "linux")]
+    pub wm_class: String,
+    pub title: String,
+}
+
+/// Returns the open top-level windows that belong to the application at `app_path`.
+/// Served from the last snapshot and the identities it resolved, so typing never waits
+/// on `wmctrl` or reads files; anything stale or missing is filled in the background.
+#[cfg(target_os = "linux")]
+pub fn windows_for_app(app_path: &str) -> Vec<OpenWindow> {
+    let Some(identity) = cached_identity(app_path) else {
+        return Vec::new();
+    };
+    if identity.exec_name.is_none() && identity.wm_class.is_none() {
+        return Vec::new();
+    }
+
+    let Some(snapshot) = cached_snapshot() else {
+        return Vec::new();
+    };
+    let running_pids: Vec<u32> = match identity.exec_name.as_deref() {
+        Some(exec_name) => snapshot
+            .processes
+            .iter()
+            .filter(|process| process.name.eq_ignore_ascii_case(exec_name))
+            .map(|process| process.pid)
+            .collect(),
+        None => Vec::new(),
+    };
+
+    snapshot
+        .windows
+        .into_iter()
+        .filter(|window| {
+            running_pids.contains(&window.pid) || window_class_matches(window, &identity)
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn windows_for_app(_app_path: &str) -> Vec<OpenWindow> {
+    Vec::new()
+}
+
+/// Only window ids that are currently mapped may be activated.
+#[cfg(target_os = "linux")]
+pub fn is_open_window(window_id: &str) -> bool {
+    if !is_window_id(window_id) {
+        return false;
+    }
+    fresh_snapshot()
+        .windows
+        .iter()
+        .any(|window| window.id.eq_ignore_ascii_case(window_id))
+}
+
+/// Takes a new snapshot in the background unless one is already being taken, e.g.
+/// when the launcher opens so the first search already has windows to offer.
+#[cfg(target_os = "linux")]
+pub fn refresh() {
+    if REFRESHING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        fresh_snapshot();
+        resolve_identities();
+        REFRESHING.store(false, Ordering::SeqCst);
+    });
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn refresh() {}
+
+#[cfg(not(target_os = "linux"))]
+pub fn is_open_window(_window_id: &str) -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+pub fn focus_window(window_id: &str) -> Result<(), String> {
+    std::process::Command::new("wmctrl")
+        .args(["-i", "-a", window_id])
+        .spawn()
+        .map_err(|e| format!("Failed to switch window (is wmctrl installed?): {}", e))?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn focus_window(_window_id: &str) -> Result<(), String> {
+    Err("Window switching is currently supported only on Linux".into())
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Clone, Debug)]
+struct RunningProcess {
+    pid: u32,
+    name: String,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Clone, Debug)]
+struct Snapshot {
+    taken_at: Instant,
+    windows: Vec<OpenWindow>,
+    processes: Vec<RunningProcess>,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Clone, Debug, Default)]
+struct DesktopIdentity {
+    exec_name: Option<String>,
+    wm_class: Option<String>,
+}
+
+#[cfg(target_os = "linux")]
+static SNAPSHOT: OnceLock<Mutex<Option<Snapshot>>> = OnceLock::new();
+#[cfg(target_os = "linux")]
+static REFRESHING: AtomicBool = AtomicBool::new(false);
+/// How each searched `.desktop` file identifies its windows, keyed by path. `None`
+/// means the file has been asked about but not read yet.
+#[cfg(target_os = "linux")]
+static IDENTITIES: OnceLock<Mutex<HashMap<String, Option<DesktopIdentity>>>> = OnceLock::new();
+
+#[cfg(target_os = "linux")]
+fn identity_store() -> &'static Mutex<HashMap<String, Option<DesktopIdentity>>> {
+    IDENTITIES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+#[cfg(target_os = "linux")]
+fn cached_identity(app_path: &str) -> Option<DesktopIdentity> {
+    let mut identities = identity_store().lock().ok()?;
+    if let Some(identity) = identities.get(app_path) {
+        return identity.clone();
+    }
+    identities.insert(app_path.to_string(), None);
+    drop(identities);
+    refresh();
+    None
+}
+
+/// Re-reads every `.desktop` file searched so far, so edits and new ones are picked up
+/// with the next snapshot.
+#[cfg(target_os = "linux")]
+fn resolve_identities() {
+    let Some(paths) = identity_store()
+        .lock()
+        .ok()
+        .map(|identities| identities.keys().cloned().collect::<Vec<_>>())
+    else {
+        return;
+    };
+    let resolved: Vec<_> = paths
+        .into_iter()
+        .map(|path| {
+            let identity = std::fs::read_to_string(&path)
+                .map(|content| parse_desktop_identity(&content))
+                .unwrap_or_default();
+            (path, Some(identity))
+        })
+        .collect();
+    if let Ok(mut identities) = identity_store().lock() {
+        identities.extend(resolved);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn snapshot_store() -> &'static Mutex<Option<Snapshot>> {
+    SNAPSHOT.get_or_init(|| Mutex::new(None))
+}
+
+#[cfg(target_os = "linux")]
+fn cached_snapshot() -> Option<Snapshot> {
+    let cached = snapshot_store().lock().ok()?.clone();
+    if cached
+        .as_ref()
+        .is_none_or(|snapshot| snapshot.taken_at.elapsed() >= SNAPSHOT_TTL)
+    {
+        refresh();
+    }
+    cached
+}
+
+#[cfg(target_os = "linux")]
+fn fresh_snapshot() -> Snapshot {
+    let snapshot = take_snapshot();
+    if let Ok(mut guard) = snapshot_store().lock() {
+        *guard = Some(snapshot.clone());
+    }
+    snapshot
+}
+
+#[cfg(target_os = "linux")]
+fn take_snapshot() -> Snapshot {
+    Snapshot {
+        taken_at: Instant::now(),
+        windows: list_windows(),
+        processes: list_processes(),
+    }
+}
+
+/// Reads the EWMH client list through `wmctrl -lpx`; returns nothing when it is
+/// unavailable (e.g. Wayland sessions without XWayland clients).
+#[cfg(target_os = "linux")]
+fn list_windows() -> Vec<OpenWindow> {
+    let Ok(output) = std::process::Command::new("wmctrl")
+        .arg("-lpx")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .output()
+    else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_wmctrl_line)
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn list_processes() -> Vec<RunningProcess> {
+    let Ok(read_dir) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    read_dir
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            let cmdline = std::fs::read(entry.path().join("cmdline")).ok()?;
+            let program = cmdline.split(|b| *b == 0).next()?;
+            let name = std::path::Path::new(std::str::from_utf8(program).ok()?)
+                .file_name()?
+                .to_str()?
+                .to_string();
+            if name.is_empty() {
+                return None;
+            }
+            Some(RunningProcess { pid, name })
+        })
+        .collect()
+}
+
+/// Parses one `wmctrl -lpx` row: `<id> <desktop> <pid> <instance.class> <host> <title...>`.
+#[cfg(target_os = "linux")]
+fn parse_wmctrl_line(line: &str) -> Option<OpenWindow> {
+    let mut rest = line.trim_start();
+    let mut fields = Vec::with_capacity(5);
+    for _ in 0..5 {
+        let end = rest.find(char::is_whitespace)?;
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    let id = fields[0];
+    if !is_window_id(id) {
+        return None;
+    }
+    // NOTE: desktop -1 marks sticky panels/docks, which are not useful switch targets
+    if fields[1] == "-1" {
+        return None;
+    }
+    Some(OpenWindow {
+        id: id.to_string(),
+        pid: fields[2].parse().unwrap_or(0),
+        wm_class: fields[3].to_string(),
+        title: rest.trim_end().to_string(),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn parse_desktop_identity(content: &str) -> DesktopIdentity {
+    let mut identity = DesktopIdentity::default();
+    let mut in_main_section = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_main_section = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_main_section {
+            continue;
+        }
+        if let Some(exec) = line.strip_prefix("Exec=") {
+            identity.exec_name = exec_program_name(exec);
+        } else if let Some(class) = line.strip_prefix("StartupWMClass=") {
+            let class = class.trim();
+            if !class.is_empty() {
+                identity.wm_class = Some(class.to_string());
+            }
+        }
+    }
+    identity
+}
+
+/// Extracts the binary name from an `Exec=` value, skipping `env VAR=value` prefixes.
+/// Sandboxed launchers (flatpak, snap) cannot be matched by binary and yield `None`.
+#[cfg(target_os = "linux")]
+fn exec_program_name(exec: &str) -> Option<String> {
+    let mut tokens = exec
+        .split_whitespace()
+        .map(|token| token.trim_matches('"'))
+        .skip_while(|token| *token == "env" || (token.contains('=') && !token.starts_with('/')));
+    let program = tokens.next()?;
+    let name = std::path::Path::new(program).file_name()?.to_str()?;
+    if matches!(name, "flatpak" | "snap" | "sh" | "bash") || name.starts_with('%') {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn window_class_matches(window: &OpenWindow, identity: &DesktopIdentity) -> bool {
+    // NOTE: wmctrl prints WM_CLASS as `instance.class`, and either half may itself contain dots
+    let class = window.wm_class.to_lowercase();
+    [identity.wm_class.as_deref(), identity.exec_name.as_deref()]
+        .into_iter()
+        .flatten()
+        .map(str::to_lowercase)
+        .any(|candidate| {
+            class == candidate
+                || class.starts_with(&format!("{}.", candidate))
+                || class.ends_with(&format!(".{}", candidate))
+        })
+}
+
+#[cfg(target_os = "linux")]
+fn is_window_id(value: &str) -> bool {
+    value
+        .strip_prefix("0x")
+        .map(|hex| !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .unwrap_or(false)
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_wmctrl_rows_with_spaced_titles() {
+        let window = parse_wmctrl_line(
+            "0x03a00003  0 4242   Navigator.firefox     host Docs  —  Mozilla Firefox",
+        )
+        .expect("row should parse");
+        assert_eq!(window.id, "0x03a00003");
+        assert_eq!(window.pid, 4242);
+        assert_eq!(window.wm_class, "Navigator.firefox");
+        assert_eq!(window.title, "Docs  —  Mozilla Firefox");
+    }
+
+    #[test]
+    fn skips_sticky_and_malformed_rows() {
+        assert!(
+            parse_wmctrl_line("0x01e00004 -1 1200 xfce4-panel.Xfce4-panel host panel").is_none()
+        );
+        assert!(parse_wmctrl_line("garbage").is_none());
+    }
+
+    #[test]
+    fn desktop_identity_reads_main_section_only() {
+        let identity = parse_desktop_identity(
+            "[Desktop Entry]\nName=Code\nExec=env GDK_BACKEND=x11 /usr/share/code/code --unity %F\nStartupWMClass=Code\n\n[Desktop Action new-window]\nExec=/usr/bin/other\n",
+        );
+        assert_eq!(identity.exec_name.as_deref(), Some("code"));
+        assert_eq!(identity.wm_class.as_deref(), Some("Code"));
+    }
+
+    #[test]
+    fn identities_are_read_in_the_background() {
+        let path =
+            std::env::temp_dir().join(format!("genie-identity-{}.desktop", std::process::id()));
+        std::fs::write(&path, "[Desktop Entry]\nExec=/usr/bin/gedit %U\n").unwrap();
+        let path = path.to_string_lossy().to_string();
+        assert!(cached_identity(&path).is_none());
+        resolve_identities();
+        let identity = cached_identity(&path).expect("identity should be resolved");
+        assert_eq!(identity.exec_name.as_deref(), Some("gedit"));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn sandboxed_launchers_match_by_class_only() {
+        assert_eq!(exec_program_name("flatpak run org.gimp.GIMP %U"), None);
+        let window = OpenWindow {
+            id: "0x1".into(),
+            pid: 7,
+            wm_class: "gimp-2.10.Gimp-2.10".into(),
+            title: "GIMP".into(),
+        };
+        let identity = DesktopIdentity {
+            exec_name: None,
+            wm_class: Some("gimp-2.10".into()),
+        };
+        assert!(window_class_matches(&window, &identity));
+    }
+}
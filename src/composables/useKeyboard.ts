@@ -8,11 +8,13 @@ interface UseKeyboardOptions {
   query: Ref<string>;
   clear: () => void;
   onAction: (result: SearchResult) => void;
+  onPin?: (result: SearchResult) => void;
+  onDelete?: (result: SearchResult) => void;
 }
 
 export function useKeyboard(opts: UseKeyboardOptions) {
   function handleKeydown(e: KeyboardEvent) {
-    const { results, selectedIndex, query, clear, onAction } = opts;
+    const { results, selectedIndex, query, clear, onAction, onPin, onDelete } = opts;
 
     switch (e.key) {
       case "ArrowDown":
@@ -49,6 +51,15 @@ export function useKeyboard(opts: UseKeyboardOptions) {
         break;
     }
 
+    const selected = results.value[selectedIndex.value];
+    if ((e.metaKey || e.ctrlKey) && selected && (e.key === "p" || e.key === "d")) {
+      const handler = e.key === "p" ? onPin : onDelete;
+      if (handler) {
+        e.preventDefault();
+        handler(selected);
+      }
+    }
+
     if ((e.metaKey || e.ctrlKey) && e.key >= "1" && e.key <= "8") {
       e.preventDefault();
       const idx = parseInt(e.key) - 1;
@@ -73,6 +73,11 @@ export function useSearch() {
     }
   }
 
+  /** Re-runs the current query, e.g. after a result was pinned or deleted. */
+  async function refresh() {
+    await performSearch(query.value, matchKeyword(query.value), ++latestRequestId);
+  }
+
   function clear() {
     query.value = "";
     results.value = [];
@@ -87,6 +92,7 @@ export function useSearch() {
     isLoading,
     activeKeyword,
     clear,
+    refresh,
   };
 }
 
@@ -14,11 +14,19 @@ export type ResultCategory =
   | "CALC"
   | "WEB"
   | "SYS"
+  | "PROC"
+  | "CMD"
+  | "WIN"
   | "SPOTIFY"
   | "CURRENCY"
   | "CLIP"
+  | "SNIP"
   | "CONTACT";
 
+export type SystemCommandOutcome =
+  | { status: "done" }
+  | { status: "needs_confirmation"; message: string };
+
 export interface GeniePlugin {
   id: string;
   name: string;